@@ -0,0 +1,77 @@
+use super::prompts::PromptTemplate;
+use super::provider::AiProvider;
+use crate::db::queries;
+use chrono::Utc;
+use rusqlite::Connection;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+pub const TTL_SETTING: &str = "ai_cache_ttl_days";
+pub const DEFAULT_TTL_DAYS: u32 = 30;
+
+/// Days a cached response stays valid. `0` turns the cache off.
+pub fn ttl_days(conn: &Connection) -> u32 {
+    queries::get_setting(conn, TTL_SETTING)
+        .ok()
+        .flatten()
+        .and_then(|v| v.trim().parse().ok())
+        .unwrap_or(DEFAULT_TTL_DAYS)
+}
+
+/// Cache key for a response: which model answered, which version of which
+/// prompt asked, and a hash of everything else that went into the request.
+pub fn key(provider: &dyn AiProvider, template: &PromptTemplate, parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part.as_bytes());
+        // Separator so ("ab", "c") and ("a", "bc") hash differently
+        hasher.update([0]);
+    }
+    format!(
+        "{}:{}:{}@{}{}:{:x}",
+        provider.name(),
+        provider.model(),
+        template.name,
+        if template.is_default { "default-" } else { "v" },
+        template.version,
+        hasher.finalize()
+    )
+}
+
+pub fn get<T: DeserializeOwned>(conn: &Connection, key: &str) -> Option<T> {
+    let ttl = ttl_days(conn);
+    if ttl == 0 {
+        return None;
+    }
+    let value = queries::get_ai_cache(conn, key, ttl).ok().flatten()?;
+    serde_json::from_str(&value).ok()
+}
+
+pub fn put<T: Serialize>(conn: &Connection, key: &str, kind: &str, value: &T) {
+    if ttl_days(conn) == 0 {
+        return;
+    }
+    let json = match serde_json::to_string(value) {
+        Ok(j) => j,
+        Err(e) => {
+            eprintln!("Failed to serialize AI cache entry: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = queries::put_ai_cache(conn, key, kind, &json, &Utc::now().to_rfc3339()) {
+        eprintln!("Failed to write AI cache entry: {}", e);
+    }
+}
+
+pub fn prune(conn: &Connection) {
+    let ttl = ttl_days(conn);
+    let result = if ttl == 0 {
+        queries::clear_ai_cache(conn)
+    } else {
+        queries::prune_ai_cache(conn, ttl)
+    };
+    if let Err(e) = result {
+        eprintln!("Failed to prune AI cache: {}", e);
+    }
+}
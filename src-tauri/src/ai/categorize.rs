@@ -2,10 +2,15 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 use super::claude::ClaudeProvider;
+use super::gemini::GeminiProvider;
+use super::mock::MockProvider;
 use super::ollama::OllamaProvider;
 use super::openai::OpenAiProvider;
-use super::provider::{AiConfig, AiMessage, AiProvider};
+use super::prompts::{self, PromptTemplate};
+use super::provider::{AiConfig, AiMessage, AiProvider, TokenUsage};
 use super::retry::complete_with_retry;
+use crate::db::synonyms::TagSynonyms;
+use crate::db::vocabulary::Vocabulary;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CategorizationRequest {
@@ -19,6 +24,10 @@ pub struct CategorizationRequest {
     pub needs_title: bool,
     pub existing_tags: Vec<String>,
     pub existing_folders: Vec<String>,
+    #[serde(default)]
+    pub vocabulary: Vocabulary,
+    #[serde(default)]
+    pub tag_synonyms: TagSynonyms,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -32,39 +41,75 @@ pub struct CategorizationResult {
     pub title: Option<String>,
 }
 
-const VALID_TYPES: &[&str] = &["shell", "snippet", "config", "note"];
-const VALID_LANGUAGES: &[&str] = &[
-    "bash",
-    "javascript",
-    "typescript",
-    "python",
-    "rust",
-    "go",
-    "json",
-    "yaml",
-    "toml",
-    "sql",
-    "html",
-    "css",
-    "markdown",
-    "dockerfile",
-    "terraform",
-    "other",
-];
-
-pub fn create_provider(config: &AiConfig) -> Arc<dyn AiProvider> {
-    match config.provider.as_str() {
+impl CategorizationRequest {
+    /// The requested fields, e.g. "type,tags,description".
+    pub fn requested_fields(&self) -> String {
+        [
+            (self.needs_type, "type"),
+            (self.needs_language, "language"),
+            (self.needs_tags, "tags"),
+            (self.needs_folder, "folder"),
+            (self.needs_description, "description"),
+            (self.needs_title, "title"),
+        ]
+        .iter()
+        .filter(|(needed, _)| *needed)
+        .map(|(_, name)| *name)
+        .collect::<Vec<_>>()
+        .join(",")
+    }
+}
+
+impl CategorizationResult {
+    pub fn is_empty(&self) -> bool {
+        self.item_type.is_none()
+            && self.language.is_none()
+            && self.tags.is_none()
+            && self.folder.is_none()
+            && self.description.is_none()
+            && self.title.is_none()
+    }
+}
+
+fn type_choices(vocabulary: &Vocabulary) -> String {
+    vocabulary
+        .types
+        .iter()
+        .map(|t| format!("\"{}\" ({})", t.name, t.label))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn language_choices(vocabulary: &Vocabulary) -> String {
+    vocabulary
+        .languages
+        .iter()
+        .map(|l| format!("\"{}\"", l))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub fn create_provider(config: &AiConfig) -> Result<Arc<dyn AiProvider>, String> {
+    Ok(match config.provider.as_str() {
+        "openai" => Arc::new(OpenAiProvider::new(config)),
+        "azure_openai" => Arc::new(OpenAiProvider::azure(config)?),
+        "openai_compatible" => Arc::new(OpenAiProvider::compatible(config)?),
         "claude" => Arc::new(ClaudeProvider::new(config)),
+        "gemini" => Arc::new(GeminiProvider::new(config)),
         "ollama" => Arc::new(OllamaProvider::new(config)),
-        _ => Arc::new(OpenAiProvider::new(config)),
-    }
+        "mock" => Arc::new(MockProvider::new(config)),
+        other => return Err(format!("Unknown AI provider: {}", other)),
+    })
 }
 
-pub async fn categorize(
-    provider: &dyn AiProvider,
-    request: &CategorizationRequest,
-) -> Result<CategorizationResult, String> {
-    let system_prompt = build_system_prompt(request);
+/// Local servers usually run without a key.
+pub fn requires_api_key(provider: &str) -> bool {
+    !matches!(provider, "ollama" | "openai_compatible" | "mock")
+}
+
+/// The system and user messages for categorizing one item.
+pub fn build_messages(request: &CategorizationRequest, template: &PromptTemplate) -> Vec<AiMessage> {
+    let system_prompt = build_system_prompt(request, template);
 
     let content_preview: String = request.content.chars().take(4000).collect();
     let user_content = format!(
@@ -72,7 +117,7 @@ pub async fn categorize(
         request.title, content_preview
     );
 
-    let messages = vec![
+    vec![
         AiMessage {
             role: "system".to_string(),
             content: system_prompt,
@@ -81,39 +126,37 @@ pub async fn categorize(
             role: "user".to_string(),
             content: user_content,
         },
-    ];
+    ]
+}
 
+pub async fn categorize(
+    provider: &dyn AiProvider,
+    request: &CategorizationRequest,
+    template: &PromptTemplate,
+    usage: &mut TokenUsage,
+) -> Result<CategorizationResult, String> {
+    let messages = build_messages(request, template);
     let response = complete_with_retry(provider, messages).await?;
+    if let Some(ref u) = response.usage {
+        usage.add(u);
+    }
     parse_response(&response.content, request)
 }
 
-fn build_system_prompt(request: &CategorizationRequest) -> String {
-    let mut prompt = String::from(
-        "You are a categorization assistant for a developer knowledge base. \
-         Analyze the given item and return a JSON object with ONLY the requested fields.\n\n",
-    );
-
+fn build_system_prompt(request: &CategorizationRequest, template: &PromptTemplate) -> String {
     let mut fields = Vec::new();
 
     if request.needs_type {
         fields.push(format!(
             "\"type\": one of [{}]",
-            VALID_TYPES
-                .iter()
-                .map(|t| format!("\"{}\"", t))
-                .collect::<Vec<_>>()
-                .join(", ")
+            type_choices(&request.vocabulary)
         ));
     }
 
     if request.needs_language {
         fields.push(format!(
             "\"language\": one of [{}] or empty string if not code",
-            VALID_LANGUAGES
-                .iter()
-                .map(|l| format!("\"{}\"", l))
-                .collect::<Vec<_>>()
-                .join(", ")
+            language_choices(&request.vocabulary)
         ));
     }
 
@@ -123,13 +166,7 @@ fn build_system_prompt(request: &CategorizationRequest) -> String {
         if !request.existing_tags.is_empty() {
             tag_instruction.push_str(&format!(
                 ". Prefer reusing from existing tags: [{}]",
-                request
-                    .existing_tags
-                    .iter()
-                    .take(50)
-                    .map(|t| format!("\"{}\"", t))
-                    .collect::<Vec<_>>()
-                    .join(", ")
+                prompts::quoted_list(&request.existing_tags)
             ));
         }
         fields.push(tag_instruction);
@@ -139,13 +176,7 @@ fn build_system_prompt(request: &CategorizationRequest) -> String {
         let folder_instruction = if !request.existing_folders.is_empty() {
             format!(
                 "\"folder\": MUST be one of these folders: [{}]. Do NOT invent new folders",
-                request
-                    .existing_folders
-                    .iter()
-                    .take(50)
-                    .map(|f| format!("\"{}\"", f))
-                    .collect::<Vec<_>>()
-                    .join(", ")
+                prompts::quoted_list(&request.existing_folders)
             )
         } else {
             "\"folder\": a hierarchical path like \"/category\" or \"/category/subcategory\""
@@ -166,16 +197,17 @@ fn build_system_prompt(request: &CategorizationRequest) -> String {
         );
     }
 
-    prompt.push_str("Return a JSON object with these fields:\n");
-    for field in &fields {
-        prompt.push_str(&format!("- {}\n", field));
-    }
-
-    prompt.push_str("\nReturn ONLY valid JSON, no markdown fences, no explanation.");
-    prompt
+    let fields: String = fields.iter().map(|f| format!("- {}\n", f)).collect();
+    template.render(&[
+        ("fields", fields),
+        ("types", type_choices(&request.vocabulary)),
+        ("languages", language_choices(&request.vocabulary)),
+        ("existing_tags", prompts::quoted_list(&request.existing_tags)),
+        ("existing_folders", prompts::quoted_list(&request.existing_folders)),
+    ])
 }
 
-fn parse_response(
+pub fn parse_response(
     raw: &str,
     request: &CategorizationRequest,
 ) -> Result<CategorizationResult, String> {
@@ -196,7 +228,7 @@ fn parse_response(
 
     if request.needs_type {
         if let Some(t) = parsed.get("type").and_then(|v| v.as_str()) {
-            if VALID_TYPES.contains(&t) {
+            if request.vocabulary.has_type(t) {
                 result.item_type = Some(t.to_string());
             }
         }
@@ -204,7 +236,7 @@ fn parse_response(
 
     if request.needs_language {
         if let Some(l) = parsed.get("language").and_then(|v| v.as_str()) {
-            if l.is_empty() || VALID_LANGUAGES.contains(&l) {
+            if l.is_empty() || request.vocabulary.has_language(l) {
                 result.language = Some(l.to_string());
             }
         }
@@ -217,6 +249,7 @@ fn parse_response(
                 .filter_map(|v| v.as_str().map(|s| s.to_lowercase()))
                 .take(5)
                 .collect();
+            let tags = request.tag_synonyms.normalize(&tags);
             if !tags.is_empty() {
                 result.tags = Some(tags);
             }
@@ -254,80 +287,54 @@ fn parse_response(
     Ok(result)
 }
 
-/// Categorize multiple items in a single AI call. Each item is a (title, content) pair.
-/// Returns one CategorizationResult per item, in the same order.
-pub async fn categorize_batch(
-    provider: &dyn AiProvider,
+/// The system and user messages for categorizing several (title, content) items at once.
+pub fn build_batch_messages(
     items: &[(String, String)],
     existing_tags: &[String],
     existing_folders: &[String],
-) -> Result<Vec<CategorizationResult>, String> {
-    if items.is_empty() {
-        return Ok(Vec::new());
-    }
-
-    let mut system_prompt = String::from(
-        "You are a categorization assistant for a developer knowledge base. \
-         You will receive multiple items. For EACH item, return a categorization object.\n\n\
-         Each object must have these fields:\n",
-    );
-
-    system_prompt.push_str(&format!(
+    vocabulary: &Vocabulary,
+    template: &PromptTemplate,
+) -> Vec<AiMessage> {
+    let mut fields = format!(
         "- \"type\": one of [{}]\n",
-        VALID_TYPES
-            .iter()
-            .map(|t| format!("\"{}\"", t))
-            .collect::<Vec<_>>()
-            .join(", ")
-    ));
-    system_prompt.push_str(&format!(
+        type_choices(vocabulary)
+    );
+    fields.push_str(&format!(
         "- \"language\": one of [{}] or empty string if not code\n",
-        VALID_LANGUAGES
-            .iter()
-            .map(|l| format!("\"{}\"", l))
-            .collect::<Vec<_>>()
-            .join(", ")
+        language_choices(vocabulary)
     ));
 
     let mut tag_instruction = "- \"tags\": array of 1-5 short lowercase tags".to_string();
     if !existing_tags.is_empty() {
         tag_instruction.push_str(&format!(
             ". Prefer reusing from: [{}]",
-            existing_tags
-                .iter()
-                .take(50)
-                .map(|t| format!("\"{}\"", t))
-                .collect::<Vec<_>>()
-                .join(", ")
+            prompts::quoted_list(existing_tags)
         ));
     }
-    system_prompt.push_str(&tag_instruction);
-    system_prompt.push('\n');
+    fields.push_str(&tag_instruction);
+    fields.push('\n');
 
     let folder_instruction = if !existing_folders.is_empty() {
         format!(
             "- \"folder\": MUST be one of these folders: [{}]. Do NOT invent new folders.",
-            existing_folders
-                .iter()
-                .take(50)
-                .map(|f| format!("\"{}\"", f))
-                .collect::<Vec<_>>()
-                .join(", ")
+            prompts::quoted_list(existing_folders)
         )
     } else {
         "- \"folder\": a hierarchical path like \"/category\" or \"/category/subcategory\""
             .to_string()
     };
-    system_prompt.push_str(&folder_instruction);
-    system_prompt.push('\n');
+    fields.push_str(&folder_instruction);
+    fields.push('\n');
 
-    system_prompt
-        .push_str("- \"description\": a concise one-line summary (max 100 chars)\n");
+    fields.push_str("- \"description\": a concise one-line summary (max 100 chars)\n");
 
-    system_prompt.push_str(
-        "\nReturn a JSON array of objects, one per item, in the same order as the input. \
-         Return ONLY valid JSON, no markdown fences, no explanation.",
-    );
+    let system_prompt = template.render(&[
+        ("fields", fields),
+        ("types", type_choices(vocabulary)),
+        ("languages", language_choices(vocabulary)),
+        ("existing_tags", prompts::quoted_list(existing_tags)),
+        ("existing_folders", prompts::quoted_list(existing_folders)),
+    ]);
 
     // Build user message with numbered items
     let mut user_content = format!("Categorize these {} items:\n\n", items.len());
@@ -336,7 +343,7 @@ pub async fn categorize_batch(
         user_content.push_str(&format!("[{}] Title: {}\nContent: {}\n\n", i, title, preview));
     }
 
-    let messages = vec![
+    vec![
         AiMessage {
             role: "system".to_string(),
             content: system_prompt,
@@ -345,15 +352,37 @@ pub async fn categorize_batch(
             role: "user".to_string(),
             content: user_content,
         },
-    ];
+    ]
+}
+
+/// Categorize multiple items in a single AI call. Each item is a (title, content) pair.
+/// Returns one CategorizationResult per item, in the same order.
+pub async fn categorize_batch(
+    provider: &dyn AiProvider,
+    items: &[(String, String)],
+    existing_tags: &[String],
+    existing_folders: &[String],
+    vocabulary: &Vocabulary,
+    template: &PromptTemplate,
+    usage: &mut TokenUsage,
+) -> Result<Vec<CategorizationResult>, String> {
+    if items.is_empty() {
+        return Ok(Vec::new());
+    }
 
+    let messages =
+        build_batch_messages(items, existing_tags, existing_folders, vocabulary, template);
     let response = complete_with_retry(provider, messages).await?;
-    parse_batch_response(&response.content, items.len())
+    if let Some(ref u) = response.usage {
+        usage.add(u);
+    }
+    parse_batch_response(&response.content, items.len(), vocabulary)
 }
 
-fn parse_batch_response(
+pub fn parse_batch_response(
     raw: &str,
     expected_count: usize,
+    vocabulary: &Vocabulary,
 ) -> Result<Vec<CategorizationResult>, String> {
     let json_str = raw.trim();
     let json_str = if json_str.starts_with("```") {
@@ -376,13 +405,13 @@ fn parse_batch_response(
         let mut result = CategorizationResult::default();
 
         if let Some(t) = entry.get("type").and_then(|v| v.as_str()) {
-            if VALID_TYPES.contains(&t) {
+            if vocabulary.has_type(t) {
                 result.item_type = Some(t.to_string());
             }
         }
 
         if let Some(l) = entry.get("language").and_then(|v| v.as_str()) {
-            if l.is_empty() || VALID_LANGUAGES.contains(&l) {
+            if l.is_empty() || vocabulary.has_language(l) {
                 result.language = Some(l.to_string());
             }
         }
@@ -424,3 +453,196 @@ fn parse_batch_response(
 
     Ok(results)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::mock::{MockProvider, MockReply};
+    use crate::ai::prompts::PromptKind;
+
+    fn request(needs_all: bool) -> CategorizationRequest {
+        CategorizationRequest {
+            title: "List containers".to_string(),
+            content: "docker ps -a".to_string(),
+            needs_type: true,
+            needs_language: true,
+            needs_tags: true,
+            needs_folder: true,
+            needs_description: true,
+            needs_title: needs_all,
+            existing_tags: vec!["docker".to_string()],
+            existing_folders: vec!["/docker".to_string()],
+            vocabulary: Vocabulary::default(),
+            tag_synonyms: TagSynonyms::default(),
+        }
+    }
+
+    #[test]
+    fn parses_a_full_response() {
+        let raw = r#"{"type": "shell", "language": "bash", "tags": ["Docker", "containers"],
+            "folder": "/docker", "description": "List all containers", "title": "  docker ps  "}"#;
+
+        let result = parse_response(raw, &request(true)).unwrap();
+
+        assert_eq!(result.item_type.as_deref(), Some("shell"));
+        assert_eq!(result.language.as_deref(), Some("bash"));
+        assert_eq!(
+            result.tags,
+            Some(vec!["docker".to_string(), "containers".to_string()])
+        );
+        assert_eq!(result.folder.as_deref(), Some("/docker"));
+        assert_eq!(result.description.as_deref(), Some("List all containers"));
+        assert_eq!(result.title.as_deref(), Some("docker ps"));
+    }
+
+    #[test]
+    fn strips_markdown_fences() {
+        let raw = "```json\n{\"type\": \"config\"}\n```";
+
+        let result = parse_response(raw, &request(false)).unwrap();
+
+        assert_eq!(result.item_type.as_deref(), Some("config"));
+    }
+
+    #[test]
+    fn drops_values_outside_the_vocabulary() {
+        let raw = r#"{"type": "recipe", "language": "cobol"}"#;
+
+        let result = parse_response(raw, &request(false)).unwrap();
+
+        assert_eq!(result.item_type, None);
+        assert_eq!(result.language, None);
+    }
+
+    #[test]
+    fn only_fills_requested_fields() {
+        let mut req = request(false);
+        req.needs_type = false;
+        req.needs_tags = false;
+        let raw = r#"{"type": "shell", "tags": ["docker"], "title": "Renamed", "language": ""}"#;
+
+        let result = parse_response(raw, &req).unwrap();
+
+        assert_eq!(result.item_type, None);
+        assert_eq!(result.tags, None);
+        assert_eq!(result.title, None);
+        assert_eq!(result.language.as_deref(), Some(""));
+    }
+
+    #[test]
+    fn sanitizes_folders_and_limits_tags_and_descriptions() {
+        let raw = format!(
+            r#"{{"folder": "..\\ops\\docker", "tags": ["a", "b", "c", "d", "e", "f"],
+                "description": "{}"}}"#,
+            "x".repeat(150)
+        );
+
+        let result = parse_response(&raw, &request(false)).unwrap();
+
+        assert_eq!(result.folder.as_deref(), Some("/ops/docker"));
+        assert_eq!(result.tags.map(|t| t.len()), Some(5));
+        assert_eq!(result.description.map(|d| d.chars().count()), Some(100));
+    }
+
+    #[test]
+    fn maps_tags_through_synonyms() {
+        let mut req = request(false);
+        req.tag_synonyms = serde_json::from_str(r#"{"k8s": "kubernetes"}"#).unwrap();
+        let raw = r#"{"tags": ["K8s", "kubernetes", "helm"]}"#;
+
+        let result = parse_response(raw, &req).unwrap();
+
+        assert_eq!(
+            result.tags,
+            Some(vec!["kubernetes".to_string(), "helm".to_string()])
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_json() {
+        assert!(parse_response("not json", &request(false)).is_err());
+    }
+
+    #[test]
+    fn pads_short_batch_responses() {
+        let raw = r#"[{"type": "shell", "tags": ["git"]}, {"type": "unknown"}]"#;
+
+        let results = parse_batch_response(raw, 3, &Vocabulary::default()).unwrap();
+
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].item_type.as_deref(), Some("shell"));
+        assert_eq!(results[0].tags, Some(vec!["git".to_string()]));
+        assert_eq!(results[1].item_type, None);
+        assert!(results[2].is_empty());
+    }
+
+    #[test]
+    fn ignores_extra_batch_entries() {
+        let raw = "```json\n[{\"folder\": \"git\"}, {\"folder\": \"/docker\"}]\n```";
+
+        let results = parse_batch_response(raw, 1, &Vocabulary::default()).unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].folder.as_deref(), Some("/git"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn categorize_sends_the_item_and_adds_up_usage() {
+        let mock = MockProvider::with_script(
+            "mock",
+            vec![
+                MockReply::ServerError {
+                    message: "busy".to_string(),
+                },
+                MockReply::Content {
+                    content: r#"{"type": "shell", "language": "bash"}"#.to_string(),
+                },
+            ],
+        );
+        let template = PromptTemplate::default_for(PromptKind::Categorize);
+        let mut usage = TokenUsage::default();
+
+        let result = categorize(&mock, &request(false), &template, &mut usage)
+            .await
+            .unwrap();
+
+        assert_eq!(result.item_type.as_deref(), Some("shell"));
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1][0].role, "system");
+        assert!(requests[1][0].content.contains("\"docker\""));
+        assert!(requests[1][1].content.contains("docker ps -a"));
+        // Only the successful call reports usage
+        assert!(!usage.is_empty());
+    }
+
+    #[tokio::test]
+    async fn categorize_batch_numbers_items_in_one_request() {
+        let mock = MockProvider::replying(r#"[{"type": "shell"}, {"type": "note"}]"#);
+        let template = PromptTemplate::default_for(PromptKind::CategorizeBatch);
+        let items = vec![
+            ("First".to_string(), "ls -la".to_string()),
+            ("Second".to_string(), "Some notes".to_string()),
+        ];
+        let mut usage = TokenUsage::default();
+
+        let results = categorize_batch(
+            &mock,
+            &items,
+            &[],
+            &[],
+            &Vocabulary::default(),
+            &template,
+            &mut usage,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[1].item_type.as_deref(), Some("note"));
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0][1].content.contains("First"));
+        assert!(requests[0][1].content.contains("Second"));
+    }
+}
@@ -3,7 +3,9 @@ use reqwest::Client;
 use serde_json::json;
 use std::time::Duration;
 
-use super::provider::{AiConfig, AiError, AiMessage, AiProvider, AiResponse};
+use super::provider::{
+    model_ids, status_error, AiConfig, AiError, AiMessage, AiProvider, AiResponse, TokenUsage,
+};
 
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
 
@@ -71,7 +73,7 @@ impl AiProvider for ClaudeProvider {
             .json(&body)
             .send()
             .await
-            .map_err(|e| AiError::ServerError(format!("Claude request failed: {}", e)))?;
+            .map_err(|e| AiError::Network(format!("Claude request failed: {}", e)))?;
 
         if !resp.status().is_success() {
             let status = resp.status().as_u16();
@@ -81,11 +83,7 @@ impl AiProvider for ClaudeProvider {
                 .and_then(|v| v.to_str().ok())
                 .and_then(|v| v.parse::<u64>().ok());
             let text = resp.text().await.unwrap_or_default();
-            return Err(match status {
-                429 => AiError::RateLimit { retry_after_secs: retry_after },
-                500..=599 => AiError::ServerError(format!("Claude API error {}: {}", status, text)),
-                _ => AiError::ClientError(format!("Claude API error {}: {}", status, text)),
-            });
+            return Err(status_error("Claude", status, retry_after, &text));
         }
 
         let data: serde_json::Value = resp
@@ -102,10 +100,44 @@ impl AiProvider for ClaudeProvider {
             .unwrap_or("")
             .to_string();
 
-        Ok(AiResponse { content })
+        let usage = data.get("usage").map(|u| TokenUsage {
+            input_tokens: u.get("input_tokens").and_then(|v| v.as_u64()).unwrap_or(0),
+            output_tokens: u.get("output_tokens").and_then(|v| v.as_u64()).unwrap_or(0),
+        });
+
+        Ok(AiResponse { content, usage })
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>, AiError> {
+        let url = format!("{}/v1/models?limit=1000", self.base_url.trim_end_matches('/'));
+        let resp = self
+            .client
+            .get(&url)
+            .header("x-api-key", &self.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .send()
+            .await
+            .map_err(|e| AiError::Network(format!("Claude request failed: {}", e)))?;
+
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let text = resp.text().await.unwrap_or_default();
+            return Err(status_error("Claude", status, None, &text));
+        }
+
+        let data: serde_json::Value = resp
+            .json()
+            .await
+            .map_err(|e| AiError::ClientError(format!("Claude response parse error: {}", e)))?;
+
+        Ok(model_ids(&data, "data", "id"))
     }
 
     fn name(&self) -> &str {
         "claude"
     }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
 }
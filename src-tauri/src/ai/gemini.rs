@@ -0,0 +1,187 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::json;
+use std::time::Duration;
+
+use super::provider::{
+    status_error, AiConfig, AiError, AiMessage, AiProvider, AiResponse, TokenUsage,
+};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+pub struct GeminiProvider {
+    client: Client,
+    api_key: String,
+    model: String,
+    base_url: String,
+}
+
+impl GeminiProvider {
+    pub fn new(config: &AiConfig) -> Self {
+        Self {
+            client: Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            api_key: config.api_key.clone(),
+            model: config.model.clone(),
+            base_url: config
+                .base_url
+                .clone()
+                .unwrap_or_else(|| "https://generativelanguage.googleapis.com".to_string()),
+        }
+    }
+}
+
+#[async_trait]
+impl AiProvider for GeminiProvider {
+    async fn complete(&self, messages: Vec<AiMessage>) -> Result<AiResponse, AiError> {
+        let url = format!(
+            "{}/v1beta/models/{}:generateContent",
+            self.base_url.trim_end_matches('/'),
+            self.model
+        );
+
+        // Gemini takes the system prompt separately and calls the assistant "model"
+        let mut system_text = String::new();
+        let mut contents: Vec<serde_json::Value> = Vec::new();
+
+        for msg in &messages {
+            if msg.role == "system" {
+                system_text = msg.content.clone();
+            } else {
+                let role = if msg.role == "assistant" {
+                    "model"
+                } else {
+                    "user"
+                };
+                contents.push(json!({
+                    "role": role,
+                    "parts": [{ "text": msg.content }],
+                }));
+            }
+        }
+
+        let mut body = json!({
+            "contents": contents,
+            "generationConfig": {
+                "temperature": 0.3,
+                "responseMimeType": "application/json",
+            },
+        });
+
+        if !system_text.is_empty() {
+            body["systemInstruction"] = json!({ "parts": [{ "text": system_text }] });
+        }
+
+        let resp = self
+            .client
+            .post(&url)
+            .header("x-goog-api-key", &self.api_key)
+            .header("Content-Type", "application/json")
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| AiError::Network(format!("Gemini request failed: {}", e)))?;
+
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let retry_after = resp
+                .headers()
+                .get("retry-after")
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse::<u64>().ok());
+            let text = resp.text().await.unwrap_or_default();
+            return Err(status_error("Gemini", status, retry_after, &text));
+        }
+
+        let data: serde_json::Value = resp
+            .json()
+            .await
+            .map_err(|e| AiError::ClientError(format!("Gemini response parse error: {}", e)))?;
+
+        let content = data
+            .get("candidates")
+            .and_then(|c| c.as_array())
+            .and_then(|arr| arr.first())
+            .and_then(|candidate| candidate.get("content"))
+            .and_then(|content| content.get("parts"))
+            .and_then(|parts| parts.as_array())
+            .map(|parts| {
+                parts
+                    .iter()
+                    .filter_map(|p| p.get("text").and_then(|t| t.as_str()))
+                    .collect::<String>()
+            })
+            .unwrap_or_default();
+
+        let usage = data.get("usageMetadata").map(|u| TokenUsage {
+            input_tokens: u
+                .get("promptTokenCount")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0),
+            output_tokens: u
+                .get("candidatesTokenCount")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0),
+        });
+
+        Ok(AiResponse { content, usage })
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>, AiError> {
+        let url = format!(
+            "{}/v1beta/models?pageSize=1000",
+            self.base_url.trim_end_matches('/')
+        );
+        let resp = self
+            .client
+            .get(&url)
+            .header("x-goog-api-key", &self.api_key)
+            .send()
+            .await
+            .map_err(|e| AiError::Network(format!("Gemini request failed: {}", e)))?;
+
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let text = resp.text().await.unwrap_or_default();
+            return Err(status_error("Gemini", status, None, &text));
+        }
+
+        let data: serde_json::Value = resp
+            .json()
+            .await
+            .map_err(|e| AiError::ClientError(format!("Gemini response parse error: {}", e)))?;
+
+        // Names come back as "models/gemini-2.0-flash"; only chat-capable ones are useful
+        let mut models: Vec<String> = data
+            .get("models")
+            .and_then(|m| m.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter(|m| {
+                        m.get("supportedGenerationMethods")
+                            .and_then(|v| v.as_array())
+                            .is_some_and(|methods| {
+                                methods
+                                    .iter()
+                                    .any(|v| v.as_str() == Some("generateContent"))
+                            })
+                    })
+                    .filter_map(|m| m.get("name").and_then(|v| v.as_str()))
+                    .map(|name| name.trim_start_matches("models/").to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        models.sort();
+        Ok(models)
+    }
+
+    fn name(&self) -> &str {
+        "gemini"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+}
@@ -0,0 +1,943 @@
+use super::categorize::{CategorizationRequest, CategorizationResult};
+use crate::db::queries::Item;
+use crate::db::vocabulary::BUILTIN_LANGUAGES;
+use std::collections::{HashMap, HashSet};
+
+/// What local heuristics could tell about a piece of content. `None` fields are
+/// left for AI enrichment to fill in.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Detection {
+    pub item_type: Option<String>,
+    pub language: Option<String>,
+    /// Tags implied by a file-type signature, e.g. `kubernetes` for a manifest.
+    pub tags: Vec<String>,
+}
+
+impl Detection {
+    fn new(item_type: &str, language: &str) -> Self {
+        Self {
+            item_type: Some(item_type.to_string()),
+            language: Some(language.to_string()),
+            tags: Vec::new(),
+        }
+    }
+
+    fn with_tag(mut self, tag: &str) -> Self {
+        self.tags.push(tag.to_string());
+        self
+    }
+}
+
+/// Common spellings of fenced-code info strings that aren't language names themselves.
+const LANGUAGE_ALIASES: &[(&str, &str)] = &[
+    ("sh", "bash"),
+    ("shell", "bash"),
+    ("zsh", "bash"),
+    ("console", "bash"),
+    ("js", "javascript"),
+    ("jsx", "javascript"),
+    ("ts", "typescript"),
+    ("tsx", "typescript"),
+    ("py", "python"),
+    ("rs", "rust"),
+    ("golang", "go"),
+    ("yml", "yaml"),
+    ("docker", "dockerfile"),
+    ("tf", "terraform"),
+    ("hcl", "terraform"),
+    ("md", "markdown"),
+    ("postgresql", "sql"),
+    ("mysql", "sql"),
+];
+
+/// Commands that, at the start of a short snippet, almost always mean a shell one-liner.
+const SHELL_COMMANDS: &[&str] = &[
+    "awk",
+    "brew",
+    "cargo",
+    "cat",
+    "cd",
+    "chmod",
+    "chown",
+    "cp",
+    "curl",
+    "df",
+    "dig",
+    "docker",
+    "du",
+    "echo",
+    "export",
+    "find",
+    "gcloud",
+    "git",
+    "go",
+    "grep",
+    "gunzip",
+    "head",
+    "helm",
+    "kill",
+    "kubectl",
+    "ln",
+    "ls",
+    "lsof",
+    "make",
+    "mkdir",
+    "mv",
+    "netstat",
+    "npm",
+    "npx",
+    "openssl",
+    "pip",
+    "ps",
+    "rm",
+    "rsync",
+    "scp",
+    "sed",
+    "ssh",
+    "sudo",
+    "systemctl",
+    "tail",
+    "tar",
+    "terraform",
+    "top",
+    "touch",
+    "wget",
+    "xargs",
+    "yarn",
+    "aws",
+    "az",
+    "pnpm",
+    "psql",
+    "mysql",
+    "redis-cli",
+    "nc",
+    "ping",
+    "journalctl",
+    "uv",
+    "python3",
+    "node",
+];
+
+/// Distinct substrings that suggest a language. A language needs at least
+/// `MIN_SIGNALS` different hits to be chosen. JavaScript comes before TypeScript
+/// so plain JS wins ties; TypeScript repeats the JS signals so its own extras tip it.
+const LANGUAGE_SIGNALS: &[(&str, &[&str])] = &[
+    (
+        "rust",
+        &[
+            "fn ",
+            "let mut ",
+            "impl ",
+            "pub fn",
+            "use std::",
+            "::new(",
+            "-> Result<",
+            "#[derive",
+            "&str",
+            "Vec<",
+        ],
+    ),
+    (
+        "python",
+        &[
+            "def ", "import ", "self.", "elif ", "print(", "__init__", "None", "lambda ", "):\n",
+        ],
+    ),
+    (
+        "go",
+        &[
+            "package ",
+            "func ",
+            ":= ",
+            "fmt.",
+            "err != nil",
+            "chan ",
+            "go func",
+            "interface{}",
+        ],
+    ),
+    (
+        "javascript",
+        &[
+            "const ",
+            "function ",
+            "=> ",
+            "require(",
+            "console.log",
+            "module.exports",
+            "let ",
+            "document.",
+        ],
+    ),
+    (
+        "typescript",
+        &[
+            "const ",
+            "function ",
+            "=> ",
+            "console.log",
+            "let ",
+            "interface ",
+            ": string",
+            ": number",
+            ": boolean",
+            "export type ",
+            "import type ",
+            "as const",
+        ],
+    ),
+    (
+        "sql",
+        &[
+            "SELECT ",
+            "INSERT INTO",
+            "CREATE TABLE",
+            "UPDATE ",
+            "DELETE FROM",
+            " WHERE ",
+            " JOIN ",
+            "ALTER TABLE",
+            "GROUP BY",
+        ],
+    ),
+    (
+        "html",
+        &[
+            "<!DOCTYPE",
+            "<html",
+            "<div",
+            "<body",
+            "<head",
+            "<span",
+            "<a href",
+            "</",
+        ],
+    ),
+    (
+        "css",
+        &[
+            "color:",
+            "margin:",
+            "padding:",
+            "display:",
+            "font-size:",
+            "@media",
+            "px;",
+            "border:",
+        ],
+    ),
+    (
+        "terraform",
+        &[
+            "resource \"",
+            "provider \"",
+            "variable \"",
+            "module \"",
+            "output \"",
+            "terraform {",
+            "data \"",
+        ],
+    ),
+    (
+        "bash",
+        &[
+            "#!/bin/", "echo ", "\nfi", "; then", "; do", "$(", "export ", "${",
+        ],
+    ),
+];
+
+const MIN_SIGNALS: usize = 2;
+
+const DOCKERFILE_INSTRUCTIONS: &[&str] = &[
+    "FROM ",
+    "RUN ",
+    "COPY ",
+    "ADD ",
+    "WORKDIR ",
+    "ENTRYPOINT ",
+    "CMD ",
+    "EXPOSE ",
+    "ENV ",
+    "ARG ",
+];
+
+/// Guess type and language of content without calling a model.
+pub fn detect(content: &str) -> Detection {
+    let text = content.trim();
+    if text.is_empty() {
+        return Detection::default();
+    }
+
+    if let Some((language, whole)) = fenced_language(text) {
+        // A fence inside prose tells us the language, but the item is still a note
+        return if whole {
+            Detection::new(type_for_language(language), language)
+        } else {
+            Detection {
+                language: Some(language.to_string()),
+                ..Detection::default()
+            }
+        };
+    }
+
+    if let Some(detection) = detect_signature(text) {
+        return detection;
+    }
+
+    if let Some(language) = detect_config(text) {
+        return Detection::new("config", language);
+    }
+
+    if let Some(interpreter) = shebang_interpreter(text) {
+        return match interpreter.as_str() {
+            "sh" | "bash" | "zsh" => Detection::new("shell", "bash"),
+            "python" | "python3" => Detection::new("snippet", "python"),
+            "node" => Detection::new("snippet", "javascript"),
+            _ => Detection::default(),
+        };
+    }
+
+    if looks_like_shell_command(text) {
+        return Detection::new("shell", "bash");
+    }
+
+    match detect_code_language(text) {
+        Some(language) => Detection::new(type_for_language(language), language),
+        None => Detection::default(),
+    }
+}
+
+fn type_for_language(language: &str) -> &'static str {
+    match language {
+        "bash" => "shell",
+        "json" | "yaml" | "toml" | "dockerfile" | "terraform" => "config",
+        "markdown" => "note",
+        _ => "snippet",
+    }
+}
+
+fn normalize_language(name: &str) -> Option<&'static str> {
+    let name = name.to_lowercase();
+    let name = LANGUAGE_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map(|(_, language)| *language)
+        .unwrap_or(&name);
+    BUILTIN_LANGUAGES.iter().copied().find(|l| *l == name)
+}
+
+/// The language named by the first fenced code block's info string, and whether
+/// that block is the whole content.
+fn fenced_language(text: &str) -> Option<(&'static str, bool)> {
+    let start = text.find("```")?;
+    let info = text[start + 3..].lines().next()?;
+    let language = normalize_language(info.split_whitespace().next()?)?;
+    let whole = start == 0 && text.len() > 3 && text.ends_with("```");
+    Some((language, whole))
+}
+
+/// Well-known file shapes that are recognisable from a line or two.
+fn detect_signature(text: &str) -> Option<Detection> {
+    let lines = significant_lines(text, '#');
+
+    if lines.iter().any(|l| l.starts_with("apiVersion:"))
+        && lines.iter().any(|l| l.starts_with("kind:"))
+    {
+        return Some(Detection::new("config", "yaml").with_tag("kubernetes"));
+    }
+
+    if lines.contains(&"[package]") && lines.iter().any(|l| l.starts_with("name = ")) {
+        return Some(Detection::new("config", "toml").with_tag("cargo"));
+    }
+
+    if looks_like_dockerfile(&lines) {
+        return Some(Detection::new("config", "dockerfile").with_tag("docker"));
+    }
+
+    None
+}
+
+fn detect_config(text: &str) -> Option<&'static str> {
+    if (text.starts_with('{') || text.starts_with('['))
+        && serde_json::from_str::<serde_json::Value>(text)
+            .is_ok_and(|v| v.is_object() || v.is_array())
+    {
+        return Some("json");
+    }
+
+    if looks_like_toml(text) {
+        return Some("toml");
+    }
+
+    // A single "Key: value" line is usually prose, so require some structure
+    let lines = significant_lines(text, '#');
+    if lines.len() >= 2
+        && lines.iter().any(|l| is_yaml_key_line(l))
+        && serde_yaml::from_str::<serde_yaml::Value>(text)
+            .is_ok_and(|v| v.is_mapping() || v.is_sequence())
+    {
+        return Some("yaml");
+    }
+
+    None
+}
+
+fn significant_lines(text: &str, comment: char) -> Vec<&str> {
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with(comment))
+        .collect()
+}
+
+fn is_key(s: &str) -> bool {
+    let s = s.trim().trim_matches('"');
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+fn is_yaml_key_line(line: &str) -> bool {
+    let line = line.trim_start_matches("- ");
+    match line.split_once(':') {
+        Some((key, rest)) => is_key(key) && (rest.is_empty() || rest.starts_with(' ')),
+        None => false,
+    }
+}
+
+fn looks_like_toml(text: &str) -> bool {
+    let lines = significant_lines(text, '#');
+    if lines.len() < 2 {
+        return false;
+    }
+
+    let mut has_table = false;
+    let mut matching = 0;
+    for line in &lines {
+        if line.starts_with('[')
+            && line.ends_with(']')
+            && is_key(line.trim_matches(|c| c == '[' || c == ']'))
+        {
+            has_table = true;
+            matching += 1;
+        } else if let Some((key, value)) = line.split_once(" = ") {
+            if is_key(key) && !value.trim().is_empty() {
+                matching += 1;
+            }
+        } else if line.starts_with(']') || line.starts_with('}') || line.ends_with(',') {
+            // Continuation of a multi-line array or inline table
+            matching += 1;
+        }
+    }
+
+    // Mostly `key = value` lines; a [table] header makes it unambiguous
+    matching * 10 >= lines.len() * 8 && (has_table || matching >= 3)
+}
+
+fn shebang_interpreter(text: &str) -> Option<String> {
+    let first = text.lines().next()?.strip_prefix("#!")?;
+    let mut parts = first.split_whitespace();
+    let program = parts.next()?;
+    let program = if program.ends_with("/env") {
+        parts.next()?
+    } else {
+        program
+    };
+    program.rsplit('/').next().map(|s| s.to_string())
+}
+
+fn looks_like_shell_command(text: &str) -> bool {
+    let lines: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect();
+    // Long multi-line content is a script or code, not a command
+    let continued = lines.iter().filter(|l| l.ends_with('\\')).count();
+    if lines.len() - continued > 3 {
+        return false;
+    }
+
+    let first = lines[0].trim_start_matches("$ ");
+    let word = first.split_whitespace().next().unwrap_or("");
+    SHELL_COMMANDS.contains(&word) || word.starts_with("./")
+}
+
+fn looks_like_dockerfile(lines: &[&str]) -> bool {
+    let first_is_from = lines
+        .first()
+        .is_some_and(|l| l.starts_with("FROM ") || l.starts_with("ARG "));
+    let instructions = lines
+        .iter()
+        .filter(|l| DOCKERFILE_INSTRUCTIONS.iter().any(|i| l.starts_with(i)))
+        .count();
+    first_is_from && instructions >= 2
+}
+
+fn detect_code_language(text: &str) -> Option<&'static str> {
+    let mut best: Option<(&'static str, usize)> = None;
+    for (language, signals) in LANGUAGE_SIGNALS {
+        let score = signals.iter().filter(|s| text.contains(*s)).count();
+        if score >= MIN_SIGNALS && best.is_none_or(|(_, b)| score > b) {
+            best = Some((language, score));
+        }
+    }
+    best.map(|(language, _)| language)
+        .filter(|language| BUILTIN_LANGUAGES.contains(language))
+}
+
+/// Words too common to say anything about an item's topic.
+const STOPWORDS: &[&str] = &[
+    "the", "and", "for", "with", "this", "that", "from", "into", "are", "was", "not", "but", "you",
+    "your", "all", "can", "use", "using", "how", "what", "when", "then", "else", "true", "false",
+    "null", "none", "return", "new", "get", "set", "run", "add", "out", "var", "let", "const",
+    "def", "fn", "pub", "function", "http", "https", "www", "com",
+];
+
+/// A keyword must co-occur with a tag in at least this many items...
+const MIN_KEYWORD_SUPPORT: usize = 2;
+/// ...and at least this share of the items containing it must carry the tag.
+const MIN_KEYWORD_PRECISION: f64 = 0.6;
+/// Total keyword weight a tag needs before it is suggested.
+const MIN_TAG_SCORE: f64 = 1.0;
+const MAX_TAGS: usize = 5;
+
+/// Keyword → tag associations learned from the tags already on existing items,
+/// plus which folders items with each tag tend to live in. Counts are kept per
+/// item, so items can be added and removed as they change instead of relearning
+/// the whole corpus.
+#[derive(Debug, Default)]
+pub struct TagDictionary {
+    /// Keyword → how many items contain it
+    doc_freq: HashMap<String, usize>,
+    /// Keyword → tag → how many items contain the keyword and carry the tag
+    co_occurrences: HashMap<String, HashMap<String, usize>>,
+    /// Lowercased tag → tag → how many items carry it
+    tag_names: HashMap<String, HashMap<String, usize>>,
+    /// Tag → folder → how many items with the tag are filed there
+    folders: HashMap<String, HashMap<String, usize>>,
+    items: HashMap<String, Contribution>,
+}
+
+/// What one item added to the counts, so it can be taken out again.
+#[derive(Debug)]
+struct Contribution {
+    file_hash: String,
+    words: HashSet<String>,
+    tags: Vec<String>,
+    folder: String,
+}
+
+fn keywords(text: &str) -> HashSet<String> {
+    text.split(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+        .map(|w| w.trim_matches(|c| c == '-' || c == '_').to_lowercase())
+        .filter(|w| {
+            w.chars().count() >= 3
+                && !w.chars().all(|c| c.is_ascii_digit())
+                && !STOPWORDS.contains(&w.as_str())
+        })
+        .collect()
+}
+
+/// Count one more or one fewer, dropping entries (and emptied maps) at zero.
+fn bump(counts: &mut HashMap<String, HashMap<String, usize>>, outer: &str, inner: &str, add: bool) {
+    let inner_counts = counts.entry(outer.to_string()).or_default();
+    let count = inner_counts.entry(inner.to_string()).or_default();
+    if add {
+        *count += 1;
+    } else {
+        *count = count.saturating_sub(1);
+        if *count == 0 {
+            inner_counts.remove(inner);
+        }
+    }
+    if inner_counts.is_empty() {
+        counts.remove(outer);
+    }
+}
+
+impl TagDictionary {
+    pub fn learn(items: &[Item]) -> Self {
+        let mut dictionary = TagDictionary::default();
+        for item in items {
+            dictionary.add(item);
+        }
+        dictionary
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Whether the counts include `id` as it was when its file hashed to `file_hash`.
+    pub fn is_current(&self, id: &str, file_hash: &str) -> bool {
+        self.items.get(id).is_some_and(|c| c.file_hash == file_hash)
+    }
+
+    /// Ids of the items counted, e.g. to find ones that have since been deleted.
+    pub fn item_ids(&self) -> Vec<String> {
+        self.items.keys().cloned().collect()
+    }
+
+    /// Count `item`, replacing what an earlier version of it contributed.
+    pub fn add(&mut self, item: &Item) {
+        self.remove(&item.id);
+        let contribution = Contribution {
+            file_hash: item.file_hash.clone(),
+            words: keywords(&format!("{} {}", item.title, item.content)),
+            tags: item.tags.clone(),
+            folder: item.folder.clone(),
+        };
+        self.apply(&contribution, true);
+        self.items.insert(item.id.clone(), contribution);
+    }
+
+    pub fn remove(&mut self, id: &str) {
+        if let Some(contribution) = self.items.remove(id) {
+            self.apply(&contribution, false);
+        }
+    }
+
+    fn apply(&mut self, contribution: &Contribution, add: bool) {
+        for word in &contribution.words {
+            let freq = self.doc_freq.entry(word.clone()).or_default();
+            if add {
+                *freq += 1;
+            } else {
+                *freq = freq.saturating_sub(1);
+                if *freq == 0 {
+                    self.doc_freq.remove(word);
+                }
+            }
+            for tag in &contribution.tags {
+                bump(&mut self.co_occurrences, word, tag, add);
+            }
+        }
+        for tag in &contribution.tags {
+            bump(&mut self.tag_names, &tag.to_lowercase(), tag, add);
+            if contribution.folder != "/" && !contribution.folder.is_empty() {
+                bump(&mut self.folders, tag, &contribution.folder, add);
+            }
+        }
+    }
+
+    /// Tags `word` is evidence for, with their weights.
+    fn keyword_tags(&self, word: &str) -> HashMap<&str, f64> {
+        let mut weights: HashMap<&str, f64> = HashMap::new();
+        // A tag is always evidence for itself
+        for tag in self.tag_names.get(word).into_iter().flat_map(|t| t.keys()) {
+            weights.insert(tag, 1.0);
+        }
+        let freq = self.doc_freq.get(word).copied().unwrap_or(0);
+        for (tag, &count) in self.co_occurrences.get(word).into_iter().flatten() {
+            let precision = count as f64 / freq.max(1) as f64;
+            if count >= MIN_KEYWORD_SUPPORT && precision >= MIN_KEYWORD_PRECISION {
+                let weight = weights.entry(tag).or_default();
+                *weight = weight.max(precision);
+            }
+        }
+        weights
+    }
+
+    /// Tags whose keywords appear in `text`, best first.
+    pub fn suggest_tags(&self, text: &str) -> Vec<String> {
+        let mut scores: HashMap<&str, f64> = HashMap::new();
+        for word in keywords(text) {
+            for (tag, weight) in self.keyword_tags(&word) {
+                *scores.entry(tag).or_default() += weight;
+            }
+        }
+
+        let mut ranked: Vec<(&str, f64)> = scores
+            .into_iter()
+            .filter(|(_, score)| *score >= MIN_TAG_SCORE)
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        ranked
+            .into_iter()
+            .take(MAX_TAGS)
+            .map(|(tag, _)| tag.to_string())
+            .collect()
+    }
+
+    /// The folder most items sharing these tags are filed under.
+    pub fn suggest_folder(&self, tags: &[String]) -> Option<String> {
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for tag in tags {
+            for (folder, count) in self.folders.get(tag).into_iter().flatten() {
+                *counts.entry(folder.as_str()).or_default() += count;
+            }
+        }
+        counts
+            .into_iter()
+            .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(a.0)))
+            .map(|(folder, _)| folder.to_string())
+    }
+}
+
+/// Rule-based first pass over a categorization request. Only fields the request
+/// asks for are filled, and only when the rules are confident; anything left as
+/// `None` is for the AI provider, if one is configured.
+pub fn classify(
+    request: &CategorizationRequest,
+    dictionary: &TagDictionary,
+) -> CategorizationResult {
+    let detection = detect(&request.content);
+    let mut result = CategorizationResult::default();
+
+    let vocabulary = &request.vocabulary;
+
+    if request.needs_type {
+        result.item_type = detection.item_type.filter(|t| vocabulary.has_type(t));
+    }
+    if request.needs_language {
+        result.language = detection
+            .language
+            .filter(|l| vocabulary.has_language(l))
+            .or_else(|| {
+                let item_type = result.item_type.as_deref()?;
+                let default = &vocabulary.get_type(item_type)?.default_language;
+                (!default.is_empty()).then(|| default.clone())
+            });
+    }
+
+    let mut tags = detection.tags;
+    for tag in dictionary.suggest_tags(&format!("{} {}", request.title, request.content)) {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags.truncate(MAX_TAGS);
+
+    if request.needs_folder {
+        result.folder = dictionary.suggest_folder(&tags).filter(|f| {
+            request.existing_folders.is_empty() || request.existing_folders.contains(f)
+        });
+    }
+    if request.needs_tags && !tags.is_empty() {
+        result.tags = Some(tags);
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::vocabulary::Vocabulary;
+
+    fn item(title: &str, content: &str, tags: &[&str], folder: &str) -> Item {
+        Item {
+            id: title.to_lowercase(),
+            title: title.to_string(),
+            item_type: "note".to_string(),
+            language: String::new(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            folder: folder.to_string(),
+            description: String::new(),
+            content: content.to_string(),
+            file_path: String::new(),
+            file_hash: String::new(),
+            created: String::new(),
+            modified: String::new(),
+            trusted: false,
+            pinned: false,
+            slot: None,
+            ai_opt_out: false,
+            encrypted: false,
+            ciphertext: String::new(),
+        }
+    }
+
+    fn request(content: &str) -> CategorizationRequest {
+        CategorizationRequest {
+            title: String::new(),
+            content: content.to_string(),
+            needs_type: true,
+            needs_language: true,
+            needs_tags: true,
+            needs_folder: true,
+            needs_description: false,
+            needs_title: false,
+            existing_tags: Vec::new(),
+            existing_folders: Vec::new(),
+            vocabulary: Vocabulary::default(),
+            tag_synonyms: Default::default(),
+        }
+    }
+
+    fn detection(item_type: &str, language: &str, tags: &[&str]) -> Detection {
+        Detection {
+            item_type: Some(item_type.to_string()),
+            language: Some(language.to_string()),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn reads_shebang_interpreters() {
+        let python = "#!/usr/bin/env python3\nimport sys\nprint(sys.argv)\nprint(1)\nprint(2)";
+        let bash = "#!/bin/bash\nset -e\nfor f in *; do\n  wc -l \"$f\"\ndone";
+
+        assert_eq!(detect(python), detection("snippet", "python", &[]));
+        assert_eq!(detect(bash), detection("shell", "bash", &[]));
+        assert_eq!(detect("#!/usr/bin/perl\nprint 1;"), Detection::default());
+    }
+
+    #[test]
+    fn uses_fence_info_strings() {
+        assert_eq!(
+            detect("```py\nprint('hi')\n```"),
+            detection("snippet", "python", &[])
+        );
+        assert_eq!(
+            detect("```yml\nport: 80\n```"),
+            detection("config", "yaml", &[])
+        );
+
+        // A fence inside prose only says which language the item is about
+        let note = detect("Restart it with:\n\n```sh\nsystemctl restart web\n```\n\nThen check.");
+        assert_eq!(note.item_type, None);
+        assert_eq!(note.language.as_deref(), Some("bash"));
+
+        assert_eq!(fenced_language("```brainfuck\n+++\n```"), None);
+    }
+
+    #[test]
+    fn recognises_file_signatures() {
+        let manifest = "# web service\napiVersion: v1\nkind: Service\nmetadata:\n  name: web";
+        let cargo = "[package]\nname = \"lynxnote\"\nversion = \"0.1.0\"";
+        let dockerfile = "FROM rust:1.80\nWORKDIR /app\nCOPY . .\nRUN cargo build --release";
+
+        assert_eq!(
+            detect(manifest),
+            detection("config", "yaml", &["kubernetes"])
+        );
+        assert_eq!(detect(cargo), detection("config", "toml", &["cargo"]));
+        assert_eq!(
+            detect(dockerfile),
+            detection("config", "dockerfile", &["docker"])
+        );
+    }
+
+    #[test]
+    fn detects_config_formats() {
+        assert_eq!(detect("{\"port\": 80}"), detection("config", "json", &[]));
+        assert_eq!(detect("[1, 2, 3]"), detection("config", "json", &[]));
+        assert_eq!(
+            detect("server:\n  port: 80\n  host: example.com"),
+            detection("config", "yaml", &[])
+        );
+        assert_eq!(
+            detect("[server]\nport = 80\nhost = \"example.com\""),
+            detection("config", "toml", &[])
+        );
+
+        // A single "Key: value" line is prose, not YAML
+        assert_eq!(detect("Note: call the vendor back"), Detection::default());
+    }
+
+    #[test]
+    fn recognises_shell_one_liners_and_code() {
+        assert_eq!(
+            detect("$ kubectl get pods -A"),
+            detection("shell", "bash", &[])
+        );
+        assert_eq!(
+            detect("fn main() {\n    let mut v: Vec<u8> = Vec::new();\n}"),
+            detection("snippet", "rust", &[])
+        );
+        assert_eq!(detect("Buy milk and eggs"), Detection::default());
+    }
+
+    #[test]
+    fn learns_tags_and_folders_from_existing_items() {
+        let items = vec![
+            item(
+                "Restart",
+                "restart the nginx container",
+                &["docker"],
+                "/ops",
+            ),
+            item("Logs", "follow a container log", &["docker"], "/ops"),
+            item("Compose", "compose up", &["docker"], "/ops"),
+            item("Branches", "list branches", &["git"], "/dev"),
+        ];
+        let dictionary = TagDictionary::learn(&items);
+
+        // Seen with the tag in every item that has it, twice or more
+        assert_eq!(
+            dictionary.suggest_tags("stop that container"),
+            vec!["docker"]
+        );
+        // A tag's own name always counts
+        assert_eq!(dictionary.suggest_tags("git rebase"), vec!["git"]);
+        // Only seen once
+        assert!(dictionary.suggest_tags("compose").is_empty());
+
+        assert_eq!(
+            dictionary
+                .suggest_folder(&["docker".to_string()])
+                .as_deref(),
+            Some("/ops")
+        );
+        assert_eq!(dictionary.suggest_folder(&["rust".to_string()]), None);
+    }
+
+    #[test]
+    fn updates_when_items_change() {
+        let mut restart = item(
+            "Restart",
+            "restart the nginx container",
+            &["docker"],
+            "/ops",
+        );
+        let logs = item("Logs", "follow a container log", &["docker"], "/ops");
+        let mut dictionary = TagDictionary::learn(&[restart.clone(), logs.clone()]);
+        assert_eq!(dictionary.suggest_tags("container"), vec!["docker"]);
+        assert!(dictionary.is_current(&restart.id, &restart.file_hash));
+
+        // Retagging one item leaves the keyword below the support threshold
+        restart.tags = vec!["podman".to_string()];
+        restart.file_hash = "changed".to_string();
+        assert!(!dictionary.is_current(&restart.id, &restart.file_hash));
+        dictionary.add(&restart);
+        assert!(dictionary.suggest_tags("container").is_empty());
+        assert_eq!(
+            dictionary
+                .suggest_folder(&["podman".to_string()])
+                .as_deref(),
+            Some("/ops")
+        );
+
+        dictionary.remove(&restart.id);
+        dictionary.remove(&logs.id);
+        assert!(dictionary.is_empty());
+        assert!(dictionary.suggest_tags("docker podman").is_empty());
+        assert_eq!(dictionary.suggest_folder(&["docker".to_string()]), None);
+    }
+
+    #[test]
+    fn classify_fills_only_requested_fields() {
+        let items = vec![
+            item("Pods", "list pods in a namespace", &["kubernetes"], "/k8s"),
+            item("Deploy", "rollout a deployment", &["kubernetes"], "/k8s"),
+        ];
+        let dictionary = TagDictionary::learn(&items);
+        let content = "apiVersion: apps/v1\nkind: Deployment\nmetadata:\n  name: web";
+
+        let result = classify(&request(content), &dictionary);
+
+        assert_eq!(result.item_type.as_deref(), Some("config"));
+        assert_eq!(result.language.as_deref(), Some("yaml"));
+        assert_eq!(result.tags, Some(vec!["kubernetes".to_string()]));
+        assert_eq!(result.folder.as_deref(), Some("/k8s"));
+
+        let mut partial = request(content);
+        partial.needs_type = false;
+        partial.needs_folder = false;
+        let result = classify(&partial, &dictionary);
+        assert_eq!(result.item_type, None);
+        assert_eq!(result.folder, None);
+        assert_eq!(result.language.as_deref(), Some("yaml"));
+    }
+}
@@ -0,0 +1,167 @@
+use super::provider::{AiProvider, TokenUsage};
+use crate::db::queries::{self, AiUsageEntry};
+use chrono::{Datelike, TimeZone, Utc};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const PRICES_SETTING: &str = "ai_prices";
+const BUDGET_SETTING: &str = "ai_monthly_budget_usd";
+
+/// USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Price {
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+}
+
+/// List prices at the time of writing, matched against the model name by
+/// longest prefix. Anything missing can be added through the price table setting.
+const DEFAULT_PRICES: &[(&str, f64, f64)] = &[
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+    ("gpt-4.1-nano", 0.10, 0.40),
+    ("gpt-4.1-mini", 0.40, 1.60),
+    ("gpt-4.1", 2.00, 8.00),
+    ("o4-mini", 1.10, 4.40),
+    ("claude-3-haiku", 0.25, 1.25),
+    ("claude-3-5-haiku", 0.80, 4.00),
+    ("claude-haiku-4", 1.00, 5.00),
+    ("claude-3-5-sonnet", 3.00, 15.00),
+    ("claude-3-7-sonnet", 3.00, 15.00),
+    ("claude-sonnet-4", 3.00, 15.00),
+    ("claude-opus-4", 15.00, 75.00),
+    ("gemini-1.5-flash", 0.075, 0.30),
+    ("gemini-1.5-pro", 1.25, 5.00),
+    ("gemini-2.0-flash", 0.10, 0.40),
+    ("gemini-2.5-flash", 0.30, 2.50),
+    ("gemini-2.5-pro", 1.25, 10.00),
+];
+
+/// The built-in prices overlaid with the user's saved ones.
+pub fn price_table(conn: &Connection) -> BTreeMap<String, Price> {
+    let mut table: BTreeMap<String, Price> = DEFAULT_PRICES
+        .iter()
+        .map(|(model, input, output)| {
+            (
+                model.to_string(),
+                Price {
+                    input_per_mtok: *input,
+                    output_per_mtok: *output,
+                },
+            )
+        })
+        .collect();
+
+    if let Some(json) = queries::get_setting(conn, PRICES_SETTING).ok().flatten() {
+        match serde_json::from_str::<BTreeMap<String, Price>>(&json) {
+            Ok(saved) => table.extend(saved),
+            Err(e) => eprintln!("Invalid saved AI price table, using defaults: {}", e),
+        }
+    }
+    table
+}
+
+/// Save price overrides. Models are matched by prefix, so "gpt-4o" also prices
+/// dated snapshots like "gpt-4o-2024-08-06".
+pub fn save_price_table(conn: &Connection, prices: &BTreeMap<String, Price>) -> Result<(), String> {
+    for (model, price) in prices {
+        if model.trim().is_empty() {
+            return Err("Model name cannot be empty".to_string());
+        }
+        if [price.input_per_mtok, price.output_per_mtok]
+            .iter()
+            .any(|p| !p.is_finite() || *p < 0.0)
+        {
+            return Err(format!("Prices for {} must be zero or more", model));
+        }
+    }
+    let json = serde_json::to_string(prices).map_err(|e| e.to_string())?;
+    queries::set_setting(conn, PRICES_SETTING, &json).map_err(|e| e.to_string())
+}
+
+pub fn estimate_cost(
+    table: &BTreeMap<String, Price>,
+    provider: &str,
+    model: &str,
+    usage: &TokenUsage,
+) -> f64 {
+    // Local models cost nothing per token
+    if provider == "ollama" {
+        return 0.0;
+    }
+    let price = table
+        .iter()
+        .filter(|(prefix, _)| model.starts_with(prefix.as_str()))
+        .max_by_key(|(prefix, _)| prefix.len())
+        .map(|(_, price)| *price);
+    match price {
+        Some(p) => {
+            (usage.input_tokens as f64 * p.input_per_mtok
+                + usage.output_tokens as f64 * p.output_per_mtok)
+                / 1_000_000.0
+        }
+        None => 0.0,
+    }
+}
+
+/// Add a ledger entry for `usage`. Failures are logged; accounting never blocks
+/// the operation it describes.
+pub fn record(conn: &Connection, operation: &str, provider: &dyn AiProvider, usage: &TokenUsage) {
+    if usage.is_empty() {
+        return;
+    }
+    let entry = AiUsageEntry {
+        operation: operation.to_string(),
+        provider: provider.name().to_string(),
+        model: provider.model().to_string(),
+        input_tokens: usage.input_tokens,
+        output_tokens: usage.output_tokens,
+        cost_usd: estimate_cost(&price_table(conn), provider.name(), provider.model(), usage),
+        created: Utc::now().to_rfc3339(),
+    };
+    if let Err(e) = queries::insert_ai_usage(conn, &entry) {
+        eprintln!("Failed to record AI usage: {}", e);
+    }
+}
+
+pub fn monthly_budget(conn: &Connection) -> Option<f64> {
+    queries::get_setting(conn, BUDGET_SETTING)
+        .ok()
+        .flatten()
+        .and_then(|v| v.trim().parse::<f64>().ok())
+        .filter(|b| *b > 0.0)
+}
+
+/// `None` removes the budget.
+pub fn set_monthly_budget(conn: &Connection, budget: Option<f64>) -> Result<(), String> {
+    match budget {
+        Some(b) if !b.is_finite() || b <= 0.0 => {
+            Err("Budget must be greater than zero".to_string())
+        }
+        Some(b) => {
+            queries::set_setting(conn, BUDGET_SETTING, &b.to_string()).map_err(|e| e.to_string())
+        }
+        None => queries::delete_setting(conn, BUDGET_SETTING).map_err(|e| e.to_string()),
+    }
+}
+
+/// Start of the current calendar month (UTC), as stored in the ledger.
+pub fn month_start() -> String {
+    let now = Utc::now();
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .single()
+        .unwrap_or(now)
+        .to_rfc3339()
+}
+
+pub fn month_to_date_cost(conn: &Connection) -> f64 {
+    queries::ai_cost_since(conn, &month_start()).unwrap_or_else(|e| {
+        eprintln!("Failed to total AI spend: {}", e);
+        0.0
+    })
+}
+
+pub fn budget_exceeded(conn: &Connection) -> bool {
+    monthly_budget(conn).is_some_and(|budget| month_to_date_cost(conn) >= budget)
+}
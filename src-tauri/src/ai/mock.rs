@@ -0,0 +1,124 @@
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use super::provider::{AiConfig, AiError, AiMessage, AiProvider, AiResponse, TokenUsage};
+
+/// What the mock answers with for a single call.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum MockReply {
+    Content { content: String },
+    RateLimit { retry_after_secs: Option<u64> },
+    ServerError { message: String },
+    ClientError { message: String },
+}
+
+/// An offline provider that replays scripted replies in order and records every
+/// request it receives. Once the script runs out it answers with `fallback`.
+///
+/// Selected as provider `mock`; the base URL, if set, is a JSON file holding the
+/// script, e.g. `[{"kind": "rate_limit", "retry_after_secs": 0},
+/// {"kind": "content", "content": "{\"type\": \"shell\"}"}]`.
+pub struct MockProvider {
+    model: String,
+    script: Mutex<VecDeque<MockReply>>,
+    fallback: String,
+    requests: Mutex<Vec<Vec<AiMessage>>>,
+}
+
+impl MockProvider {
+    pub fn new(config: &AiConfig) -> Self {
+        let script = match config.base_url {
+            Some(ref path) => match std::fs::read_to_string(path)
+                .map_err(|e| e.to_string())
+                .and_then(|json| {
+                    serde_json::from_str::<Vec<MockReply>>(&json).map_err(|e| e.to_string())
+                }) {
+                Ok(script) => script,
+                Err(e) => {
+                    eprintln!("Failed to load mock AI script {}: {}", path, e);
+                    Vec::new()
+                }
+            },
+            None => Vec::new(),
+        };
+        let model = if config.model.is_empty() {
+            "mock".to_string()
+        } else {
+            config.model.clone()
+        };
+        Self::with_script(&model, script)
+    }
+
+    pub fn with_script(model: &str, script: Vec<MockReply>) -> Self {
+        Self {
+            model: model.to_string(),
+            script: Mutex::new(script.into()),
+            fallback: "{}".to_string(),
+            requests: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Answer every call with `content`.
+    #[cfg(test)]
+    pub fn replying(content: &str) -> Self {
+        let mut mock = Self::with_script("mock", Vec::new());
+        mock.fallback = content.to_string();
+        mock
+    }
+
+    /// Every request received so far, oldest first.
+    #[cfg(test)]
+    pub fn requests(&self) -> Vec<Vec<AiMessage>> {
+        self.requests.lock().map(|r| r.clone()).unwrap_or_default()
+    }
+}
+
+/// Roughly four characters per token, so usage accounting has numbers to add up.
+fn estimate_tokens(text: &str) -> u64 {
+    (text.chars().count() as u64).div_ceil(4)
+}
+
+#[async_trait]
+impl AiProvider for MockProvider {
+    async fn complete(&self, messages: Vec<AiMessage>) -> Result<AiResponse, AiError> {
+        let input_tokens = messages.iter().map(|m| estimate_tokens(&m.content)).sum();
+        if let Ok(mut requests) = self.requests.lock() {
+            requests.push(messages);
+        }
+
+        let reply = self.script.lock().ok().and_then(|mut s| s.pop_front());
+        let content = match reply {
+            Some(MockReply::Content { content }) => content,
+            Some(MockReply::RateLimit { retry_after_secs }) => {
+                return Err(AiError::RateLimit { retry_after_secs })
+            }
+            Some(MockReply::ServerError { message }) => return Err(AiError::ServerError(message)),
+            Some(MockReply::ClientError { message }) => return Err(AiError::ClientError(message)),
+            None => self.fallback.clone(),
+        };
+
+        let usage = TokenUsage {
+            input_tokens,
+            output_tokens: estimate_tokens(&content),
+        };
+        Ok(AiResponse {
+            content,
+            usage: Some(usage),
+        })
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>, AiError> {
+        Ok(vec![self.model.clone()])
+    }
+
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
+}
@@ -1,7 +1,16 @@
+pub mod cache;
 pub mod categorize;
 pub mod claude;
+pub mod gemini;
+pub mod heuristics;
+pub mod ledger;
+pub mod mock;
 pub mod ollama;
 pub mod openai;
+pub mod prompts;
 pub mod provider;
+pub mod queue;
+pub mod redact;
 pub mod retry;
+pub mod tag_merges;
 pub mod taxonomy;
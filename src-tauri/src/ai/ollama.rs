@@ -3,7 +3,9 @@ use reqwest::Client;
 use serde_json::json;
 use std::time::Duration;
 
-use super::provider::{AiConfig, AiError, AiMessage, AiProvider, AiResponse};
+use super::provider::{
+    model_ids, status_error, AiConfig, AiError, AiMessage, AiProvider, AiResponse, TokenUsage,
+};
 
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
 
@@ -58,16 +60,12 @@ impl AiProvider for OllamaProvider {
             .json(&body)
             .send()
             .await
-            .map_err(|e| AiError::ServerError(format!("Ollama request failed: {}", e)))?;
+            .map_err(|e| AiError::Network(format!("Ollama request failed: {}", e)))?;
 
         if !resp.status().is_success() {
             let status = resp.status().as_u16();
             let text = resp.text().await.unwrap_or_default();
-            return Err(match status {
-                429 => AiError::RateLimit { retry_after_secs: None },
-                500..=599 => AiError::ServerError(format!("Ollama API error {}: {}", status, text)),
-                _ => AiError::ClientError(format!("Ollama API error {}: {}", status, text)),
-            });
+            return Err(status_error("Ollama", status, None, &text));
         }
 
         let data: serde_json::Value = resp
@@ -82,10 +80,47 @@ impl AiProvider for OllamaProvider {
             .unwrap_or("")
             .to_string();
 
-        Ok(AiResponse { content })
+        // Ollama reports counts at the top level, and omits them for cached prompts
+        let count = |key: &str| data.get(key).and_then(|v| v.as_u64());
+        let usage = match (count("prompt_eval_count"), count("eval_count")) {
+            (None, None) => None,
+            (input, output) => Some(TokenUsage {
+                input_tokens: input.unwrap_or(0),
+                output_tokens: output.unwrap_or(0),
+            }),
+        };
+
+        Ok(AiResponse { content, usage })
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>, AiError> {
+        let url = format!("{}/api/tags", self.base_url.trim_end_matches('/'));
+        let resp = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| AiError::Network(format!("Ollama request failed: {}", e)))?;
+
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let text = resp.text().await.unwrap_or_default();
+            return Err(status_error("Ollama", status, None, &text));
+        }
+
+        let data: serde_json::Value = resp
+            .json()
+            .await
+            .map_err(|e| AiError::ClientError(format!("Ollama response parse error: {}", e)))?;
+
+        Ok(model_ids(&data, "models", "name"))
     }
 
     fn name(&self) -> &str {
         "ollama"
     }
+
+    fn model(&self) -> &str {
+        &self.model
+    }
 }
@@ -1,32 +1,136 @@
 use async_trait::async_trait;
-use reqwest::Client;
+use reqwest::{Client, RequestBuilder};
 use serde_json::json;
 use std::time::Duration;
 
-use super::provider::{AiConfig, AiError, AiMessage, AiProvider, AiResponse};
+use super::provider::{
+    model_ids, status_error, AiConfig, AiError, AiMessage, AiProvider, AiResponse, TokenUsage,
+};
 
 const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
 
+/// API version used for Azure OpenAI when none is configured.
+pub const DEFAULT_AZURE_API_VERSION: &str = "2024-10-21";
+
+/// Any endpoint speaking the OpenAI chat completions API: OpenAI itself, Azure
+/// OpenAI deployments, and compatible servers like LM Studio, vLLM or llama.cpp.
 pub struct OpenAiProvider {
     client: Client,
+    name: &'static str,
+    label: &'static str,
     api_key: String,
     model: String,
-    base_url: String,
+    chat_url: String,
+    models_url: Option<String>,
+    /// Header carrying the API key. `Authorization` gets a `Bearer` prefix.
+    auth_header: String,
+    /// Ask for `response_format: json_object`. Some compatible servers reject it.
+    json_mode: bool,
 }
 
 impl OpenAiProvider {
-    pub fn new(config: &AiConfig) -> Self {
+    fn build(
+        config: &AiConfig,
+        name: &'static str,
+        label: &'static str,
+        chat_url: String,
+        models_url: Option<String>,
+        auth_header: String,
+        json_mode: bool,
+    ) -> Self {
         Self {
             client: Client::builder()
                 .timeout(REQUEST_TIMEOUT)
                 .build()
                 .unwrap_or_default(),
+            name,
+            label,
             api_key: config.api_key.clone(),
             model: config.model.clone(),
-            base_url: config
-                .base_url
+            chat_url,
+            models_url,
+            auth_header,
+            json_mode,
+        }
+    }
+
+    pub fn new(config: &AiConfig) -> Self {
+        let base_url = config
+            .base_url
+            .clone()
+            .unwrap_or_else(|| "https://api.openai.com".to_string());
+        let base_url = base_url.trim_end_matches('/');
+        Self::build(
+            config,
+            "openai",
+            "OpenAI",
+            format!("{}/v1/chat/completions", base_url),
+            Some(format!("{}/v1/models", base_url)),
+            "Authorization".to_string(),
+            true,
+        )
+    }
+
+    /// Azure OpenAI: the base URL is the resource endpoint and the model is the
+    /// deployment name.
+    pub fn azure(config: &AiConfig) -> Result<Self, String> {
+        let base_url = config
+            .base_url
+            .clone()
+            .ok_or("Azure OpenAI needs the resource endpoint as the base URL")?;
+        if config.model.is_empty() {
+            return Err("Azure OpenAI needs the deployment name as the model".to_string());
+        }
+        let api_version = config
+            .api_version
+            .clone()
+            .unwrap_or_else(|| DEFAULT_AZURE_API_VERSION.to_string());
+        Ok(Self::build(
+            config,
+            "azure_openai",
+            "Azure OpenAI",
+            format!(
+                "{}/openai/deployments/{}/chat/completions?api-version={}",
+                base_url.trim_end_matches('/'),
+                config.model,
+                api_version
+            ),
+            // Requests go to deployments, which this API can't list
+            None,
+            "api-key".to_string(),
+            true,
+        ))
+    }
+
+    /// A self-hosted or third-party server with an OpenAI-style API. The base URL
+    /// is everything before `/chat/completions`, usually ending in `/v1`.
+    pub fn compatible(config: &AiConfig) -> Result<Self, String> {
+        let base_url = config
+            .base_url
+            .clone()
+            .ok_or("An OpenAI-compatible provider needs a base URL")?;
+        let base_url = base_url.trim_end_matches('/');
+        Ok(Self::build(
+            config,
+            "openai_compatible",
+            "OpenAI-compatible",
+            format!("{}/chat/completions", base_url),
+            Some(format!("{}/models", base_url)),
+            config
+                .auth_header
                 .clone()
-                .unwrap_or_else(|| "https://api.openai.com".to_string()),
+                .unwrap_or_else(|| "Authorization".to_string()),
+            config.json_mode.unwrap_or(true),
+        ))
+    }
+
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        if self.api_key.is_empty() {
+            request
+        } else if self.auth_header.eq_ignore_ascii_case("authorization") {
+            request.header("Authorization", format!("Bearer {}", self.api_key))
+        } else {
+            request.header(self.auth_header.as_str(), &self.api_key)
         }
     }
 }
@@ -34,8 +138,6 @@ impl OpenAiProvider {
 #[async_trait]
 impl AiProvider for OpenAiProvider {
     async fn complete(&self, messages: Vec<AiMessage>) -> Result<AiResponse, AiError> {
-        let url = format!("{}/v1/chat/completions", self.base_url.trim_end_matches('/'));
-
         let msgs: Vec<serde_json::Value> = messages
             .iter()
             .map(|m| {
@@ -46,22 +148,22 @@ impl AiProvider for OpenAiProvider {
             })
             .collect();
 
-        let body = json!({
+        let mut body = json!({
             "model": self.model,
             "messages": msgs,
             "temperature": 0.3,
-            "response_format": { "type": "json_object" },
         });
+        if self.json_mode {
+            body["response_format"] = json!({ "type": "json_object" });
+        }
 
         let resp = self
-            .client
-            .post(&url)
-            .header("Authorization", format!("Bearer {}", self.api_key))
+            .authorize(self.client.post(&self.chat_url))
             .header("Content-Type", "application/json")
             .json(&body)
             .send()
             .await
-            .map_err(|e| AiError::ServerError(format!("OpenAI request failed: {}", e)))?;
+            .map_err(|e| AiError::Network(format!("{} request failed: {}", self.label, e)))?;
 
         if !resp.status().is_success() {
             let status = resp.status().as_u16();
@@ -71,17 +173,12 @@ impl AiProvider for OpenAiProvider {
                 .and_then(|v| v.to_str().ok())
                 .and_then(|v| v.parse::<u64>().ok());
             let text = resp.text().await.unwrap_or_default();
-            return Err(match status {
-                429 => AiError::RateLimit { retry_after_secs: retry_after },
-                500..=599 => AiError::ServerError(format!("OpenAI API error {}: {}", status, text)),
-                _ => AiError::ClientError(format!("OpenAI API error {}: {}", status, text)),
-            });
+            return Err(status_error(self.label, status, retry_after, &text));
         }
 
-        let data: serde_json::Value = resp
-            .json()
-            .await
-            .map_err(|e| AiError::ClientError(format!("OpenAI response parse error: {}", e)))?;
+        let data: serde_json::Value = resp.json().await.map_err(|e| {
+            AiError::ClientError(format!("{} response parse error: {}", self.label, e))
+        })?;
 
         let content = data
             .get("choices")
@@ -93,10 +190,45 @@ impl AiProvider for OpenAiProvider {
             .unwrap_or("")
             .to_string();
 
-        Ok(AiResponse { content })
+        let usage = data.get("usage").map(|u| TokenUsage {
+            input_tokens: u.get("prompt_tokens").and_then(|v| v.as_u64()).unwrap_or(0),
+            output_tokens: u.get("completion_tokens").and_then(|v| v.as_u64()).unwrap_or(0),
+        });
+
+        Ok(AiResponse { content, usage })
+    }
+
+    async fn list_models(&self) -> Result<Vec<String>, AiError> {
+        let Some(ref url) = self.models_url else {
+            return Err(AiError::ClientError(format!(
+                "{} can't list models; enter the deployment name as the model",
+                self.label
+            )));
+        };
+        let resp = self
+            .authorize(self.client.get(url))
+            .send()
+            .await
+            .map_err(|e| AiError::Network(format!("{} request failed: {}", self.label, e)))?;
+
+        if !resp.status().is_success() {
+            let status = resp.status().as_u16();
+            let text = resp.text().await.unwrap_or_default();
+            return Err(status_error(self.label, status, None, &text));
+        }
+
+        let data: serde_json::Value = resp.json().await.map_err(|e| {
+            AiError::ClientError(format!("{} response parse error: {}", self.label, e))
+        })?;
+
+        Ok(model_ids(&data, "data", "id"))
     }
 
     fn name(&self) -> &str {
-        "openai"
+        self.name
+    }
+
+    fn model(&self) -> &str {
+        &self.model
     }
 }
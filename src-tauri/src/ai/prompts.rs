@@ -0,0 +1,254 @@
+use crate::db::queries;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+/// Bumped whenever a built-in template below changes.
+const DEFAULT_VERSION: u32 = 1;
+
+const CATEGORIZE_DEFAULT: &str =
+    "You are a categorization assistant for a developer knowledge base. \
+Analyze the given item and return a JSON object with ONLY the requested fields.
+
+Return a JSON object with these fields:
+{{fields}}
+Return ONLY valid JSON, no markdown fences, no explanation.";
+
+const CATEGORIZE_BATCH_DEFAULT: &str =
+    "You are a categorization assistant for a developer knowledge base. \
+You will receive multiple items. For EACH item, return a categorization object.
+
+Each object must have these fields:
+{{fields}}
+Return a JSON array of objects, one per item, in the same order as the input. \
+Return ONLY valid JSON, no markdown fences, no explanation.";
+
+const TAXONOMY_DEFAULT: &str =
+    "You are a knowledge base organizer. Given a list of developer items \
+(commands, snippets, configs, notes), design a hierarchical folder structure that \
+logically organizes them all.
+
+Rules:
+- Each folder path must start with \"/\"
+- Use 1-2 levels of hierarchy (e.g. \"/docker\", \"/git/workflows\")
+- Return EXACTLY {{max_folders}} folders — no more, no fewer. Group broadly so every item fits.
+- Use lowercase, short descriptive names
+- Prefer broad categories over narrow ones. Multiple related items should share a folder.
+
+Return ONLY a JSON array of folder path strings, no markdown fences, no explanation.";
+
+const TAG_MERGES_DEFAULT: &str =
+    "You are a knowledge base librarian. Given the {{tag_count}} tags used in a \
+developer knowledge base, find groups of tags that mean the same thing: abbreviations, \
+spelling variants, singular and plural forms and synonyms (e.g. \"k8s\" and \"kubernetes\").
+
+Rules:
+- Only group tags that mean the same thing, not tags that are merely related
+- For each group, pick the clearest and most common name as the target
+- Use lowercase
+- Leave out tags that have no synonym in the list
+
+Return ONLY a JSON array of objects like {\"target\": \"kubernetes\", \"sources\": [\"k8s\", \"kube\"]}, \
+no markdown fences, no explanation.";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptKind {
+    Categorize,
+    CategorizeBatch,
+    Taxonomy,
+    TagMerges,
+}
+
+impl PromptKind {
+    pub fn all() -> [PromptKind; 4] {
+        [
+            PromptKind::Categorize,
+            PromptKind::CategorizeBatch,
+            PromptKind::Taxonomy,
+            PromptKind::TagMerges,
+        ]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PromptKind::Categorize => "categorize",
+            PromptKind::CategorizeBatch => "categorize_batch",
+            PromptKind::Taxonomy => "taxonomy",
+            PromptKind::TagMerges => "tag_merges",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<PromptKind> {
+        Self::all().into_iter().find(|k| k.name() == name)
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            PromptKind::Categorize => "System prompt for enriching a single item on save",
+            PromptKind::CategorizeBatch => {
+                "System prompt for categorizing items in batches during recategorize"
+            }
+            PromptKind::Taxonomy => {
+                "System prompt for designing the folder structure during recategorize"
+            }
+            PromptKind::TagMerges => "System prompt for suggesting tags to merge",
+        }
+    }
+
+    fn default_body(&self) -> &'static str {
+        match self {
+            PromptKind::Categorize => CATEGORIZE_DEFAULT,
+            PromptKind::CategorizeBatch => CATEGORIZE_BATCH_DEFAULT,
+            PromptKind::Taxonomy => TAXONOMY_DEFAULT,
+            PromptKind::TagMerges => TAG_MERGES_DEFAULT,
+        }
+    }
+
+    /// Variables that can be used as `{{name}}` in this template.
+    pub fn variables(&self) -> &'static [&'static str] {
+        match self {
+            PromptKind::Categorize | PromptKind::CategorizeBatch => &[
+                "fields",
+                "types",
+                "languages",
+                "existing_tags",
+                "existing_folders",
+            ],
+            PromptKind::Taxonomy => &["max_folders", "item_count"],
+            PromptKind::TagMerges => &["tag_count"],
+        }
+    }
+
+    fn setting_key(&self) -> String {
+        format!("prompt_template_{}", self.name())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub name: String,
+    pub description: String,
+    pub body: String,
+    pub version: u32,
+    pub is_default: bool,
+    pub variables: Vec<String>,
+}
+
+/// What is persisted for a customised template.
+#[derive(Debug, Serialize, Deserialize)]
+struct StoredTemplate {
+    body: String,
+    version: u32,
+}
+
+impl PromptTemplate {
+    fn new(kind: PromptKind, body: String, version: u32, is_default: bool) -> Self {
+        Self {
+            name: kind.name().to_string(),
+            description: kind.description().to_string(),
+            body,
+            version,
+            is_default,
+            variables: kind.variables().iter().map(|v| v.to_string()).collect(),
+        }
+    }
+
+    pub fn default_for(kind: PromptKind) -> Self {
+        Self::new(kind, kind.default_body().to_string(), DEFAULT_VERSION, true)
+    }
+
+    /// Substitute `{{name}}` variables. Unknown names are left as written.
+    pub fn render(&self, vars: &[(&str, String)]) -> String {
+        let mut out = self.body.clone();
+        for (name, value) in vars {
+            out = out.replace(&format!("{{{{{}}}}}", name), value);
+        }
+        out
+    }
+}
+
+fn stored(conn: &Connection, kind: PromptKind) -> Option<StoredTemplate> {
+    let json = queries::get_setting(conn, &kind.setting_key())
+        .ok()
+        .flatten()?;
+    serde_json::from_str(&json)
+        .map_err(|e| eprintln!("Invalid saved {} prompt, using default: {}", kind.name(), e))
+        .ok()
+}
+
+/// The user's version of a template if there is one, otherwise the built-in.
+pub fn load(conn: &Connection, kind: PromptKind) -> PromptTemplate {
+    match stored(conn, kind) {
+        Some(s) => PromptTemplate::new(kind, s.body, s.version, false),
+        None => PromptTemplate::default_for(kind),
+    }
+}
+
+/// Names inside `{{...}}` that aren't variables of `kind`.
+fn unknown_variables(kind: PromptKind, body: &str) -> Vec<String> {
+    let mut unknown = Vec::new();
+    let mut rest = body;
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else { break };
+        let name = after[..end].trim();
+        if !kind.variables().contains(&name) && !unknown.iter().any(|u| u == name) {
+            unknown.push(name.to_string());
+        }
+        rest = &after[end + 2..];
+    }
+    unknown
+}
+
+pub fn validate(kind: PromptKind, body: &str) -> Result<(), String> {
+    if body.trim().is_empty() {
+        return Err("Prompt template cannot be empty".to_string());
+    }
+    let unknown = unknown_variables(kind, body);
+    if !unknown.is_empty() {
+        return Err(format!(
+            "Unknown variable(s) {} in {} template. Available: {}",
+            unknown.join(", "),
+            kind.name(),
+            kind.variables().join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// Save a new version of a template. Versions only ever go up, so a saved
+/// template never reuses the version number of an earlier body.
+pub fn save(conn: &Connection, kind: PromptKind, body: &str) -> Result<PromptTemplate, String> {
+    validate(kind, body)?;
+    let previous = stored(conn, kind).map_or(DEFAULT_VERSION, |s| s.version);
+    let counter_key = format!("{}_version", kind.setting_key());
+    let last_issued = queries::get_setting(conn, &counter_key)
+        .map_err(|e| e.to_string())?
+        .and_then(|v| v.parse::<u32>().ok())
+        .unwrap_or(DEFAULT_VERSION);
+    let version = previous.max(last_issued) + 1;
+
+    let json = serde_json::to_string(&StoredTemplate {
+        body: body.to_string(),
+        version,
+    })
+    .map_err(|e| e.to_string())?;
+    queries::set_setting(conn, &kind.setting_key(), &json).map_err(|e| e.to_string())?;
+    queries::set_setting(conn, &counter_key, &version.to_string()).map_err(|e| e.to_string())?;
+
+    Ok(PromptTemplate::new(kind, body.to_string(), version, false))
+}
+
+pub fn reset(conn: &Connection, kind: PromptKind) -> Result<PromptTemplate, String> {
+    queries::delete_setting(conn, &kind.setting_key()).map_err(|e| e.to_string())?;
+    Ok(PromptTemplate::default_for(kind))
+}
+
+/// Quote and comma-join up to 50 names, the form prompts list them in.
+pub fn quoted_list(names: &[String]) -> String {
+    names
+        .iter()
+        .take(50)
+        .map(|n| format!("\"{}\"", n))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -8,6 +8,15 @@ pub struct AiConfig {
     pub api_key: String,
     pub model: String,
     pub base_url: Option<String>,
+    /// Azure OpenAI `api-version`.
+    #[serde(default)]
+    pub api_version: Option<String>,
+    /// Header the API key is sent in, for OpenAI-compatible servers.
+    #[serde(default)]
+    pub auth_header: Option<String>,
+    /// Whether OpenAI-compatible servers are asked for JSON output.
+    #[serde(default)]
+    pub json_mode: Option<bool>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,9 +25,29 @@ pub struct AiMessage {
     pub content: String,
 }
 
+/// Token counts reported by the provider for one or more calls.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+impl TokenUsage {
+    pub fn add(&mut self, other: &TokenUsage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.input_tokens == 0 && self.output_tokens == 0
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AiResponse {
     pub content: String,
+    #[serde(default)]
+    pub usage: Option<TokenUsage>,
 }
 
 #[derive(Debug)]
@@ -26,6 +55,21 @@ pub enum AiError {
     RateLimit { retry_after_secs: Option<u64> },
     ServerError(String),
     ClientError(String),
+    /// The API key was rejected.
+    Unauthorized(String),
+    /// The provider couldn't be reached at all.
+    Network(String),
+}
+
+/// The error for an unsuccessful HTTP response from a provider.
+pub fn status_error(label: &str, status: u16, retry_after_secs: Option<u64>, text: &str) -> AiError {
+    let message = format!("{} API error {}: {}", label, status, text);
+    match status {
+        429 => AiError::RateLimit { retry_after_secs },
+        401 | 403 => AiError::Unauthorized(message),
+        500..=599 => AiError::ServerError(message),
+        _ => AiError::ClientError(message),
+    }
 }
 
 impl fmt::Display for AiError {
@@ -40,12 +84,33 @@ impl fmt::Display for AiError {
             }
             AiError::ServerError(msg) => write!(f, "Server error: {}", msg),
             AiError::ClientError(msg) => write!(f, "Client error: {}", msg),
+            AiError::Unauthorized(msg) => write!(f, "Unauthorized: {}", msg),
+            AiError::Network(msg) => write!(f, "Network error: {}", msg),
         }
     }
 }
 
+/// The `field` of every object in the `list` array of a model listing, sorted.
+pub fn model_ids(data: &serde_json::Value, list: &str, field: &str) -> Vec<String> {
+    let mut ids: Vec<String> = data
+        .get(list)
+        .and_then(|l| l.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|m| m.get(field).and_then(|v| v.as_str()))
+                .map(|s| s.to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    ids.sort();
+    ids
+}
+
 #[async_trait]
 pub trait AiProvider: Send + Sync {
     async fn complete(&self, messages: Vec<AiMessage>) -> Result<AiResponse, AiError>;
+    /// Models this provider's account or server can use.
+    async fn list_models(&self) -> Result<Vec<String>, AiError>;
     fn name(&self) -> &str;
+    fn model(&self) -> &str;
 }
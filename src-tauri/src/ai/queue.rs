@@ -0,0 +1,186 @@
+use super::categorize::CategorizationRequest;
+use super::heuristics::TagDictionary;
+use crate::db::queries;
+use chrono::Utc;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, Notify};
+
+const WORKERS_SETTING: &str = "ai_queue_workers";
+const RATE_SETTING: &str = "ai_queue_requests_per_minute";
+const BATCH_SETTING: &str = "ai_queue_batch_size";
+
+/// A job interrupted this many times (e.g. the app quit mid-request) is dropped
+/// on the next start rather than retried forever.
+pub const MAX_ATTEMPTS: u32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct QueueSettings {
+    /// Groups of jobs processed at the same time.
+    pub workers: usize,
+    /// Provider requests allowed per minute across all workers.
+    pub requests_per_minute: u32,
+    /// Most jobs coalesced into one `categorize_batch` request.
+    pub batch_size: usize,
+}
+
+impl Default for QueueSettings {
+    fn default() -> Self {
+        Self {
+            workers: 2,
+            requests_per_minute: 20,
+            batch_size: 5,
+        }
+    }
+}
+
+fn setting<T: std::str::FromStr>(conn: &Connection, key: &str) -> Option<T> {
+    queries::get_setting(conn, key)
+        .ok()
+        .flatten()
+        .and_then(|v| v.trim().parse().ok())
+}
+
+pub fn load_settings(conn: &Connection) -> QueueSettings {
+    let defaults = QueueSettings::default();
+    QueueSettings {
+        workers: setting(conn, WORKERS_SETTING).unwrap_or(defaults.workers),
+        requests_per_minute: setting(conn, RATE_SETTING).unwrap_or(defaults.requests_per_minute),
+        batch_size: setting(conn, BATCH_SETTING).unwrap_or(defaults.batch_size),
+    }
+}
+
+pub fn save_settings(conn: &Connection, settings: &QueueSettings) -> Result<(), String> {
+    if !(1..=8).contains(&settings.workers) {
+        return Err("Workers must be between 1 and 8".to_string());
+    }
+    if !(1..=600).contains(&settings.requests_per_minute) {
+        return Err("Requests per minute must be between 1 and 600".to_string());
+    }
+    if !(1..=20).contains(&settings.batch_size) {
+        return Err("Batch size must be between 1 and 20".to_string());
+    }
+    for (key, value) in [
+        (WORKERS_SETTING, settings.workers.to_string()),
+        (RATE_SETTING, settings.requests_per_minute.to_string()),
+        (BATCH_SETTING, settings.batch_size.to_string()),
+    ] {
+        queries::set_setting(conn, key, &value).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// The fields a queued job should fill. Title and content are read from the item
+/// when the job runs, so edits made while it waits are what gets categorized.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct JobFields {
+    pub needs_type: bool,
+    pub needs_language: bool,
+    pub needs_tags: bool,
+    pub needs_folder: bool,
+    pub needs_description: bool,
+    pub needs_title: bool,
+}
+
+impl JobFields {
+    pub fn from_request(request: &CategorizationRequest) -> Self {
+        Self {
+            needs_type: request.needs_type,
+            needs_language: request.needs_language,
+            needs_tags: request.needs_tags,
+            needs_folder: request.needs_folder,
+            needs_description: request.needs_description,
+            needs_title: request.needs_title,
+        }
+    }
+
+    fn merge(&mut self, other: &JobFields) {
+        self.needs_type |= other.needs_type;
+        self.needs_language |= other.needs_language;
+        self.needs_tags |= other.needs_tags;
+        self.needs_folder |= other.needs_folder;
+        self.needs_description |= other.needs_description;
+        self.needs_title |= other.needs_title;
+    }
+
+    pub fn request(&self, title: &str, content: &str) -> CategorizationRequest {
+        CategorizationRequest {
+            title: title.to_string(),
+            content: content.to_string(),
+            needs_type: self.needs_type,
+            needs_language: self.needs_language,
+            needs_tags: self.needs_tags,
+            needs_folder: self.needs_folder,
+            needs_description: self.needs_description,
+            needs_title: self.needs_title,
+            existing_tags: Vec::new(),
+            existing_folders: Vec::new(),
+            vocabulary: Default::default(),
+            tag_synonyms: Default::default(),
+        }
+    }
+}
+
+pub fn parse_fields(json: &str) -> JobFields {
+    serde_json::from_str(json).unwrap_or_else(|e| {
+        eprintln!("Invalid queued enrichment job, ignoring its fields: {}", e);
+        JobFields::default()
+    })
+}
+
+/// Queue enrichment for an item. An item has at most one job; queueing it again
+/// widens that job to cover both requests.
+pub fn enqueue(conn: &Connection, item_id: &str, fields: JobFields) -> Result<(), String> {
+    let mut merged = fields;
+    if let Some(existing) = queries::get_ai_job(conn, item_id).map_err(|e| e.to_string())? {
+        merged.merge(&parse_fields(&existing.fields));
+    }
+    let json = serde_json::to_string(&merged).map_err(|e| e.to_string())?;
+    queries::upsert_ai_job(conn, item_id, &json, &Utc::now().to_rfc3339())
+        .map_err(|e| e.to_string())
+}
+
+/// In-memory side of the queue: wakes the dispatcher and spaces out requests.
+pub struct AiQueue {
+    pub wake: Notify,
+    pub active: AtomicUsize,
+    next_request: Mutex<Instant>,
+    /// Learned from existing items and kept in step with them by the workers,
+    /// so a batch only relearns the items that changed. Lock after `db`.
+    pub tag_dictionary: std::sync::Mutex<TagDictionary>,
+}
+
+impl Default for AiQueue {
+    fn default() -> Self {
+        Self {
+            wake: Notify::new(),
+            active: AtomicUsize::new(0),
+            next_request: Mutex::new(Instant::now()),
+            tag_dictionary: std::sync::Mutex::new(TagDictionary::default()),
+        }
+    }
+}
+
+impl AiQueue {
+    pub fn active_workers(&self) -> usize {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    /// Wait for the next request slot so that, across all workers, no more than
+    /// `requests_per_minute` requests start in any minute.
+    pub async fn throttle(&self, requests_per_minute: u32) {
+        let interval = Duration::from_secs(60) / requests_per_minute.max(1);
+        let wait = {
+            let mut next = self.next_request.lock().await;
+            let now = Instant::now();
+            let slot = (*next).max(now);
+            *next = slot + interval;
+            slot - now
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
@@ -14,7 +14,7 @@ pub async fn complete_with_retry(
         let msgs = messages.clone();
         match provider.complete(msgs).await {
             Ok(response) => return Ok(response),
-            Err(AiError::ClientError(msg)) => {
+            Err(AiError::ClientError(msg)) | Err(AiError::Unauthorized(msg)) => {
                 return Err(msg);
             }
             Err(AiError::RateLimit { retry_after_secs }) => {
@@ -32,7 +32,7 @@ pub async fn complete_with_retry(
                 sleep(Duration::from_secs(delay)).await;
                 last_error = "Rate limited".to_string();
             }
-            Err(AiError::ServerError(msg)) => {
+            Err(AiError::ServerError(msg)) | Err(AiError::Network(msg)) => {
                 if attempt == MAX_RETRIES {
                     return Err(format!("Server error after {} retries: {}", MAX_RETRIES, msg));
                 }
@@ -53,3 +53,94 @@ pub async fn complete_with_retry(
 
     Err(last_error)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::mock::{MockProvider, MockReply};
+
+    fn messages() -> Vec<AiMessage> {
+        vec![AiMessage {
+            role: "user".to_string(),
+            content: "ping".to_string(),
+        }]
+    }
+
+    fn content(text: &str) -> MockReply {
+        MockReply::Content {
+            content: text.to_string(),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retries_rate_limits_and_server_errors_until_success() {
+        let mock = MockProvider::with_script(
+            "mock",
+            vec![
+                MockReply::RateLimit {
+                    retry_after_secs: Some(1),
+                },
+                MockReply::ServerError {
+                    message: "overloaded".to_string(),
+                },
+                content("{\"ok\": true}"),
+            ],
+        );
+
+        let response = complete_with_retry(&mock, messages()).await.unwrap();
+
+        assert_eq!(response.content, "{\"ok\": true}");
+        assert_eq!(mock.requests().len(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn client_errors_are_not_retried() {
+        let mock = MockProvider::with_script(
+            "mock",
+            vec![
+                MockReply::ClientError {
+                    message: "bad model".to_string(),
+                },
+                content("never reached"),
+            ],
+        );
+
+        let err = complete_with_retry(&mock, messages()).await.unwrap_err();
+
+        assert_eq!(err, "bad model");
+        assert_eq!(mock.requests().len(), 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn gives_up_after_max_retries() {
+        let script = (0..=MAX_RETRIES)
+            .map(|_| MockReply::RateLimit {
+                retry_after_secs: None,
+            })
+            .collect();
+        let mock = MockProvider::with_script("mock", script);
+
+        let err = complete_with_retry(&mock, messages()).await.unwrap_err();
+
+        assert_eq!(err, format!("Rate limited after {} retries", MAX_RETRIES));
+        assert_eq!(mock.requests().len(), MAX_RETRIES as usize + 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn honours_retry_after() {
+        let mock = MockProvider::with_script(
+            "mock",
+            vec![
+                MockReply::RateLimit {
+                    retry_after_secs: Some(30),
+                },
+                content("{}"),
+            ],
+        );
+
+        let started = tokio::time::Instant::now();
+        complete_with_retry(&mock, messages()).await.unwrap();
+
+        assert_eq!(started.elapsed().as_secs(), 30);
+    }
+}
@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+
+use super::prompts::PromptTemplate;
+use super::provider::{AiMessage, AiProvider, TokenUsage};
+use super::retry::complete_with_retry;
+use crate::db::synonyms::same_tag;
+
+/// Tags are listed one per line, so this bounds the prompt for huge tag sets.
+const MAX_TAGS: usize = 500;
+
+/// Existing tags the model thinks mean the same thing as `target`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TagMergeSuggestion {
+    pub target: String,
+    pub sources: Vec<String>,
+}
+
+/// The system and user messages asking which of `tags` are synonyms.
+pub fn build_tag_merge_messages(tags: &[String], template: &PromptTemplate) -> Vec<AiMessage> {
+    let tags = &tags[..tags.len().min(MAX_TAGS)];
+    let list: String = tags.iter().map(|t| format!("- {}\n", t)).collect();
+
+    vec![
+        AiMessage {
+            role: "system".to_string(),
+            content: template.render(&[("tag_count", tags.len().to_string())]),
+        },
+        AiMessage {
+            role: "user".to_string(),
+            content: format!("Here are {} tags:\n\n{}", tags.len(), list),
+        },
+    ]
+}
+
+pub async fn suggest_tag_merges(
+    provider: &dyn AiProvider,
+    tags: &[String],
+    template: &PromptTemplate,
+    usage: &mut TokenUsage,
+) -> Result<Vec<TagMergeSuggestion>, String> {
+    if tags.len() < 2 {
+        return Ok(Vec::new());
+    }
+    let messages = build_tag_merge_messages(tags, template);
+    let response = complete_with_retry(provider, messages).await?;
+    if let Some(ref u) = response.usage {
+        usage.add(u);
+    }
+    parse_tag_merge_response(&response.content, tags)
+}
+
+/// Suggestions whose sources are all in `existing`. A tag is only merged once:
+/// later groups that mention it again lose it, and groups left with nothing to
+/// merge are dropped.
+pub fn parse_tag_merge_response(
+    raw: &str,
+    existing: &[String],
+) -> Result<Vec<TagMergeSuggestion>, String> {
+    let json_str = raw.trim();
+    let json_str = if json_str.starts_with("```") {
+        let start = json_str.find('[').unwrap_or(0);
+        let end = json_str.rfind(']').map(|i| i + 1).unwrap_or(json_str.len());
+        &json_str[start..end]
+    } else {
+        json_str
+    };
+
+    let parsed: Vec<TagMergeSuggestion> = serde_json::from_str(json_str)
+        .map_err(|e| format!("Failed to parse tag merge response: {}", e))?;
+
+    // The model may change a tag's case; use the spelling the items have
+    let find_existing = |name: &str| existing.iter().find(|e| same_tag(e, name)).cloned();
+    let mut claimed: Vec<String> = Vec::new();
+    let is_claimed = |claimed: &[String], tag: &str| claimed.iter().any(|c| same_tag(c, tag));
+    let mut suggestions = Vec::new();
+    for suggestion in parsed {
+        let target = suggestion.target.trim();
+        let target = find_existing(target).unwrap_or_else(|| target.to_string());
+        if target.is_empty() || target.contains(',') || is_claimed(&claimed, &target) {
+            continue;
+        }
+        let mut sources: Vec<String> = Vec::new();
+        for source in suggestion.sources {
+            let Some(source) = find_existing(source.trim()) else {
+                continue;
+            };
+            if !same_tag(&source, &target)
+                && !is_claimed(&claimed, &source)
+                && !is_claimed(&sources, &source)
+            {
+                sources.push(source);
+            }
+        }
+        if sources.is_empty() {
+            continue;
+        }
+        claimed.push(target.clone());
+        claimed.extend(sources.iter().cloned());
+        suggestions.push(TagMergeSuggestion { target, sources });
+    }
+    Ok(suggestions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::mock::MockProvider;
+    use crate::ai::prompts::PromptKind;
+
+    fn tags(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn keeps_only_existing_tags_merged_once() {
+        let raw = r#"[
+            {"target": "kubernetes", "sources": ["K8s", "kube", "kubernetes"]},
+            {"target": "k8s-tools", "sources": ["k8s", "helm"]},
+            {"target": "javascript", "sources": ["cobol"]}
+        ]"#;
+
+        let suggestions =
+            parse_tag_merge_response(raw, &tags(&["k8s", "kubernetes", "helm", "js"])).unwrap();
+
+        assert_eq!(
+            suggestions,
+            vec![
+                TagMergeSuggestion {
+                    target: "kubernetes".to_string(),
+                    sources: tags(&["k8s"]),
+                },
+                TagMergeSuggestion {
+                    target: "k8s-tools".to_string(),
+                    sources: tags(&["helm"]),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn matches_existing_tags_ignoring_case() {
+        let raw = r#"[{"target": "kubernetes", "sources": ["k8s", "KUBE"]}]"#;
+
+        let suggestions =
+            parse_tag_merge_response(raw, &tags(&["K8s", "Kubernetes", "kube"])).unwrap();
+
+        assert_eq!(
+            suggestions,
+            vec![TagMergeSuggestion {
+                target: "Kubernetes".to_string(),
+                sources: tags(&["K8s", "kube"]),
+            }]
+        );
+    }
+
+    #[test]
+    fn strips_markdown_fences_and_rejects_invalid_json() {
+        let raw = "```json\n[{\"target\": \"javascript\", \"sources\": [\"js\"]}]\n```";
+
+        assert_eq!(
+            parse_tag_merge_response(raw, &tags(&["js"])).unwrap().len(),
+            1
+        );
+        assert!(parse_tag_merge_response("{}", &tags(&["js"])).is_err());
+    }
+
+    #[tokio::test]
+    async fn lists_every_tag_in_one_request() {
+        let mock = MockProvider::replying(r#"[{"target": "javascript", "sources": ["js"]}]"#);
+        let template = PromptTemplate::default_for(PromptKind::TagMerges);
+        let mut usage = TokenUsage::default();
+
+        let suggestions =
+            suggest_tag_merges(&mock, &tags(&["js", "javascript"]), &template, &mut usage)
+                .await
+                .unwrap();
+
+        assert_eq!(suggestions[0].sources, tags(&["js"]));
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        assert!(requests[0][1].content.contains("- js\n- javascript\n"));
+    }
+}
@@ -1,35 +1,28 @@
-use super::provider::{AiMessage, AiProvider};
+use super::prompts::PromptTemplate;
+use super::provider::{AiMessage, AiProvider, TokenUsage};
 use super::retry::complete_with_retry;
 
-pub async fn generate_taxonomy(
-    provider: &dyn AiProvider,
+/// The system and user messages asking for a folder structure covering `items`.
+pub fn build_taxonomy_messages(
     items: &[crate::db::queries::Item],
     max_folders: usize,
-) -> Result<Vec<String>, String> {
+    template: &PromptTemplate,
+) -> Vec<AiMessage> {
     let mut summaries = String::new();
     for item in items {
         let preview: String = item.content.chars().take(100).collect();
         summaries.push_str(&format!("- {}: {}\n", item.title, preview));
     }
 
-    let system_prompt = format!(
-        "You are a knowledge base organizer. Given a list of developer items \
-        (commands, snippets, configs, notes), design a hierarchical folder structure that \
-        logically organizes them all.\n\n\
-        Rules:\n\
-        - Each folder path must start with \"/\"\n\
-        - Use 1-2 levels of hierarchy (e.g. \"/docker\", \"/git/workflows\")\n\
-        - Return EXACTLY {} folders — no more, no fewer. Group broadly so every item fits.\n\
-        - Use lowercase, short descriptive names\n\
-        - Prefer broad categories over narrow ones. Multiple related items should share a folder.\n\n\
-        Return ONLY a JSON array of folder path strings, no markdown fences, no explanation.",
-        max_folders
-    );
-
-    let messages = vec![
+    let system_prompt = template.render(&[
+        ("max_folders", max_folders.to_string()),
+        ("item_count", items.len().to_string()),
+    ]);
+
+    vec![
         AiMessage {
             role: "system".to_string(),
-            content: system_prompt.to_string(),
+            content: system_prompt,
         },
         AiMessage {
             role: "user".to_string(),
@@ -39,13 +32,25 @@ pub async fn generate_taxonomy(
                 summaries
             ),
         },
-    ];
+    ]
+}
 
+pub async fn generate_taxonomy(
+    provider: &dyn AiProvider,
+    items: &[crate::db::queries::Item],
+    max_folders: usize,
+    template: &PromptTemplate,
+    usage: &mut TokenUsage,
+) -> Result<Vec<String>, String> {
+    let messages = build_taxonomy_messages(items, max_folders, template);
     let response = complete_with_retry(provider, messages).await?;
+    if let Some(ref u) = response.usage {
+        usage.add(u);
+    }
     parse_taxonomy_response(&response.content, max_folders)
 }
 
-fn parse_taxonomy_response(raw: &str, max_folders: usize) -> Result<Vec<String>, String> {
+pub fn parse_taxonomy_response(raw: &str, max_folders: usize) -> Result<Vec<String>, String> {
     let json_str = raw.trim();
     let json_str = if json_str.starts_with("```") {
         let start = json_str.find('[').unwrap_or(0);
@@ -79,3 +84,74 @@ fn parse_taxonomy_response(raw: &str, max_folders: usize) -> Result<Vec<String>,
 
     Ok(folders)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::mock::MockProvider;
+    use crate::ai::prompts::PromptKind;
+    use crate::db::queries::Item;
+
+    fn item(title: &str, content: &str) -> Item {
+        Item {
+            id: title.to_lowercase(),
+            title: title.to_string(),
+            item_type: "note".to_string(),
+            language: String::new(),
+            tags: Vec::new(),
+            folder: "/".to_string(),
+            description: String::new(),
+            content: content.to_string(),
+            file_path: String::new(),
+            file_hash: String::new(),
+            created: String::new(),
+            modified: String::new(),
+            trusted: false,
+            pinned: false,
+            slot: None,
+            ai_opt_out: false,
+            encrypted: false,
+            ciphertext: String::new(),
+        }
+    }
+
+    #[test]
+    fn prefixes_folders_and_caps_the_count() {
+        let raw = r#"["docker", "/git/workflows", "/misc"]"#;
+
+        let folders = parse_taxonomy_response(raw, 2).unwrap();
+
+        assert_eq!(folders, vec!["/docker", "/git/workflows"]);
+    }
+
+    #[test]
+    fn strips_markdown_fences() {
+        let raw = "```json\n[\"/docker\"]\n```";
+
+        assert_eq!(parse_taxonomy_response(raw, 10).unwrap(), vec!["/docker"]);
+    }
+
+    #[test]
+    fn rejects_empty_and_invalid_taxonomies() {
+        assert!(parse_taxonomy_response("[]", 10).is_err());
+        assert!(parse_taxonomy_response("{\"folders\": []}", 10).is_err());
+    }
+
+    #[tokio::test]
+    async fn asks_for_the_configured_number_of_folders() {
+        let mock = MockProvider::replying(r#"["/docker", "/git"]"#);
+        let template = PromptTemplate::default_for(PromptKind::Taxonomy);
+        let items = vec![item("Containers", "docker ps"), item("Branches", "git branch")];
+        let mut usage = TokenUsage::default();
+
+        let folders = generate_taxonomy(&mock, &items, 7, &template, &mut usage)
+            .await
+            .unwrap();
+
+        assert_eq!(folders, vec!["/docker", "/git"]);
+        let requests = mock.requests();
+        assert!(requests[0][0].content.contains("EXACTLY 7 folders"));
+        assert!(requests[0][1].content.contains("- Containers: docker ps"));
+        assert!(!usage.is_empty());
+    }
+}
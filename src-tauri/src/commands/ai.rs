@@ -1,15 +1,26 @@
 use crate::ai::categorize;
-use crate::ai::provider::AiConfig;
+use crate::ai::ledger::{self, Price};
+use crate::ai::provider::{AiConfig, AiError, AiMessage, AiProvider};
+use crate::ai::redact::{self, CustomPattern, Redaction, RedactingProvider, Redactor};
+use crate::db::queries;
+use crate::secrets::store;
 use crate::state::AppState;
+use chrono::{Duration, Utc};
 use rusqlite::params;
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Manager, State};
 
 #[derive(Debug, Serialize)]
 pub struct AiSettings {
     pub provider: String,
     pub model: String,
     pub base_url: String,
+    pub api_version: String,
+    pub auth_header: String,
+    pub json_mode: bool,
     pub has_api_key: bool,
     pub is_configured: bool,
 }
@@ -20,6 +31,45 @@ pub struct AiSettingsInput {
     pub model: String,
     pub api_key: Option<String>,
     pub base_url: Option<String>,
+    #[serde(default)]
+    pub api_version: Option<String>,
+    #[serde(default)]
+    pub auth_header: Option<String>,
+    #[serde(default)]
+    pub json_mode: Option<bool>,
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.filter(|v| !v.trim().is_empty())
+}
+
+/// Secret-store key the provider API key is saved under.
+const API_KEY_SECRET: &str = "ai_api_key";
+
+/// The saved API key, from the OS keyring (or its encrypted-file fallback).
+/// Keyring calls can block, so read it before taking the DB lock.
+pub fn saved_api_key() -> Option<String> {
+    store::get(API_KEY_SECRET)
+        .map_err(|e| eprintln!("Failed to read AI API key: {}", e))
+        .ok()
+        .flatten()
+        .filter(|k| !k.is_empty())
+}
+
+/// Move an API key saved as plaintext by older versions into the secret store.
+pub fn migrate_api_key(db: &rusqlite::Connection) {
+    let Some(key) = get_ai_setting(db, "ai_api_key") else {
+        return;
+    };
+    if !key.is_empty() {
+        if let Err(e) = store::set(API_KEY_SECRET, &key) {
+            eprintln!("Failed to move AI API key to the keyring, keeping it in place: {}", e);
+            return;
+        }
+    }
+    if let Err(e) = queries::delete_setting(db, "ai_api_key") {
+        eprintln!("Failed to remove plaintext AI API key: {}", e);
+    }
 }
 
 fn get_ai_setting(db: &rusqlite::Connection, key: &str) -> Option<String> {
@@ -39,13 +89,14 @@ fn set_ai_setting(db: &rusqlite::Connection, key: &str, value: &str) -> Result<(
     Ok(())
 }
 
-pub fn read_ai_config(db: &rusqlite::Connection) -> Option<AiConfig> {
+/// The saved settings with `api_key` (from `saved_api_key`), if they are complete.
+pub fn read_ai_config(db: &rusqlite::Connection, api_key: Option<String>) -> Option<AiConfig> {
     let provider = get_ai_setting(db, "ai_provider")?;
     let model = get_ai_setting(db, "ai_model").unwrap_or_default();
-    let api_key = get_ai_setting(db, "ai_api_key").unwrap_or_default();
-    let base_url = get_ai_setting(db, "ai_base_url");
+    let api_key = api_key.unwrap_or_default();
+    let base_url = non_empty(get_ai_setting(db, "ai_base_url"));
 
-    if provider.is_empty() || (provider != "ollama" && api_key.is_empty()) {
+    if provider.is_empty() || (categorize::requires_api_key(&provider) && api_key.is_empty()) {
         return None;
     }
 
@@ -54,11 +105,45 @@ pub fn read_ai_config(db: &rusqlite::Connection) -> Option<AiConfig> {
         model,
         api_key,
         base_url,
+        api_version: non_empty(get_ai_setting(db, "ai_api_version")),
+        auth_header: non_empty(get_ai_setting(db, "ai_auth_header")),
+        json_mode: get_ai_setting(db, "ai_json_mode").map(|v| v != "false"),
+    })
+}
+
+/// Settings from the panel, with the saved API key filling in for one that
+/// wasn't re-entered. Without input, the saved settings.
+fn config_for(state: &AppState, input: Option<AiSettingsInput>) -> Result<AiConfig, String> {
+    let Some(input) = input else {
+        let api_key = saved_api_key();
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        return read_ai_config(&db, api_key).ok_or_else(|| "AI is not configured".to_string());
+    };
+    let api_key = input
+        .api_key
+        .filter(|k| !k.is_empty())
+        .or_else(saved_api_key)
+        .unwrap_or_default();
+    if categorize::requires_api_key(&input.provider) && api_key.is_empty() {
+        return Err("An API key is required".to_string());
+    }
+    Ok(AiConfig {
+        provider: input.provider,
+        model: input.model,
+        api_key,
+        base_url: non_empty(input.base_url),
+        api_version: non_empty(input.api_version),
+        auth_header: non_empty(input.auth_header),
+        json_mode: input.json_mode,
     })
 }
 
-pub async fn rebuild_ai_provider(state: &AppState) {
-    let config = {
+/// Recreate the shared provider from the saved settings. Every message it sends
+/// goes through the redaction pass first, and what was masked is logged.
+pub async fn rebuild_ai_provider(app_handle: &AppHandle) {
+    let state = app_handle.state::<AppState>();
+    let api_key = saved_api_key();
+    let (config, redactor) = {
         let db = match state.db.lock() {
             Ok(db) => db,
             Err(e) => {
@@ -66,31 +151,67 @@ pub async fn rebuild_ai_provider(state: &AppState) {
                 return;
             }
         };
-        read_ai_config(&db)
+        (read_ai_config(&db, api_key), Redactor::load(&db))
     };
 
-    let provider = config.map(|c| categorize::create_provider(&c));
+    let provider = config.and_then(|c| {
+        categorize::create_provider(&c)
+            .map_err(|e| eprintln!("Failed to create AI provider: {}", e))
+            .ok()
+    });
+    let provider = provider.map(|inner| -> Arc<dyn AiProvider> {
+        let log_handle = app_handle.clone();
+        Arc::new(RedactingProvider::new(
+            inner,
+            redactor,
+            Box::new(move |provider, redactions| {
+                log_redactions(&log_handle, provider, redactions)
+            }),
+        ))
+    });
     let mut guard = state.ai_provider.write().await;
     *guard = provider;
 }
 
+fn log_redactions(app_handle: &AppHandle, provider: &dyn AiProvider, redactions: &[Redaction]) {
+    let state = app_handle.state::<AppState>();
+    let Ok(db) = state.db.lock() else {
+        return;
+    };
+    let entries: Vec<(String, String)> = redactions
+        .iter()
+        .map(|r| (r.rule.clone(), r.fingerprint.clone()))
+        .collect();
+    if let Err(e) =
+        queries::insert_redactions(&db, provider.name(), &entries, &Utc::now().to_rfc3339())
+    {
+        eprintln!("Failed to log redactions: {}", e);
+    }
+}
+
 #[tauri::command]
 pub fn get_ai_settings(state: State<AppState>) -> Result<AiSettings, String> {
+    let has_api_key = saved_api_key().is_some();
     let db = state.db.lock().map_err(|e| e.to_string())?;
 
     let provider = get_ai_setting(&db, "ai_provider").unwrap_or_default();
     let model = get_ai_setting(&db, "ai_model").unwrap_or_default();
     let base_url = get_ai_setting(&db, "ai_base_url").unwrap_or_default();
-    let has_api_key = get_ai_setting(&db, "ai_api_key")
-        .map(|k| !k.is_empty())
-        .unwrap_or(false);
+    let api_version = get_ai_setting(&db, "ai_api_version").unwrap_or_default();
+    let auth_header = get_ai_setting(&db, "ai_auth_header").unwrap_or_default();
+    let json_mode = get_ai_setting(&db, "ai_json_mode").is_none_or(|v| v != "false");
+    drop(db);
 
-    let is_configured = !provider.is_empty() && (provider == "ollama" || has_api_key);
+    let is_configured =
+        !provider.is_empty() && (!categorize::requires_api_key(&provider) || has_api_key);
 
     Ok(AiSettings {
         provider,
         model,
         base_url,
+        api_version,
+        auth_header,
+        json_mode,
         has_api_key,
         is_configured,
     })
@@ -98,23 +219,229 @@ pub fn get_ai_settings(state: State<AppState>) -> Result<AiSettings, String> {
 
 #[tauri::command]
 pub async fn set_ai_settings(
+    app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
     input: AiSettingsInput,
 ) -> Result<AiSettings, String> {
+    // Reject unknown providers and missing endpoints before anything is saved
+    categorize::create_provider(&AiConfig {
+        provider: input.provider.clone(),
+        model: input.model.clone(),
+        api_key: String::new(),
+        base_url: non_empty(input.base_url.clone()),
+        api_version: None,
+        auth_header: None,
+        json_mode: None,
+    })?;
+
+    // The key goes first and without the DB lock: if the keyring refuses it,
+    // none of the settings change
+    match input.api_key.as_deref() {
+        Some("") => store::delete(API_KEY_SECRET)?,
+        Some(key) => store::set(API_KEY_SECRET, key)?,
+        None => {}
+    }
     {
         let db = state.db.lock().map_err(|e| e.to_string())?;
         set_ai_setting(&db, "ai_provider", &input.provider)?;
         set_ai_setting(&db, "ai_model", &input.model)?;
-        if let Some(ref key) = input.api_key {
-            set_ai_setting(&db, "ai_api_key", key)?;
-        }
         set_ai_setting(
             &db,
             "ai_base_url",
             &input.base_url.unwrap_or_default(),
         )?;
+        set_ai_setting(&db, "ai_api_version", &input.api_version.unwrap_or_default())?;
+        set_ai_setting(&db, "ai_auth_header", &input.auth_header.unwrap_or_default())?;
+        set_ai_setting(
+            &db,
+            "ai_json_mode",
+            if input.json_mode.unwrap_or(true) { "true" } else { "false" },
+        )?;
     }
 
-    rebuild_ai_provider(&state).await;
+    rebuild_ai_provider(&app_handle).await;
+    let _ = app_handle.emit("ai-settings-changed", ());
     get_ai_settings(state)
 }
+
+/// Drop every cached AI response so the next enrichment or recategorize asks the
+/// provider again. Returns the number of entries removed.
+#[tauri::command]
+pub fn clear_ai_cache(state: State<AppState>) -> Result<usize, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    queries::clear_ai_cache(&db).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Serialize)]
+pub struct AiUsageReport {
+    pub daily: Vec<queries::AiUsageRollup>,
+    pub monthly: Vec<queries::AiUsageRollup>,
+    pub month_to_date_usd: f64,
+    pub monthly_budget_usd: Option<f64>,
+    pub budget_exceeded: bool,
+}
+
+/// Token and cost totals per provider/model, by day for the last `days` days
+/// (default 30) and by month for the last year.
+#[tauri::command]
+pub fn get_ai_usage(state: State<AppState>, days: Option<u32>) -> Result<AiUsageReport, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let now = Utc::now();
+    let daily_since = (now - Duration::days(days.unwrap_or(30).into())).to_rfc3339();
+    let monthly_since = (now - Duration::days(365)).to_rfc3339();
+
+    let daily = queries::ai_usage_rollup(&db, "%Y-%m-%d", &daily_since).map_err(|e| e.to_string())?;
+    let monthly =
+        queries::ai_usage_rollup(&db, "%Y-%m", &monthly_since).map_err(|e| e.to_string())?;
+    let month_to_date_usd = ledger::month_to_date_cost(&db);
+    let monthly_budget_usd = ledger::monthly_budget(&db);
+
+    Ok(AiUsageReport {
+        daily,
+        monthly,
+        month_to_date_usd,
+        monthly_budget_usd,
+        budget_exceeded: monthly_budget_usd.is_some_and(|b| month_to_date_usd >= b),
+    })
+}
+
+#[tauri::command]
+pub fn get_ai_price_table(state: State<AppState>) -> Result<BTreeMap<String, Price>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    Ok(ledger::price_table(&db))
+}
+
+/// Save per-model prices (USD per million tokens). Entries override the built-in
+/// price for the same model prefix.
+#[tauri::command]
+pub fn set_ai_price_table(
+    state: State<AppState>,
+    prices: BTreeMap<String, Price>,
+) -> Result<BTreeMap<String, Price>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    ledger::save_price_table(&db, &prices)?;
+    Ok(ledger::price_table(&db))
+}
+
+/// Set the monthly spend limit in USD, or clear it with `None`. Once month-to-date
+/// spend reaches it, background enrichment stops calling the provider.
+#[tauri::command]
+pub fn set_ai_budget(state: State<AppState>, budget_usd: Option<f64>) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    ledger::set_monthly_budget(&db, budget_usd)
+}
+
+#[derive(Debug, Serialize)]
+pub struct ConnectionTest {
+    pub ok: bool,
+    pub latency_ms: u64,
+    /// "unauthorized", "rate_limited", "network", "server" or "client".
+    pub error_kind: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Send one tiny request with the given settings (or the saved ones) and report
+/// how long it took or why it failed. Nothing is retried, so the error is the
+/// provider's first answer.
+#[tauri::command]
+pub async fn test_ai_connection(
+    state: State<'_, AppState>,
+    input: Option<AiSettingsInput>,
+) -> Result<ConnectionTest, String> {
+    let config = config_for(&state, input)?;
+    let provider = categorize::create_provider(&config)?;
+    let messages = vec![
+        AiMessage {
+            role: "system".to_string(),
+            content: "Reply with the JSON object {\"ok\": true} and nothing else.".to_string(),
+        },
+        AiMessage {
+            role: "user".to_string(),
+            content: "ping".to_string(),
+        },
+    ];
+
+    let started = Instant::now();
+    let outcome = provider.complete(messages).await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    Ok(match outcome {
+        Ok(response) => {
+            if let (Some(usage), Ok(db)) = (response.usage, state.db.lock()) {
+                ledger::record(&db, "test_connection", provider.as_ref(), &usage);
+            }
+            ConnectionTest {
+                ok: true,
+                latency_ms,
+                error_kind: None,
+                error: None,
+            }
+        }
+        Err(e) => {
+            let kind = match e {
+                AiError::Unauthorized(_) => "unauthorized",
+                AiError::RateLimit { .. } => "rate_limited",
+                AiError::Network(_) => "network",
+                AiError::ServerError(_) => "server",
+                AiError::ClientError(_) => "client",
+            };
+            ConnectionTest {
+                ok: false,
+                latency_ms,
+                error_kind: Some(kind.to_string()),
+                error: Some(e.to_string()),
+            }
+        }
+    })
+}
+
+/// Model names the provider offers, for the settings panel to choose from.
+#[tauri::command]
+pub async fn list_ai_models(
+    state: State<'_, AppState>,
+    input: Option<AiSettingsInput>,
+) -> Result<Vec<String>, String> {
+    let config = config_for(&state, input)?;
+    categorize::create_provider(&config)?
+        .list_models()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// The most recent masked values, newest first (default 200).
+#[tauri::command]
+pub fn list_redaction_log(
+    state: State<AppState>,
+    limit: Option<usize>,
+) -> Result<Vec<queries::RedactionLogEntry>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    queries::list_redactions(&db, limit.unwrap_or(200)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn clear_redaction_log(state: State<AppState>) -> Result<usize, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    queries::clear_redactions(&db).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_redaction_patterns(state: State<AppState>) -> Result<Vec<CustomPattern>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    Ok(redact::load_patterns(&db))
+}
+
+/// Save user-defined patterns, masked in addition to the built-in rules. Each must
+/// be a valid regex; with a capture group only the group is masked.
+#[tauri::command]
+pub async fn set_redaction_patterns(
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+    patterns: Vec<CustomPattern>,
+) -> Result<Vec<CustomPattern>, String> {
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        redact::save_patterns(&db, &patterns)?;
+    }
+    rebuild_ai_provider(&app_handle).await;
+    Ok(patterns)
+}
@@ -0,0 +1,539 @@
+use crate::ai::cache;
+use crate::ai::categorize::{self, CategorizationRequest, CategorizationResult};
+use crate::ai::heuristics;
+use crate::ai::ledger;
+use crate::ai::prompts::{self, PromptKind, PromptTemplate};
+use crate::ai::provider::{AiProvider, TokenUsage};
+use crate::ai::queue::{self, JobFields, QueueSettings};
+use crate::db::queries::{self, AiJob, Item};
+use crate::db::{synonyms, vocabulary};
+use crate::files::{links, markdown};
+use crate::graph::related;
+use crate::state::AppState;
+use chrono::Utc;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+
+/// How long the dispatcher sleeps with nothing to do before checking the table
+/// again, in case a wake-up was missed.
+const IDLE_POLL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct QueueStatus {
+    pub pending: usize,
+    pub running: usize,
+    pub active_workers: usize,
+    pub settings: QueueSettings,
+    pub budget_exceeded: bool,
+}
+
+/// Queue background enrichment for an item and wake the dispatcher.
+pub fn enqueue(app_handle: &AppHandle, item_id: &str, request: &CategorizationRequest) {
+    let state = app_handle.state::<AppState>();
+    let queued = match state.db.lock() {
+        Ok(db) => queue::enqueue(&db, item_id, JobFields::from_request(request)),
+        Err(e) => Err(e.to_string()),
+    };
+    match queued {
+        Ok(()) => state.ai_queue.wake.notify_one(),
+        Err(e) => eprintln!("Failed to queue enrichment for {}: {}", item_id, e),
+    }
+}
+
+/// Requeue jobs left over from the last run and start the dispatcher, which
+/// hands groups of pending jobs to at most `workers` tasks at a time.
+pub fn start(app_handle: &AppHandle) {
+    let state = app_handle.state::<AppState>();
+    if let Ok(db) = state.db.lock() {
+        match queries::recover_ai_jobs(&db, queue::MAX_ATTEMPTS) {
+            Ok(n) if n > 0 => println!("Resuming {} queued enrichment job(s)", n),
+            Ok(_) => {}
+            Err(e) => eprintln!("Failed to recover enrichment jobs: {}", e),
+        }
+    }
+
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        let state = app_handle.state::<AppState>();
+        loop {
+            let settings = match state.db.lock() {
+                Ok(db) => queue::load_settings(&db),
+                Err(_) => return,
+            };
+            if state.ai_queue.active_workers() >= settings.workers {
+                // A worker finishing wakes us
+                state.ai_queue.wake.notified().await;
+                continue;
+            }
+
+            let provider: Option<Arc<dyn AiProvider>> = state.ai_provider.read().await.clone();
+            let jobs = match state.db.lock() {
+                Ok(db) => {
+                    queries::claim_ai_jobs(&db, settings.batch_size, &Utc::now().to_rfc3339())
+                        .unwrap_or_else(|e| {
+                            eprintln!("Failed to claim enrichment jobs: {}", e);
+                            Vec::new()
+                        })
+                }
+                Err(_) => return,
+            };
+            if jobs.is_empty() {
+                let _ = tokio::time::timeout(IDLE_POLL, state.ai_queue.wake.notified()).await;
+                continue;
+            }
+
+            state.ai_queue.active.fetch_add(1, Ordering::SeqCst);
+            let worker_handle = app_handle.clone();
+            tauri::async_runtime::spawn(async move {
+                process(&worker_handle, jobs, provider, settings).await;
+                let state = worker_handle.state::<AppState>();
+                state.ai_queue.active.fetch_sub(1, Ordering::SeqCst);
+                state.ai_queue.wake.notify_one();
+            });
+        }
+    });
+}
+
+/// A claimed job with everything needed to categorize it.
+struct Prepared {
+    job: AiJob,
+    request: CategorizationRequest,
+    /// The request minus what the local classifier already answered.
+    ai_request: CategorizationRequest,
+    local: CategorizationResult,
+}
+
+impl Prepared {
+    fn needs_ai(&self) -> bool {
+        let r = &self.ai_request;
+        r.needs_type
+            || r.needs_language
+            || r.needs_tags
+            || r.needs_folder
+            || r.needs_description
+            || r.needs_title
+    }
+}
+
+/// Bring the tag dictionary up to date with the items table, relearning only
+/// the items whose files changed since the last batch.
+fn refresh_dictionary(conn: &Connection, dictionary: &mut heuristics::TagDictionary) {
+    let hashes: HashMap<String, String> = match queries::list_item_hashes(conn) {
+        Ok(hashes) => hashes.into_iter().collect(),
+        Err(e) => {
+            eprintln!("Failed to list items for the tag dictionary: {}", e);
+            return;
+        }
+    };
+    for id in dictionary.item_ids() {
+        if !hashes.contains_key(&id) {
+            dictionary.remove(&id);
+        }
+    }
+    let stale: Vec<&String> = hashes
+        .iter()
+        .filter(|(id, hash)| !dictionary.is_current(id, hash))
+        .map(|(id, _)| id)
+        .collect();
+    if stale.is_empty() {
+        return;
+    }
+    if dictionary.is_empty() {
+        // First batch since startup: one query beats one per item
+        *dictionary =
+            heuristics::TagDictionary::learn(&queries::list_items(conn).unwrap_or_default());
+        return;
+    }
+    for id in stale {
+        if let Ok(Some(item)) = queries::get_item(conn, id) {
+            dictionary.add(&item);
+        }
+    }
+}
+
+async fn process(
+    app_handle: &AppHandle,
+    jobs: Vec<AiJob>,
+    provider: Option<Arc<dyn AiProvider>>,
+    settings: QueueSettings,
+) {
+    let state = app_handle.state::<AppState>();
+
+    // Gather existing vocabulary and run the local classifier (don't hold lock across await)
+    let (prepared, single_prompt, batch_prompt, over_budget) = {
+        let db = match state.db.lock() {
+            Ok(db) => db,
+            Err(_) => return,
+        };
+        let existing_tags = queries::get_all_tags(&db).unwrap_or_default();
+        let existing_folders = queries::get_all_folders(&db).unwrap_or_default();
+        let vocabulary = vocabulary::load(&db);
+        let tag_synonyms = synonyms::load(&db);
+        let mut dictionary = match state.ai_queue.tag_dictionary.lock() {
+            Ok(dictionary) => dictionary,
+            Err(_) => return,
+        };
+        refresh_dictionary(&db, &mut dictionary);
+
+        let mut prepared = Vec::with_capacity(jobs.len());
+        for job in jobs {
+            let Ok(Some(item)) = queries::get_item(&db, &job.item_id) else {
+                // Deleted while it waited
+                let _ = queries::finish_ai_job(&db, &job.item_id, job.generation);
+                continue;
+            };
+            let mut request = queue::parse_fields(&job.fields).request(&item.title, &item.content);
+            request.existing_tags = existing_tags.clone();
+            request.existing_folders = existing_folders.clone();
+            request.vocabulary = vocabulary.clone();
+            request.tag_synonyms = tag_synonyms.clone();
+            let local = heuristics::classify(&request, &dictionary);
+
+            // Type and language detected from the content itself are reliable,
+            // so only ask the model for what the rules couldn't tell
+            let mut ai_request = request.clone();
+            ai_request.needs_type &= local.item_type.is_none();
+            ai_request.needs_language &= local.language.is_none();
+            if !item.ai_allowed() {
+                // `ai: never` and encrypted items get the local classifier's answer and nothing more
+                ai_request.needs_type = false;
+                ai_request.needs_language = false;
+                ai_request.needs_tags = false;
+                ai_request.needs_folder = false;
+                ai_request.needs_description = false;
+                ai_request.needs_title = false;
+            }
+            prepared.push(Prepared {
+                job,
+                request,
+                ai_request,
+                local,
+            });
+        }
+        (
+            prepared,
+            prompts::load(&db, PromptKind::Categorize),
+            prompts::load(&db, PromptKind::CategorizeBatch),
+            ledger::budget_exceeded(&db),
+        )
+    };
+
+    let ai_results = match provider {
+        Some(provider) => {
+            categorize_prepared(
+                app_handle,
+                provider.as_ref(),
+                &prepared,
+                &single_prompt,
+                &batch_prompt,
+                over_budget,
+                settings.requests_per_minute,
+            )
+            .await
+        }
+        None => vec![CategorizationResult::default(); prepared.len()],
+    };
+
+    for (p, ai_result) in prepared.iter().zip(ai_results) {
+        let result = CategorizationResult {
+            item_type: p.local.item_type.clone().or(ai_result.item_type),
+            language: p.local.language.clone().or(ai_result.language),
+            tags: ai_result.tags.or_else(|| p.local.tags.clone()),
+            folder: ai_result.folder.or_else(|| p.local.folder.clone()),
+            description: ai_result.description,
+            title: ai_result.title,
+        };
+        let updated = apply_result(&state, &p.job.item_id, &p.request, &result);
+
+        if let Ok(db) = state.db.lock() {
+            if let Err(e) = queries::finish_ai_job(&db, &p.job.item_id, p.job.generation) {
+                eprintln!(
+                    "Failed to finish enrichment job for {}: {}",
+                    p.job.item_id, e
+                );
+            }
+        }
+
+        // Emit event so frontend refreshes
+        if let Some(updated) = updated {
+            let _ = app_handle.emit("items-changed", &updated.id);
+        }
+    }
+}
+
+/// AI answers for each prepared job, in order. Jobs that don't need a title are
+/// coalesced into one `categorize_batch` request when there are several of them;
+/// the rest are categorized one at a time.
+async fn categorize_prepared(
+    app_handle: &AppHandle,
+    provider: &dyn AiProvider,
+    prepared: &[Prepared],
+    single_prompt: &PromptTemplate,
+    batch_prompt: &PromptTemplate,
+    over_budget: bool,
+    requests_per_minute: u32,
+) -> Vec<CategorizationResult> {
+    let state = app_handle.state::<AppState>();
+    let batchable = |p: &Prepared| p.needs_ai() && !p.ai_request.needs_title;
+    let coalesce = prepared.iter().filter(|p| batchable(p)).count() > 1;
+
+    // Existing tags and folders are left out of the keys: they change with every
+    // item, and an unchanged item should not be re-sent because of that
+    let keys: Vec<Option<String>> = prepared
+        .iter()
+        .map(|p| {
+            if !p.needs_ai() {
+                return None;
+            }
+            let r = &p.ai_request;
+            let vocabulary_key = serde_json::to_string(&r.vocabulary).unwrap_or_default();
+            Some(if coalesce && batchable(p) {
+                cache::key(
+                    provider,
+                    batch_prompt,
+                    &[&vocabulary_key, &r.title, &r.content],
+                )
+            } else {
+                cache::key(
+                    provider,
+                    single_prompt,
+                    &[&r.requested_fields(), &vocabulary_key, &r.title, &r.content],
+                )
+            })
+        })
+        .collect();
+    let mut results: Vec<Option<CategorizationResult>> = match state.db.lock() {
+        Ok(db) => keys
+            .iter()
+            .map(|k| match k {
+                Some(k) => cache::get(&db, k),
+                None => Some(CategorizationResult::default()),
+            })
+            .collect(),
+        Err(_) => vec![None; prepared.len()],
+    };
+    let misses: Vec<usize> = (0..prepared.len())
+        .filter(|&i| results[i].is_none())
+        .collect();
+
+    if over_budget {
+        // Background enrichment pauses for the rest of the month once the budget
+        // is spent; the local result still applies
+        for &i in &misses {
+            eprintln!(
+                "AI budget reached, skipping enrichment for {}",
+                prepared[i].job.item_id
+            );
+            let _ = app_handle.emit("ai-budget-exceeded", &prepared[i].job.item_id);
+        }
+        return results.into_iter().map(Option::unwrap_or_default).collect();
+    }
+
+    let (batched, mut single): (Vec<usize>, Vec<usize>) = misses
+        .into_iter()
+        .partition(|&i| coalesce && batchable(&prepared[i]));
+
+    if !batched.is_empty() {
+        let first = &prepared[batched[0]].ai_request;
+        let batch_items: Vec<(String, String)> = batched
+            .iter()
+            .map(|&i| {
+                let r = &prepared[i].ai_request;
+                (r.title.clone(), r.content.clone())
+            })
+            .collect();
+
+        state.ai_queue.throttle(requests_per_minute).await;
+        let mut usage = TokenUsage::default();
+        let outcome = categorize::categorize_batch(
+            provider,
+            &batch_items,
+            &first.existing_tags,
+            &first.existing_folders,
+            &first.vocabulary,
+            batch_prompt,
+            &mut usage,
+        )
+        .await;
+
+        if let Ok(db) = state.db.lock() {
+            ledger::record(&db, "categorize_batch", provider, &usage);
+            if let Ok(ref fresh) = outcome {
+                for (&i, r) in batched.iter().zip(fresh) {
+                    if let Some(ref key) = keys[i] {
+                        if !r.is_empty() {
+                            cache::put(&db, key, "categorize_batch", r);
+                        }
+                    }
+                }
+            }
+        }
+        match outcome {
+            Ok(fresh) => {
+                for (&i, r) in batched.iter().zip(fresh) {
+                    results[i] = Some(r);
+                }
+            }
+            Err(e) => {
+                eprintln!(
+                    "Batch enrichment failed for {} item(s), falling back to individual: {}",
+                    batched.len(),
+                    e
+                );
+                single.extend(batched);
+            }
+        }
+    }
+
+    for i in single {
+        let p = &prepared[i];
+        state.ai_queue.throttle(requests_per_minute).await;
+        let mut usage = TokenUsage::default();
+        let outcome =
+            categorize::categorize(provider, &p.ai_request, single_prompt, &mut usage).await;
+        if let Ok(db) = state.db.lock() {
+            ledger::record(&db, "categorize", provider, &usage);
+            if let (Ok(ref r), Some(ref key)) = (&outcome, &keys[i]) {
+                cache::put(&db, key, "categorize", r);
+            }
+        }
+        match outcome {
+            Ok(r) => results[i] = Some(r),
+            Err(e) => eprintln!("AI categorization failed for {}: {}", p.job.item_id, e),
+        }
+    }
+
+    results.into_iter().map(Option::unwrap_or_default).collect()
+}
+
+/// Write the requested fields of `result` to the item, its file and the DB.
+/// Returns the updated item, or `None` if nothing changed.
+fn apply_result(
+    state: &AppState,
+    item_id: &str,
+    request: &CategorizationRequest,
+    result: &CategorizationResult,
+) -> Option<Item> {
+    let db = state.db.lock().ok()?;
+    let mut updated = queries::get_item(&db, item_id).ok()??;
+    let mut changed = false;
+
+    if let Some(ref t) = result.item_type {
+        if request.needs_type {
+            updated.item_type = t.clone();
+            changed = true;
+        }
+    }
+    if let Some(ref l) = result.language {
+        if request.needs_language {
+            updated.language = l.clone();
+            changed = true;
+        }
+    }
+    if let Some(ref tags) = result.tags {
+        if request.needs_tags {
+            // Also covers cached and batch results, which skip parse_response's mapping
+            updated.tags = request.tag_synonyms.normalize(tags);
+            changed = true;
+        }
+    }
+    if let Some(ref f) = result.folder {
+        if request.needs_folder {
+            updated.folder = f.clone();
+            changed = true;
+        }
+    }
+    if let Some(ref d) = result.description {
+        if request.needs_description {
+            updated.description = d.clone();
+            changed = true;
+        }
+    }
+
+    let title_changed = if let Some(ref t) = result.title {
+        if request.needs_title {
+            updated.title = t.clone();
+            changed = true;
+            true
+        } else {
+            false
+        }
+    } else {
+        false
+    };
+
+    if !changed {
+        return None;
+    }
+
+    updated.modified = Utc::now().to_rfc3339();
+
+    // Save to file
+    let data_dir: std::path::PathBuf = state.data_dir.lock().ok()?.clone();
+
+    // Save new file first, then delete old (prevents data loss if save fails)
+    if let Err(e) = markdown::save_item_to_file(&data_dir, &updated) {
+        eprintln!("Failed to save enriched item file: {}", e);
+        return None;
+    }
+
+    if title_changed {
+        let old_path = &updated.file_path;
+        let _ = std::fs::remove_file(old_path);
+    }
+
+    // Recompute hash
+    let file_path = markdown::item_file_path(&data_dir, &updated.title, &updated.id);
+    let raw = std::fs::read_to_string(&file_path).ok()?;
+    updated.file_path = file_path.to_string_lossy().to_string();
+    updated.file_hash = markdown::compute_hash(&raw);
+
+    // Update DB
+    if let Err(e) = queries::insert_item(&db, &updated) {
+        eprintln!("Failed to update enriched item in DB: {}", e);
+        return None;
+    }
+    links::update(&db, &updated);
+    related::refresh(&db);
+
+    Some(updated)
+}
+
+/// Queue depth and settings, for the UI to poll while enrichment runs.
+#[tauri::command]
+pub fn get_ai_queue_status(state: State<AppState>) -> Result<QueueStatus, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let counts = queries::count_ai_jobs(&db).map_err(|e| e.to_string())?;
+    let count = |status: &str| {
+        counts
+            .iter()
+            .find(|(s, _)| s == status)
+            .map_or(0, |(_, n)| *n)
+    };
+    Ok(QueueStatus {
+        pending: count("pending"),
+        running: count("running"),
+        active_workers: state.ai_queue.active_workers(),
+        settings: queue::load_settings(&db),
+        budget_exceeded: ledger::budget_exceeded(&db),
+    })
+}
+
+#[tauri::command]
+pub fn set_ai_queue_settings(
+    state: State<AppState>,
+    settings: QueueSettings,
+) -> Result<QueueSettings, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    queue::save_settings(&db, &settings)?;
+    drop(db);
+    // The dispatcher re-reads settings each round; wake it so more workers
+    // start right away
+    state.ai_queue.wake.notify_one();
+    Ok(settings)
+}
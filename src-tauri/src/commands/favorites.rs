@@ -0,0 +1,156 @@
+use crate::commands::usage;
+use crate::db::queries::{self, Item};
+use crate::exec::placeholders;
+use crate::files::sync;
+use crate::secrets::store;
+use crate::state::AppState;
+use chrono::Utc;
+use tauri::{Emitter, Manager, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
+
+pub const SLOT_COUNT: u8 = 9;
+
+/// Copy an item's content and record the use. The DB lock is held only to load
+/// the item and to record the use: keyring lookups can block, and listeners to
+/// `usage-changed` (the tray) need to query freely.
+fn copy_and_track(
+    app_handle: &tauri::AppHandle,
+    find: impl FnOnce(&rusqlite::Connection) -> rusqlite::Result<Option<Item>>,
+) -> Result<(), String> {
+    let state = app_handle.state::<AppState>();
+    let item = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        find(&db)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| "Item not found".to_string())?
+    };
+    // Encrypted bodies and keyring secrets are filled in only here, on their way to the clipboard
+    let content = state.vault.content(&item)?;
+    let content = placeholders::expand_secrets(&content, store::resolve)?;
+    app_handle
+        .clipboard()
+        .write_text(content)
+        .map_err(|e| format!("Failed to copy to clipboard: {}", e))?;
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        usage::track(&db, &item.id, "copy");
+    }
+
+    let _ = app_handle.emit("usage-changed", &item.id);
+    Ok(())
+}
+
+/// Copy the item in `slot` without opening any window. Called from the slot shortcuts.
+pub fn copy_slot(app_handle: &tauri::AppHandle, slot: u8) {
+    let result = copy_and_track(app_handle, |db| queries::get_item_by_slot(db, slot));
+    if let Err(e) = result {
+        eprintln!("Quick slot {}: {}", slot, e);
+    }
+}
+
+/// Copy an item from outside any window (e.g. the tray menu).
+pub fn copy_by_id(app_handle: &tauri::AppHandle, id: &str) {
+    if let Err(e) = copy_and_track(app_handle, |db| queries::get_item(db, id)) {
+        eprintln!("Copy {}: {}", id, e);
+    }
+}
+
+#[tauri::command]
+pub fn copy_item(app_handle: tauri::AppHandle, id: String) -> Result<(), String> {
+    copy_and_track(&app_handle, |db| queries::get_item(db, &id))
+}
+
+#[tauri::command]
+pub fn set_pinned(
+    app_handle: tauri::AppHandle,
+    state: State<AppState>,
+    id: String,
+    pinned: bool,
+) -> Result<Item, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let data_dir = state.data_dir.lock().map_err(|e| e.to_string())?;
+    let item = queries::get_item(&db, &id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Item not found".to_string())?;
+
+    let updated = sync::write_item(
+        &db,
+        &data_dir,
+        &Item {
+            pinned,
+            modified: Utc::now().to_rfc3339(),
+            ..item
+        },
+    )?;
+    drop(db);
+    drop(data_dir);
+
+    let _ = app_handle.emit("items-changed", &updated.id);
+    Ok(updated)
+}
+
+/// Assign an item to a quick slot (1-9), or clear it with `None`. A slot holds
+/// one item; whatever held it before is moved out.
+#[tauri::command]
+pub fn set_quick_slot(
+    app_handle: tauri::AppHandle,
+    state: State<AppState>,
+    id: String,
+    slot: Option<u8>,
+) -> Result<Item, String> {
+    if let Some(n) = slot {
+        if !(1..=SLOT_COUNT).contains(&n) {
+            return Err(format!("Quick slot must be between 1 and {}", SLOT_COUNT));
+        }
+    }
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let data_dir = state.data_dir.lock().map_err(|e| e.to_string())?;
+    let item = queries::get_item(&db, &id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Item not found".to_string())?;
+    let now = Utc::now().to_rfc3339();
+
+    if let Some(n) = slot {
+        if let Some(previous) = queries::get_item_by_slot(&db, n).map_err(|e| e.to_string())? {
+            if previous.id != item.id {
+                sync::write_item(
+                    &db,
+                    &data_dir,
+                    &Item {
+                        slot: None,
+                        modified: now.clone(),
+                        ..previous
+                    },
+                )?;
+            }
+        }
+    }
+
+    let updated = sync::write_item(
+        &db,
+        &data_dir,
+        &Item {
+            slot,
+            modified: now,
+            ..item
+        },
+    )?;
+    drop(db);
+    drop(data_dir);
+
+    let _ = app_handle.emit("items-changed", &updated.id);
+    Ok(updated)
+}
+
+#[tauri::command]
+pub fn get_pinned_items(state: State<AppState>) -> Result<Vec<Item>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    queries::pinned_items(&db).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_quick_slots(state: State<AppState>) -> Result<Vec<Item>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    queries::slotted_items(&db).map_err(|e| e.to_string())
+}
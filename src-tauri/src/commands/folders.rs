@@ -0,0 +1,141 @@
+use crate::db::queries::{self, Item};
+use crate::files::sync;
+use crate::state::AppState;
+use chrono::Utc;
+use tauri::{Emitter, State};
+
+/// Normalise a folder path to `/a/b`: leading slash, no trailing slash, no empty
+/// or relative segments.
+fn normalize_folder(folder: &str) -> Result<String, String> {
+    let segments: Vec<&str> = folder
+        .split(['/', '\\'])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .collect();
+    if segments.iter().any(|s| *s == "." || *s == "..") {
+        return Err(format!("Invalid folder: {}", folder));
+    }
+    Ok(format!("/{}", segments.join("/")))
+}
+
+/// The rest of `folder` below `root` (`""` for `root` itself), or `None` when
+/// `folder` isn't in `root`'s subtree.
+fn subtree_suffix<'a>(folder: &'a str, root: &str) -> Option<&'a str> {
+    if root == "/" {
+        return Some(if folder == "/" { "" } else { folder });
+    }
+    let rest = folder.strip_prefix(root)?;
+    (rest.is_empty() || rest.starts_with('/')).then_some(rest)
+}
+
+/// Apply `refolder` to the folder of every item and rewrite those that changed,
+/// as one transactional batch. Emits a single `items-changed` for the whole batch.
+/// Returns how many items changed. Only frontmatter changes; item files all live
+/// flat in the data directory (see `sync::write_items`).
+fn refolder(
+    app_handle: &tauri::AppHandle,
+    state: &AppState,
+    refolder: impl Fn(&str) -> Option<String>,
+) -> Result<usize, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let data_dir = state.data_dir.lock().map_err(|e| e.to_string())?;
+
+    let now = Utc::now().to_rfc3339();
+    let changed: Vec<Item> = queries::list_items(&db)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter_map(|item| {
+            let folder = refolder(&item.folder).filter(|f| *f != item.folder)?;
+            Some(Item {
+                folder,
+                modified: now.clone(),
+                ..item
+            })
+        })
+        .collect();
+    if changed.is_empty() {
+        return Ok(0);
+    }
+
+    sync::write_items(&db, &data_dir, &changed)?;
+    drop(data_dir);
+    drop(db);
+
+    let _ = app_handle.emit("items-changed", "folders");
+    Ok(changed.len())
+}
+
+/// Move `from` and its subfolders to `to`, e.g. `/docker` to `/containers/docker`
+/// also moves `/docker/compose` to `/containers/docker/compose`.
+#[tauri::command]
+pub fn rename_folder(
+    app_handle: tauri::AppHandle,
+    state: State<AppState>,
+    from: String,
+    to: String,
+) -> Result<usize, String> {
+    let from = normalize_folder(&from)?;
+    let to = normalize_folder(&to)?;
+    if from == "/" {
+        return Err("The root folder can't be renamed".to_string());
+    }
+    if subtree_suffix(&to, &from).is_some() {
+        return Err(format!("Can't move {} into itself", from));
+    }
+    refolder(&app_handle, &state, |folder| {
+        subtree_suffix(folder, &from).map(|rest| format!("{}{}", to, rest).replace("//", "/"))
+    })
+}
+
+/// Move each of `sources`, with its subfolders, into `target`. Subfolders keep
+/// their names under `target`.
+#[tauri::command]
+pub fn merge_folders(
+    app_handle: tauri::AppHandle,
+    state: State<AppState>,
+    sources: Vec<String>,
+    target: String,
+) -> Result<usize, String> {
+    let target = normalize_folder(&target)?;
+    let sources = sources
+        .iter()
+        .map(|s| normalize_folder(s))
+        .collect::<Result<Vec<_>, _>>()?;
+    if let Some(source) = sources
+        .iter()
+        .find(|s| *s == "/" || subtree_suffix(&target, s).is_some_and(|rest| !rest.is_empty()))
+    {
+        return Err(format!("Can't merge {} into {}", source, target));
+    }
+    refolder(&app_handle, &state, |folder| {
+        sources.iter().find_map(|source| {
+            subtree_suffix(folder, source)
+                .map(|rest| format!("{}{}", target, rest).replace("//", "/"))
+        })
+    })
+}
+
+/// Delete `folder` and its subfolders, moving their items to `reassign_to`
+/// (the root folder by default).
+#[tauri::command]
+pub fn delete_folder(
+    app_handle: tauri::AppHandle,
+    state: State<AppState>,
+    folder: String,
+    reassign_to: Option<String>,
+) -> Result<usize, String> {
+    let folder = normalize_folder(&folder)?;
+    let reassign_to = normalize_folder(reassign_to.as_deref().unwrap_or("/"))?;
+    if folder == "/" {
+        return Err("The root folder can't be deleted".to_string());
+    }
+    if subtree_suffix(&reassign_to, &folder).is_some() {
+        return Err(format!(
+            "Can't reassign items to {}, it's being deleted",
+            reassign_to
+        ));
+    }
+    refolder(&app_handle, &state, |f| {
+        subtree_suffix(f, &folder).map(|_| reassign_to.clone())
+    })
+}
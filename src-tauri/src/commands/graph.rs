@@ -0,0 +1,45 @@
+use crate::db::queries::{self, RelatedItem};
+use crate::graph::build::{self, Graph, GraphFilter};
+use crate::graph::export::{self, GraphFormat};
+use crate::state::AppState;
+use tauri::State;
+
+const DEFAULT_RELATED_LIMIT: usize = 10;
+
+#[tauri::command]
+pub fn get_related_items(
+    state: State<AppState>,
+    id: String,
+    limit: Option<usize>,
+) -> Result<Vec<RelatedItem>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    queries::get_related_items(&db, &id, limit.unwrap_or(DEFAULT_RELATED_LIMIT))
+        .map_err(|e| e.to_string())
+}
+
+/// Items as nodes and their links and similarity as weighted edges.
+#[tauri::command]
+pub fn get_graph(state: State<AppState>, filter: Option<GraphFilter>) -> Result<Graph, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    build::build(&db, &filter.unwrap_or_default())
+}
+
+/// Serialize the item graph as GraphML, DOT or JSON Graph Format. Written to
+/// `path` when one is given; the text is returned either way.
+#[tauri::command]
+pub fn export_graph(
+    state: State<AppState>,
+    format: GraphFormat,
+    filter: Option<GraphFilter>,
+    path: Option<String>,
+) -> Result<String, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let graph = build::build(&db, &filter.unwrap_or_default())?;
+    drop(db);
+
+    let text = export::export(&graph, format)?;
+    if let Some(path) = path.filter(|p| !p.is_empty()) {
+        std::fs::write(&path, &text).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    }
+    Ok(text)
+}
@@ -0,0 +1,17 @@
+use crate::db::queries::{self, Link};
+use crate::state::AppState;
+use tauri::State;
+
+/// `[[...]]` links in an item, including dangling ones with no `target_id`.
+#[tauri::command]
+pub fn get_outgoing_links(state: State<AppState>, id: String) -> Result<Vec<Link>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    queries::get_outgoing_links(&db, &id).map_err(|e| e.to_string())
+}
+
+/// Items whose links resolve to this one, by id or title.
+#[tauri::command]
+pub fn get_backlinks(state: State<AppState>, id: String) -> Result<Vec<Link>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    queries::get_backlinks(&db, &id).map_err(|e| e.to_string())
+}
@@ -1,5 +1,18 @@
 pub mod ai;
+pub mod enrichment;
+pub mod favorites;
+pub mod folders;
+pub mod graph;
+pub mod links;
+pub mod prompts;
 pub mod recategorize;
+pub mod run;
 pub mod search;
+pub mod secrets;
 pub mod settings;
+pub mod shortcuts;
 pub mod snippets;
+pub mod tags;
+pub mod usage;
+pub mod vault;
+pub mod vocabulary;
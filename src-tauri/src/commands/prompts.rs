@@ -0,0 +1,196 @@
+use crate::ai::categorize::{self, CategorizationRequest};
+use crate::ai::ledger;
+use crate::ai::prompts::{self, PromptKind, PromptTemplate};
+use crate::ai::provider::AiProvider;
+use crate::ai::retry::complete_with_retry;
+use crate::ai::{tag_merges, taxonomy};
+use crate::commands::recategorize::DEFAULT_MAX_FOLDERS;
+use crate::db::queries::{self, Item};
+use crate::db::{synonyms, vocabulary};
+use crate::state::AppState;
+use serde::Serialize;
+use std::sync::Arc;
+use tauri::State;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PromptTestResult {
+    pub system_prompt: String,
+    pub user_prompt: String,
+    pub raw: String,
+    pub parsed: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+fn parse_kind(name: &str) -> Result<PromptKind, String> {
+    PromptKind::from_name(name).ok_or_else(|| format!("Unknown prompt template: {}", name))
+}
+
+#[tauri::command]
+pub fn list_prompt_templates(state: State<AppState>) -> Result<Vec<PromptTemplate>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    Ok(PromptKind::all()
+        .into_iter()
+        .map(|kind| prompts::load(&db, kind))
+        .collect())
+}
+
+#[tauri::command]
+pub fn set_prompt_template(
+    state: State<AppState>,
+    name: String,
+    body: String,
+) -> Result<PromptTemplate, String> {
+    let kind = parse_kind(&name)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    prompts::save(&db, kind, &body)
+}
+
+#[tauri::command]
+pub fn reset_prompt_template(
+    state: State<AppState>,
+    name: String,
+) -> Result<PromptTemplate, String> {
+    let kind = parse_kind(&name)?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    prompts::reset(&db, kind)
+}
+
+/// Run a template against an item (or, for the taxonomy template, against all items
+/// and, for the tag merge template, against all tags)
+/// and return the rendered prompts alongside the raw and parsed model output.
+/// `body` tries out an unsaved edit instead of the saved template.
+#[tauri::command]
+pub async fn test_prompt(
+    state: State<'_, AppState>,
+    name: String,
+    item_id: Option<String>,
+    body: Option<String>,
+) -> Result<PromptTestResult, String> {
+    let kind = parse_kind(&name)?;
+
+    let provider: Arc<dyn AiProvider> = {
+        let guard = state.ai_provider.read().await;
+        guard
+            .clone()
+            .ok_or_else(|| "AI provider not configured".to_string())?
+    };
+
+    let (template, items, item, existing_tags, existing_folders, vocabulary, tag_synonyms) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let mut template = prompts::load(&db, kind);
+        if let Some(body) = body {
+            prompts::validate(kind, &body)?;
+            template.body = body;
+        }
+        let item: Option<Item> = match item_id {
+            Some(id) => Some(
+                queries::get_item(&db, &id)
+                    .map_err(|e| e.to_string())?
+                    .ok_or_else(|| "Item not found".to_string())?,
+            ),
+            None => None,
+        };
+        if item.as_ref().is_some_and(|i| i.ai_opt_out) {
+            return Err("This item is marked `ai: never` and is not sent to AI providers".to_string());
+        }
+        if item.as_ref().is_some_and(|i| i.encrypted) {
+            return Err("Encrypted items are not sent to AI providers".to_string());
+        }
+        let items = if kind == PromptKind::Taxonomy {
+            queries::list_items(&db)
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .filter(|i| i.ai_allowed())
+                .collect()
+        } else {
+            Vec::new()
+        };
+        (
+            template,
+            items,
+            item,
+            queries::get_all_tags(&db).unwrap_or_default(),
+            queries::get_all_folders(&db).unwrap_or_default(),
+            vocabulary::load(&db),
+            synonyms::load(&db),
+        )
+    };
+
+    let request = item.map(|item| CategorizationRequest {
+        title: item.title,
+        content: item.content,
+        needs_type: true,
+        needs_language: true,
+        needs_tags: true,
+        needs_folder: true,
+        needs_description: true,
+        needs_title: true,
+        existing_tags: existing_tags.clone(),
+        existing_folders,
+        vocabulary,
+        tag_synonyms,
+    });
+
+    let messages = match (kind, &request) {
+        (PromptKind::Taxonomy, _) => {
+            if items.is_empty() {
+                return Err("There are no items to build a taxonomy from".to_string());
+            }
+            taxonomy::build_taxonomy_messages(&items, DEFAULT_MAX_FOLDERS, &template)
+        }
+        (PromptKind::TagMerges, _) => {
+            if existing_tags.len() < 2 {
+                return Err("There are not enough tags to look for merges".to_string());
+            }
+            tag_merges::build_tag_merge_messages(&existing_tags, &template)
+        }
+        (_, None) => return Err("Choose an item to test this prompt with".to_string()),
+        (PromptKind::Categorize, Some(request)) => categorize::build_messages(request, &template),
+        (PromptKind::CategorizeBatch, Some(request)) => categorize::build_batch_messages(
+            &[(request.title.clone(), request.content.clone())],
+            &request.existing_tags,
+            &request.existing_folders,
+            &request.vocabulary,
+            &template,
+        ),
+    };
+
+    let system_prompt = messages[0].content.clone();
+    let user_prompt = messages[1].content.clone();
+    let response = complete_with_retry(provider.as_ref(), messages).await?;
+    if let (Some(usage), Ok(db)) = (response.usage, state.db.lock()) {
+        ledger::record(&db, "test_prompt", provider.as_ref(), &usage);
+    }
+
+    let parsed = match (kind, &request) {
+        (PromptKind::Taxonomy, _) => {
+            taxonomy::parse_taxonomy_response(&response.content, DEFAULT_MAX_FOLDERS)
+                .and_then(|r| serde_json::to_value(r).map_err(|e| e.to_string()))
+        }
+        (PromptKind::TagMerges, _) => {
+            tag_merges::parse_tag_merge_response(&response.content, &existing_tags)
+                .and_then(|r| serde_json::to_value(r).map_err(|e| e.to_string()))
+        }
+        (PromptKind::Categorize, Some(request)) => {
+            categorize::parse_response(&response.content, request)
+                .and_then(|r| serde_json::to_value(r).map_err(|e| e.to_string()))
+        }
+        (PromptKind::CategorizeBatch, Some(request)) => {
+            categorize::parse_batch_response(&response.content, 1, &request.vocabulary)
+                .and_then(|r| serde_json::to_value(r).map_err(|e| e.to_string()))
+        }
+        (_, None) => unreachable!("checked when building messages"),
+    };
+
+    let (parsed, error) = match parsed {
+        Ok(value) => (Some(value), None),
+        Err(e) => (None, Some(e)),
+    };
+    Ok(PromptTestResult {
+        system_prompt,
+        user_prompt,
+        raw: response.content,
+        parsed,
+        error,
+    })
+}
@@ -1,8 +1,12 @@
+use crate::ai::cache;
 use crate::ai::categorize::{self, CategorizationRequest, CategorizationResult};
-use crate::ai::provider::AiProvider;
+use crate::ai::ledger;
+use crate::ai::provider::{AiProvider, TokenUsage};
+use crate::ai::prompts::{self, PromptKind};
 use crate::ai::taxonomy;
-use crate::db::queries;
-use crate::files::markdown;
+use crate::db::{queries, synonyms, vocabulary};
+use crate::files::{links, markdown};
+use crate::graph::related;
 use crate::state::AppState;
 use chrono::Utc;
 use serde::Serialize;
@@ -10,7 +14,7 @@ use std::sync::Arc;
 use tauri::{Emitter, Manager, State};
 
 const BATCH_SIZE: usize = 10;
-const DEFAULT_MAX_FOLDERS: usize = 10;
+pub const DEFAULT_MAX_FOLDERS: usize = 10;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct RecategorizeProgress {
@@ -36,10 +40,14 @@ pub async fn recategorize_all(
             .ok_or_else(|| "AI provider not configured".to_string())?
     };
 
-    // Load all items from DB
+    // Load all items from DB, leaving out those marked `ai: never` and encrypted ones
     let all_items: Vec<queries::Item> = {
         let db = state.db.lock().map_err(|e| e.to_string())?;
-        queries::list_items(&db).map_err(|e| e.to_string())?
+        queries::list_items(&db)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .filter(|item| item.ai_allowed())
+            .collect()
     };
 
     if all_items.is_empty() {
@@ -61,10 +69,18 @@ pub async fn recategorize_all(
         return Ok(());
     }
 
-    // Get existing tags for categorization
-    let existing_tags: Vec<String> = {
+    // Get existing tags, the type/language vocabulary, tag synonyms and prompt
+    // templates for categorization
+    let (existing_tags, vocabulary, tag_synonyms, taxonomy_prompt, batch_prompt, single_prompt) = {
         let db = state.db.lock().map_err(|e| e.to_string())?;
-        queries::get_all_tags(&db).unwrap_or_default()
+        (
+            queries::get_all_tags(&db).unwrap_or_default(),
+            vocabulary::load(&db),
+            synonyms::load(&db),
+            prompts::load(&db, PromptKind::Taxonomy),
+            prompts::load(&db, PromptKind::CategorizeBatch),
+            prompts::load(&db, PromptKind::Categorize),
+        )
     };
 
     // Spawn background task so command returns immediately
@@ -82,7 +98,46 @@ pub async fn recategorize_all(
             },
         );
 
-        let taxonomy = match taxonomy::generate_taxonomy(provider.as_ref(), &all_items, max_folders).await {
+        // The taxonomy depends on every item, so any edit invalidates it
+        let mut taxonomy_parts = vec![max_folders.to_string()];
+        for item in &all_items {
+            taxonomy_parts.push(item.title.clone());
+            taxonomy_parts.push(item.content.clone());
+        }
+        let taxonomy_key = cache::key(
+            provider.as_ref(),
+            &taxonomy_prompt,
+            &taxonomy_parts.iter().map(String::as_str).collect::<Vec<_>>(),
+        );
+        let cached_taxonomy: Option<Vec<String>> = state
+            .db
+            .lock()
+            .ok()
+            .and_then(|db| cache::get(&db, &taxonomy_key));
+
+        let generated = match cached_taxonomy {
+            Some(t) => Ok(t),
+            None => {
+                let mut usage = TokenUsage::default();
+                let outcome = taxonomy::generate_taxonomy(
+                    provider.as_ref(),
+                    &all_items,
+                    max_folders,
+                    &taxonomy_prompt,
+                    &mut usage,
+                )
+                .await;
+                if let Ok(db) = state.db.lock() {
+                    ledger::record(&db, "taxonomy", provider.as_ref(), &usage);
+                    if let Ok(ref t) = outcome {
+                        cache::put(&db, &taxonomy_key, "taxonomy", t);
+                    }
+                }
+                outcome
+            }
+        };
+
+        let taxonomy = match generated {
             Ok(t) => t,
             Err(e) => {
                 eprintln!("Taxonomy generation failed: {}", e);
@@ -111,6 +166,8 @@ pub async fn recategorize_all(
 
         // Pass 2: Categorize filtered items in batches
         let total = filtered_items.len();
+        let folders_key = taxonomy.join("\n");
+        let vocabulary_key = serde_json::to_string(&vocabulary).unwrap_or_default();
 
         for chunk_start in (0..total).step_by(BATCH_SIZE) {
             let chunk_end = (chunk_start + BATCH_SIZE).min(total);
@@ -131,56 +188,113 @@ pub async fn recategorize_all(
                 },
             );
 
+            // Items whose content, folder choices and vocabulary are unchanged since
+            // the last run are answered from the cache
+            let cache_keys: Vec<String> = chunk
+                .iter()
+                .map(|item| {
+                    cache::key(
+                        provider.as_ref(),
+                        &batch_prompt,
+                        &[&folders_key, &vocabulary_key, &item.title, &item.content],
+                    )
+                })
+                .collect();
+            let mut cached: Vec<Option<CategorizationResult>> = match state.db.lock() {
+                Ok(db) => cache_keys.iter().map(|k| cache::get(&db, k)).collect(),
+                Err(_) => vec![None; chunk.len()],
+            };
+            let misses: Vec<usize> = (0..chunk.len()).filter(|&i| cached[i].is_none()).collect();
+
             // Build batch input
-            let batch_items: Vec<(String, String)> = chunk
+            let batch_items: Vec<(String, String)> = misses
                 .iter()
-                .map(|item| (item.title.clone(), item.content.clone()))
+                .map(|&i| (chunk[i].title.clone(), chunk[i].content.clone()))
                 .collect();
 
             // Try batch categorization first
-            let results: Vec<CategorizationResult> = match categorize::categorize_batch(
-                provider.as_ref(),
-                &batch_items,
-                &existing_tags,
-                &taxonomy,
-            )
-            .await
-            {
-                Ok(r) => r,
-                Err(e) => {
-                    eprintln!(
-                        "Batch categorization failed for items {}-{}, falling back to individual: {}",
-                        chunk_start + 1,
-                        chunk_end,
-                        e
-                    );
-                    // Fall back to individual categorization
-                    let mut fallback_results = Vec::with_capacity(chunk.len());
-                    for item in chunk {
-                        let request = CategorizationRequest {
-                            title: item.title.clone(),
-                            content: item.content.clone(),
-                            needs_type: true,
-                            needs_language: true,
-                            needs_tags: true,
-                            needs_folder: true,
-                            needs_description: true,
-                            needs_title: false,
-                            existing_tags: existing_tags.clone(),
-                            existing_folders: taxonomy.clone(),
-                        };
-                        match categorize::categorize(provider.as_ref(), &request).await {
-                            Ok(r) => fallback_results.push(r),
-                            Err(e2) => {
-                                eprintln!("Individual categorization failed for '{}': {}", item.title, e2);
-                                fallback_results.push(CategorizationResult::default());
+            let mut batch_usage = TokenUsage::default();
+            let mut fallback_usage = TokenUsage::default();
+            let fresh: Vec<CategorizationResult> = if batch_items.is_empty() {
+                Vec::new()
+            } else {
+                match categorize::categorize_batch(
+                    provider.as_ref(),
+                    &batch_items,
+                    &existing_tags,
+                    &taxonomy,
+                    &vocabulary,
+                    &batch_prompt,
+                    &mut batch_usage,
+                )
+                .await
+                {
+                    Ok(r) => r,
+                    Err(e) => {
+                        eprintln!(
+                            "Batch categorization failed for items {}-{}, falling back to individual: {}",
+                            chunk_start + 1,
+                            chunk_end,
+                            e
+                        );
+                        // Fall back to individual categorization
+                        let mut fallback_results = Vec::with_capacity(misses.len());
+                        for &i in &misses {
+                            let item = &chunk[i];
+                            let request = CategorizationRequest {
+                                title: item.title.clone(),
+                                content: item.content.clone(),
+                                needs_type: true,
+                                needs_language: true,
+                                needs_tags: true,
+                                needs_folder: true,
+                                needs_description: true,
+                                needs_title: false,
+                                existing_tags: existing_tags.clone(),
+                                existing_folders: taxonomy.clone(),
+                                vocabulary: vocabulary.clone(),
+                                tag_synonyms: tag_synonyms.clone(),
+                            };
+                            match categorize::categorize(
+                                provider.as_ref(),
+                                &request,
+                                &single_prompt,
+                                &mut fallback_usage,
+                            )
+                            .await
+                            {
+                                Ok(r) => fallback_results.push(r),
+                                Err(e2) => {
+                                    eprintln!("Individual categorization failed for '{}': {}", item.title, e2);
+                                    fallback_results.push(CategorizationResult::default());
+                                }
                             }
                         }
+                        fallback_results
                     }
-                    fallback_results
                 }
             };
 
+            {
+                let db = state.db.lock().ok();
+                if let Some(ref db) = db {
+                    ledger::record(db, "categorize_batch", provider.as_ref(), &batch_usage);
+                    ledger::record(db, "categorize", provider.as_ref(), &fallback_usage);
+                }
+                for (&i, result) in misses.iter().zip(fresh) {
+                    // Answers from the individual fallback are equivalent, so they are
+                    // cached under the batch key too; empty answers are not worth keeping
+                    if let Some(ref db) = db {
+                        if !result.is_empty() {
+                            cache::put(db, &cache_keys[i], "categorize_batch", &result);
+                        }
+                    }
+                    cached[i] = Some(result);
+                }
+            }
+            let results: Vec<CategorizationResult> =
+                cached.into_iter().map(Option::unwrap_or_default).collect();
+
             // Apply results to each item in the chunk
             for (item, result) in chunk.iter().zip(results.iter()) {
                 let mut updated = item.clone();
@@ -195,7 +309,7 @@ pub async fn recategorize_all(
                     changed = true;
                 }
                 if let Some(ref tags) = result.tags {
-                    updated.tags = tags.clone();
+                    updated.tags = tag_synonyms.normalize(tags);
                     changed = true;
                 }
                 if let Some(ref f) = result.folder {
@@ -240,6 +354,9 @@ pub async fn recategorize_all(
                 };
                 if let Err(e) = queries::insert_item(&db, &updated) {
                     eprintln!("Failed to update DB for '{}': {}", updated.title, e);
+                } else {
+                    links::update(&db, &updated);
+                    related::refresh(&db);
                 }
             }
         }
@@ -0,0 +1,164 @@
+use crate::commands::usage;
+use crate::db::queries::{self, RunRecord};
+use crate::exec::placeholders::{self, Placeholder};
+use crate::exec::runner::{self, RunSpec};
+use crate::secrets::store;
+use crate::state::AppState;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::{Emitter, State};
+use uuid::Uuid;
+
+const DEFAULT_HISTORY_LIMIT: usize = 20;
+
+#[derive(Debug, Deserialize)]
+pub struct RunItemInput {
+    pub id: String,
+    pub values: Option<HashMap<String, String>>,
+    pub cwd: Option<String>,
+    pub timeout_secs: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunOutputEvent {
+    pub run_id: String,
+    pub item_id: String,
+    pub stream: String,
+    pub line: String,
+}
+
+#[tauri::command]
+pub async fn run_item(
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+    input: RunItemInput,
+) -> Result<RunRecord, String> {
+    let (item, shell, default_cwd, default_timeout) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let item = queries::get_item(&db, &input.id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| "Item not found".to_string())?;
+        let setting = |key: &str| {
+            queries::get_setting(&db, key)
+                .ok()
+                .flatten()
+                .filter(|v| !v.is_empty())
+        };
+        (
+            item,
+            setting("run_shell"),
+            setting("run_cwd"),
+            setting("run_timeout_secs").and_then(|v| v.parse::<u64>().ok()),
+        )
+    };
+
+    if item.item_type != "shell" {
+        return Err("Only shell items can be run".to_string());
+    }
+    if !item.trusted {
+        return Err(
+            "Item is not trusted. Set `trusted: true` in its frontmatter to allow running it"
+                .to_string(),
+        );
+    }
+
+    let content = state.vault.content(&item)?;
+    let values = input.values.unwrap_or_default();
+    let command =
+        placeholders::expand(runner::strip_code_fence(&content), &values, store::resolve)?;
+    if command.trim().is_empty() {
+        return Err("Item has no command to run".to_string());
+    }
+
+    let cwd = input
+        .cwd
+        .filter(|c| !c.is_empty())
+        .or(default_cwd)
+        .map(PathBuf::from)
+        .or_else(dirs::home_dir)
+        .unwrap_or_else(|| PathBuf::from("."));
+    if !cwd.is_dir() {
+        return Err(format!(
+            "Working directory does not exist: {}",
+            cwd.display()
+        ));
+    }
+
+    let spec = RunSpec {
+        shell: shell.unwrap_or_else(runner::default_shell),
+        cwd,
+        timeout: Duration::from_secs(
+            input
+                .timeout_secs
+                .or(default_timeout)
+                .unwrap_or(runner::DEFAULT_TIMEOUT_SECS),
+        ),
+        command,
+    };
+
+    let run_id = Uuid::new_v4().to_string();
+    let started = Utc::now().to_rfc3339();
+
+    let emitter = app_handle.clone();
+    let event_run_id = run_id.clone();
+    let event_item_id = item.id.clone();
+    let outcome = runner::run_command(&spec, move |stream, line| {
+        let _ = emitter.emit(
+            "run-output",
+            RunOutputEvent {
+                run_id: event_run_id.clone(),
+                item_id: event_item_id.clone(),
+                stream: stream.to_string(),
+                line,
+            },
+        );
+    })
+    .await?;
+
+    let record = RunRecord {
+        id: run_id,
+        item_id: item.id,
+        exit_code: outcome.exit_code,
+        timed_out: outcome.timed_out,
+        duration_ms: outcome.duration.as_millis() as u64,
+        started,
+    };
+
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        if let Err(e) = queries::insert_run(&db, &record) {
+            eprintln!("Failed to record run for {}: {}", record.item_id, e);
+        }
+        usage::track(&db, &record.item_id, "run");
+    }
+
+    let _ = app_handle.emit("run-finished", &record);
+    let _ = app_handle.emit("usage-changed", &record.item_id);
+    Ok(record)
+}
+
+#[tauri::command]
+pub fn get_item_placeholders(
+    state: State<AppState>,
+    id: String,
+) -> Result<Vec<Placeholder>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let item = queries::get_item(&db, &id)
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| "Item not found".to_string())?;
+    Ok(placeholders::find_placeholders(&state.vault.content(&item)?))
+}
+
+#[tauri::command]
+pub fn get_run_history(
+    state: State<AppState>,
+    item_id: String,
+    limit: Option<usize>,
+) -> Result<Vec<RunRecord>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    queries::list_runs(&db, &item_id, limit.unwrap_or(DEFAULT_HISTORY_LIMIT))
+        .map_err(|e| e.to_string())
+}
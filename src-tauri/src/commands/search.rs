@@ -1,9 +1,13 @@
 use crate::db::queries::{self, Item};
+use crate::db::synonyms;
 use crate::state::AppState;
 use tauri::State;
 
 #[tauri::command]
 pub fn search_items(state: State<AppState>, query: String) -> Result<Vec<Item>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    queries::search_items(&db, &query).map_err(|e| e.to_string())
+    let mut items =
+        queries::search_items(&db, &query, &synonyms::load(&db)).map_err(|e| e.to_string())?;
+    state.vault.reveal(&mut items);
+    Ok(items)
 }
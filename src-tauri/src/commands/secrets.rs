@@ -0,0 +1,102 @@
+use crate::ai::redact::{self, Redactor};
+use crate::db::queries::{self, Item, SecretFinding};
+use crate::exec::placeholders;
+use crate::files::sync;
+use crate::secrets::store;
+use crate::state::AppState;
+use chrono::Utc;
+use tauri::{Emitter, State};
+
+/// Credential-looking values found in stored items, for one item or all of them.
+#[tauri::command]
+pub fn list_secret_findings(
+    state: State<AppState>,
+    item_id: Option<String>,
+) -> Result<Vec<SecretFinding>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    queries::list_secret_findings(&db, item_id.as_deref()).map_err(|e| e.to_string())
+}
+
+/// Move the secret with `fingerprint` out of the item and into the OS keyring
+/// under `name`. Every occurrence in the item becomes `{{secret:NAME}}`, filled
+/// back in only when the item is copied or run.
+#[tauri::command]
+pub fn move_secret_to_keyring(
+    app_handle: tauri::AppHandle,
+    state: State<AppState>,
+    item_id: String,
+    fingerprint: String,
+    name: String,
+) -> Result<Item, String> {
+    let name = name.trim().to_string();
+    if !placeholders::is_valid_name(&name) {
+        return Err(
+            "Secret names start with a letter or _ and use only letters, digits, _ and -"
+                .to_string(),
+        );
+    }
+
+    // The keyring can block, so it is only called with no locks held
+    let (item, redactor) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let item = queries::get_item(&db, &item_id)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| "Item not found".to_string())?;
+        (item, Redactor::load(&db))
+    };
+
+    let matches: Vec<_> = redactor
+        .find(&item.content)
+        .into_iter()
+        .filter(|m| redact::fingerprint(m.secret(&item.content)) == fingerprint)
+        .collect();
+    let secret = matches
+        .first()
+        .map(|m| m.secret(&item.content).to_string())
+        .ok_or_else(|| "That secret is no longer in the item".to_string())?;
+
+    let key = store::item_secret_key(&name);
+    match store::get(&key)? {
+        Some(existing) if existing != secret => {
+            return Err(format!(
+                "A different secret named '{}' is already in the keyring",
+                name
+            ))
+        }
+        Some(_) => {}
+        None => store::set(&key, &secret)?,
+    }
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let data_dir = state.data_dir.lock().map_err(|e| e.to_string())?;
+    let item = queries::get_item(&db, &item_id)
+        .map_err(|e| e.to_string())?
+        .filter(|current| current.content == item.content)
+        .ok_or_else(|| {
+            "The item changed while the secret was being saved; try again".to_string()
+        })?;
+
+    let content = placeholders::replace_with_secret(&item.content, &matches, &name);
+
+    let updated = sync::write_item(
+        &db,
+        &data_dir,
+        &Item {
+            content,
+            modified: Utc::now().to_rfc3339(),
+            ..item
+        },
+    )?;
+    drop(db);
+    drop(data_dir);
+
+    let _ = app_handle.emit("items-changed", &updated.id);
+    Ok(updated)
+}
+
+/// `content` with its `{{secret:NAME}}` markers filled in from the keyring, for
+/// copying text that was edited in the viewer.
+#[tauri::command]
+pub fn expand_secrets(content: String) -> Result<String, String> {
+    placeholders::expand_secrets(&content, store::resolve)
+}
@@ -0,0 +1,282 @@
+use crate::commands::favorites;
+use crate::commands::snippets;
+use crate::db::queries;
+use crate::state::AppState;
+use serde::Serialize;
+use std::str::FromStr;
+use tauri::{Emitter, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutAction {
+    ToggleSearch,
+    QuickAdd,
+    NewFromClipboard,
+    OpenMainWindow,
+    QuickSlot(u8),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ShortcutBinding {
+    pub action: String,
+    pub label: String,
+    pub accelerator: Option<String>,
+    pub default_accelerator: Option<String>,
+    pub registered: bool,
+}
+
+impl ShortcutAction {
+    pub fn all() -> Vec<ShortcutAction> {
+        let mut actions = vec![
+            ShortcutAction::ToggleSearch,
+            ShortcutAction::QuickAdd,
+            ShortcutAction::NewFromClipboard,
+            ShortcutAction::OpenMainWindow,
+        ];
+        actions.extend((1..=favorites::SLOT_COUNT).map(ShortcutAction::QuickSlot));
+        actions
+    }
+
+    pub fn id(&self) -> String {
+        match self {
+            ShortcutAction::ToggleSearch => "toggle_search".to_string(),
+            ShortcutAction::QuickAdd => "quick_add".to_string(),
+            ShortcutAction::NewFromClipboard => "new_from_clipboard".to_string(),
+            ShortcutAction::OpenMainWindow => "open_main_window".to_string(),
+            ShortcutAction::QuickSlot(n) => format!("quick_slot_{}", n),
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<ShortcutAction> {
+        Self::all().into_iter().find(|a| a.id() == id)
+    }
+
+    fn label(&self) -> String {
+        match self {
+            ShortcutAction::ToggleSearch => "Toggle search overlay".to_string(),
+            ShortcutAction::QuickAdd => "Quick add".to_string(),
+            ShortcutAction::NewFromClipboard => "New item from clipboard".to_string(),
+            ShortcutAction::OpenMainWindow => "Open main window".to_string(),
+            ShortcutAction::QuickSlot(n) => format!("Copy quick slot {}", n),
+        }
+    }
+
+    fn default_accelerator(&self) -> Option<String> {
+        match self {
+            ShortcutAction::ToggleSearch => Some("CommandOrControl+Shift+Space".to_string()),
+            ShortcutAction::QuickSlot(n) => Some(format!("CommandOrControl+Shift+{}", n)),
+            _ => None,
+        }
+    }
+
+    fn setting_key(&self) -> String {
+        format!("shortcut_{}", self.id())
+    }
+}
+
+/// The accelerator bound to `action`: the saved setting if there is one (an empty
+/// value means the user disabled it), otherwise the default.
+fn binding_for(conn: &rusqlite::Connection, action: ShortcutAction) -> Option<String> {
+    match queries::get_setting(conn, &action.setting_key())
+        .ok()
+        .flatten()
+    {
+        Some(value) if value.is_empty() => None,
+        Some(value) => Some(value),
+        None => action.default_accelerator(),
+    }
+}
+
+fn current_bindings(state: &AppState) -> Result<Vec<(ShortcutAction, Option<String>)>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    Ok(ShortcutAction::all()
+        .into_iter()
+        .map(|action| (action, binding_for(&db, action)))
+        .collect())
+}
+
+fn parse_accelerator(accelerator: &str) -> Result<Shortcut, String> {
+    Shortcut::from_str(accelerator)
+        .map_err(|e| format!("Invalid shortcut '{}': {}", accelerator, e))
+}
+
+fn register(
+    app: &tauri::AppHandle,
+    action: ShortcutAction,
+    accelerator: &str,
+) -> Result<(), String> {
+    app.global_shortcut()
+        .on_shortcut(
+            accelerator,
+            move |app: &tauri::AppHandle, _shortcut, event| {
+                if event.state == ShortcutState::Pressed {
+                    dispatch(app, action);
+                }
+            },
+        )
+        .map_err(|e| e.to_string())
+}
+
+/// Register every configured shortcut. A binding that fails (typically because
+/// another application owns it) is logged and skipped rather than aborting startup.
+pub fn register_all(app: &tauri::AppHandle) {
+    let state = app.state::<AppState>();
+    let bindings = match current_bindings(&state) {
+        Ok(b) => b,
+        Err(e) => {
+            eprintln!("Failed to load shortcut settings: {}", e);
+            return;
+        }
+    };
+
+    for (action, accelerator) in bindings {
+        if let Some(accelerator) = accelerator {
+            if let Err(e) = register(app, action, &accelerator) {
+                eprintln!(
+                    "Failed to register {} for {}: {}",
+                    accelerator,
+                    action.id(),
+                    e
+                );
+            }
+        }
+    }
+}
+
+fn show_main_window(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.show();
+        let _ = window.set_focus();
+    }
+}
+
+fn dispatch(app: &tauri::AppHandle, action: ShortcutAction) {
+    match action {
+        ShortcutAction::ToggleSearch => {
+            if let Some(window) = app.get_webview_window("search") {
+                if window.is_visible().unwrap_or(false) {
+                    let _ = window.hide();
+                } else {
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                    let _ = window.emit("search-focus", ());
+                }
+            }
+        }
+        ShortcutAction::QuickAdd => {
+            show_main_window(app);
+            let _ = app.emit("quick-add", ());
+        }
+        ShortcutAction::NewFromClipboard => new_from_clipboard(app),
+        ShortcutAction::OpenMainWindow => show_main_window(app),
+        ShortcutAction::QuickSlot(n) => favorites::copy_slot(app, n),
+    }
+}
+
+pub fn new_from_clipboard(app: &tauri::AppHandle) {
+    if let Err(e) = snippets::create_from_clipboard(app) {
+        eprintln!("New from clipboard: {}", e);
+    }
+}
+
+#[tauri::command]
+pub fn get_shortcuts(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<ShortcutBinding>, String> {
+    let shortcuts = app_handle.global_shortcut();
+    Ok(current_bindings(&state)?
+        .into_iter()
+        .map(|(action, accelerator)| ShortcutBinding {
+            action: action.id(),
+            label: action.label(),
+            registered: accelerator
+                .as_deref()
+                .is_some_and(|a| shortcuts.is_registered(a)),
+            accelerator,
+            default_accelerator: action.default_accelerator(),
+        })
+        .collect())
+}
+
+/// Rebind `action`. `None` restores the default, an empty string disables it.
+/// Async so the plugin's main-thread round trip doesn't block the main thread.
+#[tauri::command]
+pub async fn set_shortcut(
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    action: String,
+    accelerator: Option<String>,
+) -> Result<Vec<ShortcutBinding>, String> {
+    let action = ShortcutAction::from_id(&action)
+        .ok_or_else(|| format!("Unknown shortcut action: {}", action))?;
+    let requested = match accelerator {
+        None => action.default_accelerator(),
+        Some(a) if a.trim().is_empty() => None,
+        Some(a) => Some(a.trim().to_string()),
+    };
+
+    let bindings = current_bindings(&state)?;
+    let previous = bindings
+        .iter()
+        .find(|(a, _)| *a == action)
+        .and_then(|(_, acc)| acc.clone());
+
+    if let Some(ref accelerator) = requested {
+        let shortcut = parse_accelerator(accelerator)?;
+        for (other, other_acc) in &bindings {
+            if *other == action {
+                continue;
+            }
+            let clashes = other_acc
+                .as_deref()
+                .and_then(|a| parse_accelerator(a).ok())
+                .is_some_and(|s| s.id() == shortcut.id());
+            if clashes {
+                return Err(format!(
+                    "{} is already bound to \"{}\"",
+                    accelerator,
+                    other.label()
+                ));
+            }
+        }
+    }
+
+    if requested != previous {
+        let shortcuts = app_handle.global_shortcut();
+        if let Some(ref old) = previous {
+            if shortcuts.is_registered(old.as_str()) {
+                shortcuts
+                    .unregister(old.as_str())
+                    .map_err(|e| e.to_string())?;
+            }
+        }
+        if let Some(ref new) = requested {
+            if let Err(e) = register(&app_handle, action, new) {
+                if let Some(ref old) = previous {
+                    let _ = register(&app_handle, action, old);
+                }
+                return Err(format!(
+                    "Could not register {}: {}. It may already be in use by another application",
+                    new, e
+                ));
+            }
+        }
+    }
+
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let result = if requested == action.default_accelerator() {
+            queries::delete_setting(&db, &action.setting_key())
+        } else {
+            queries::set_setting(
+                &db,
+                &action.setting_key(),
+                requested.as_deref().unwrap_or(""),
+            )
+        };
+        result.map_err(|e| e.to_string())?;
+    }
+
+    get_shortcuts(app_handle, state)
+}
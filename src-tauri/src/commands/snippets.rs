@@ -1,11 +1,18 @@
-use crate::ai::categorize::{self, CategorizationRequest};
+use crate::ai::categorize::CategorizationRequest;
+use crate::ai::heuristics;
+use crate::ai::redact::Redactor;
+use crate::commands::enrichment;
 use crate::db::queries::{self, Item};
-use crate::files::markdown;
+use crate::db::synonyms::{self, TagSynonyms};
+use crate::db::vocabulary::{self, Vocabulary};
+use crate::files::{links, markdown};
+use crate::graph::related;
+use crate::secrets::scanner;
 use crate::state::AppState;
 use chrono::Utc;
 use serde::Deserialize;
-use std::sync::Arc;
 use tauri::{Emitter, Manager, State};
+use tauri_plugin_clipboard_manager::ClipboardExt;
 use uuid::Uuid;
 
 #[derive(Debug, Deserialize)]
@@ -18,6 +25,10 @@ pub struct CreateItemInput {
     pub folder: Option<String>,
     pub description: Option<String>,
     pub content: String,
+    #[serde(default)]
+    pub ai_opt_out: Option<bool>,
+    #[serde(default)]
+    pub encrypted: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -31,6 +42,25 @@ pub struct UpdateItemInput {
     pub folder: Option<String>,
     pub description: Option<String>,
     pub content: Option<String>,
+    #[serde(default)]
+    pub ai_opt_out: Option<bool>,
+    #[serde(default)]
+    pub encrypted: Option<bool>,
+}
+
+/// Encrypt the body of an `encrypted` item for its file. Needs an unlocked vault.
+fn seal_content(state: &AppState, item: Item) -> Result<Item, String> {
+    if !item.encrypted {
+        return Ok(Item {
+            ciphertext: String::new(),
+            ..item
+        });
+    }
+    let ciphertext = state
+        .vault
+        .encrypt(&item.id, &item.content)
+        .map_err(|e| format!("Can't save an encrypted item: {}", e))?;
+    Ok(Item { ciphertext, ..item })
 }
 
 /// Check which fields were left as defaults and could benefit from AI categorization.
@@ -57,150 +87,11 @@ fn needs_enrichment(item: &Item, input_type: &Option<String>, input_lang: &Optio
         needs_title,
         existing_tags: Vec::new(),
         existing_folders: Vec::new(),
+        vocabulary: Vocabulary::default(),
+        tag_synonyms: TagSynonyms::default(),
     })
 }
 
-fn spawn_enrichment(
-    app_handle: tauri::AppHandle,
-    item_id: String,
-    mut request: CategorizationRequest,
-) {
-    tauri::async_runtime::spawn(async move {
-        let state = app_handle.state::<AppState>();
-
-        // Get the AI provider (read lock, non-blocking for other readers)
-        let provider: Arc<dyn crate::ai::provider::AiProvider> = {
-            let guard = state.ai_provider.read().await;
-            match guard.clone() {
-                Some(p) => p,
-                None => return,
-            }
-        };
-
-        // Gather existing vocabulary from DB (don't hold lock across await)
-        {
-            let db = match state.db.lock() {
-                Ok(db) => db,
-                Err(_) => return,
-            };
-            request.existing_tags = queries::get_all_tags(&db).unwrap_or_default();
-            request.existing_folders = queries::get_all_folders(&db).unwrap_or_default();
-        }
-
-        // Call AI (this is the async network call)
-        let result = match categorize::categorize(provider.as_ref(), &request).await {
-            Ok(r) => r,
-            Err(e) => {
-                eprintln!("AI categorization failed for {}: {}", item_id, e);
-                return;
-            }
-        };
-
-        // Apply results: read item, merge, save file, update DB
-        let updated = {
-            let db = match state.db.lock() {
-                Ok(db) => db,
-                Err(_) => return,
-            };
-
-            let item = match queries::get_item(&db, &item_id) {
-                Ok(Some(item)) => item,
-                _ => return,
-            };
-
-            let mut changed = false;
-            let mut updated = item;
-
-            if let Some(ref t) = result.item_type {
-                if request.needs_type {
-                    updated.item_type = t.clone();
-                    changed = true;
-                }
-            }
-            if let Some(ref l) = result.language {
-                if request.needs_language {
-                    updated.language = l.clone();
-                    changed = true;
-                }
-            }
-            if let Some(ref tags) = result.tags {
-                if request.needs_tags {
-                    updated.tags = tags.clone();
-                    changed = true;
-                }
-            }
-            if let Some(ref f) = result.folder {
-                if request.needs_folder {
-                    updated.folder = f.clone();
-                    changed = true;
-                }
-            }
-            if let Some(ref d) = result.description {
-                if request.needs_description {
-                    updated.description = d.clone();
-                    changed = true;
-                }
-            }
-
-            let title_changed = if let Some(ref t) = result.title {
-                if request.needs_title {
-                    updated.title = t.clone();
-                    changed = true;
-                    true
-                } else {
-                    false
-                }
-            } else {
-                false
-            };
-
-            if !changed {
-                return;
-            }
-
-            updated.modified = Utc::now().to_rfc3339();
-
-            // Save to file
-            let data_dir: std::path::PathBuf = match state.data_dir.lock() {
-                Ok(d) => d.clone(),
-                Err(_) => return,
-            };
-
-            // Save new file first, then delete old (prevents data loss if save fails)
-            if let Err(e) = markdown::save_item_to_file(&data_dir, &updated) {
-                eprintln!("Failed to save enriched item file: {}", e);
-                return;
-            }
-
-            if title_changed {
-                let old_path = &updated.file_path;
-                let _ = std::fs::remove_file(old_path);
-            }
-
-            // Recompute hash
-            let file_path = markdown::item_file_path(&data_dir, &updated.title, &updated.id);
-            let raw = match std::fs::read_to_string(&file_path) {
-                Ok(r) => r,
-                Err(_) => return,
-            };
-            let hash = markdown::compute_hash(&raw);
-            updated.file_path = file_path.to_string_lossy().to_string();
-            updated.file_hash = hash;
-
-            // Update DB
-            if let Err(e) = queries::insert_item(&db, &updated) {
-                eprintln!("Failed to update enriched item in DB: {}", e);
-                return;
-            }
-
-            updated
-        };
-
-        // Emit event so frontend refreshes
-        let _ = app_handle.emit("items-changed", &updated.id);
-    });
-}
-
 #[tauri::command]
 pub fn create_item(
     app_handle: tauri::AppHandle,
@@ -210,20 +101,43 @@ pub fn create_item(
     let id = Uuid::new_v4().to_string();
     let now = Utc::now().to_rfc3339();
 
+    // A type with a default language (e.g. shell → bash) settles the language too
+    let language = match (&input.item_type, input.language) {
+        (Some(item_type), None) => {
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            vocabulary::load(&db)
+                .get_type(item_type)
+                .map(|t| t.default_language.clone())
+                .filter(|l| !l.is_empty())
+        }
+        (_, language) => language,
+    };
+
     let input_type = input.item_type.clone();
-    let input_lang = input.language.clone();
+    let input_lang = language.clone();
     let input_tags = input.tags.clone();
     let input_folder = input.folder.clone();
     let input_desc = input.description.clone();
 
+    // Tags are saved under their canonical names, e.g. k8s as kubernetes
+    let tags = match input.tags {
+        Some(tags) => {
+            let db = state.db.lock().map_err(|e| e.to_string())?;
+            synonyms::load(&db).normalize(&tags)
+        }
+        None => Vec::new(),
+    };
+
     let title = input.title.unwrap_or_else(|| format!("Untitled-{}", &id[..8]));
 
     let item = Item {
         id: id.clone(),
         title,
-        item_type: input.item_type.unwrap_or_else(|| "note".to_string()),
-        language: input.language.unwrap_or_default(),
-        tags: input.tags.unwrap_or_default(),
+        item_type: input
+            .item_type
+            .unwrap_or_else(|| vocabulary::FALLBACK_TYPE.to_string()),
+        language: language.unwrap_or_default(),
+        tags,
         folder: input.folder.unwrap_or_else(|| "/".to_string()),
         description: input.description.unwrap_or_default(),
         content: input.content,
@@ -231,7 +145,15 @@ pub fn create_item(
         file_hash: String::new(),
         created: now.clone(),
         modified: now,
+        // Only ever set by hand in the file's frontmatter, never over IPC
+        trusted: false,
+        pinned: false,
+        slot: None,
+        ai_opt_out: input.ai_opt_out.unwrap_or(false),
+        encrypted: input.encrypted.unwrap_or(false),
+        ciphertext: String::new(),
     };
+    let item = seal_content(&state, item)?;
 
     let data_dir = state.data_dir.lock().map_err(|e| e.to_string())?;
     let file_path = markdown::save_item_to_file(&data_dir, &item)
@@ -248,17 +170,60 @@ pub fn create_item(
 
     let db = state.db.lock().map_err(|e| e.to_string())?;
     queries::insert_item(&db, &item).map_err(|e| e.to_string())?;
+    let secrets_found = scanner::update(&db, &Redactor::load(&db), &item);
+    links::update(&db, &item);
+    related::refresh(&db);
     drop(db);
     drop(data_dir);
 
+    if secrets_found > 0 {
+        let _ = app_handle.emit("secrets-found", &item.id);
+    }
+    let _ = app_handle.emit("items-changed", &item.id);
+
     // Check if AI enrichment is needed
-    if let Some(request) = needs_enrichment(&item, &input_type, &input_lang, &input_tags, &input_folder, &input_desc) {
-        spawn_enrichment(app_handle, item.id.clone(), request);
+    if !item.encrypted {
+        if let Some(request) = needs_enrichment(&item, &input_type, &input_lang, &input_tags, &input_folder, &input_desc) {
+            enrichment::enqueue(&app_handle, &item.id, &request);
+        }
     }
 
     Ok(item)
 }
 
+/// Create an item from the clipboard text. Type and language are filled in by
+/// local heuristics where they are recognisable, so AI enrichment only has to
+/// supply what they couldn't.
+pub fn create_from_clipboard(app_handle: &tauri::AppHandle) -> Result<Item, String> {
+    let content = app_handle
+        .clipboard()
+        .read_text()
+        .map_err(|e| format!("Failed to read clipboard: {}", e))?;
+    if content.trim().is_empty() {
+        return Err("Clipboard is empty".to_string());
+    }
+
+    let detected = heuristics::detect(&content);
+    let input = CreateItemInput {
+        title: None,
+        item_type: detected.item_type,
+        language: detected.language,
+        tags: None,
+        folder: None,
+        description: None,
+        content,
+        ai_opt_out: None,
+        encrypted: None,
+    };
+    let item = create_item(app_handle.clone(), app_handle.state::<AppState>(), input)?;
+    Ok(item)
+}
+
+#[tauri::command]
+pub fn create_item_from_clipboard(app_handle: tauri::AppHandle) -> Result<Item, String> {
+    create_from_clipboard(&app_handle)
+}
+
 #[tauri::command]
 pub fn update_item(
     app_handle: tauri::AppHandle,
@@ -266,9 +231,13 @@ pub fn update_item(
     input: UpdateItemInput,
 ) -> Result<Item, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    let existing = queries::get_item(&db, &input.id)
+    let mut existing = queries::get_item(&db, &input.id)
         .map_err(|e| e.to_string())?
         .ok_or_else(|| "Item not found".to_string())?;
+    // An encrypted body must be readable to be kept, even if only metadata changes
+    if existing.encrypted {
+        existing.content = state.vault.content(&existing)?;
+    }
 
     let now = Utc::now().to_rfc3339();
 
@@ -277,13 +246,17 @@ pub fn update_item(
     let input_tags = input.tags.clone();
     let input_folder = input.folder.clone();
     let input_desc = input.description.clone();
+    let old_title = existing.title.clone();
 
     let updated = Item {
         id: existing.id,
         title: input.title.unwrap_or(existing.title),
         item_type: input.item_type.unwrap_or(existing.item_type),
         language: input.language.unwrap_or(existing.language),
-        tags: input.tags.unwrap_or(existing.tags),
+        tags: input
+            .tags
+            .map(|tags| synonyms::load(&db).normalize(&tags))
+            .unwrap_or(existing.tags),
         folder: input.folder.unwrap_or(existing.folder),
         description: input.description.unwrap_or(existing.description),
         content: input.content.unwrap_or(existing.content),
@@ -291,7 +264,14 @@ pub fn update_item(
         file_hash: String::new(),
         created: existing.created,
         modified: now,
+        trusted: existing.trusted,
+        pinned: existing.pinned,
+        slot: existing.slot,
+        ai_opt_out: input.ai_opt_out.unwrap_or(existing.ai_opt_out),
+        encrypted: input.encrypted.unwrap_or(existing.encrypted),
+        ciphertext: String::new(),
     };
+    let updated = seal_content(&state, updated)?;
 
     let data_dir = state.data_dir.lock().map_err(|e| e.to_string())?;
     let new_path = markdown::item_file_path(&data_dir, &updated.title, &updated.id);
@@ -315,19 +295,37 @@ pub fn update_item(
     };
 
     queries::insert_item(&db, &updated).map_err(|e| e.to_string())?;
+    let secrets_found = scanner::update(&db, &Redactor::load(&db), &updated);
+    links::update(&db, &updated);
+    related::refresh(&db);
+    // Keep `[[Old Title]]` links in other items pointing here under the new name
+    if updated.title != old_title {
+        links::rewrite_backlinks(&db, &data_dir, &updated.id, &old_title, &updated.title);
+    }
     drop(db);
     drop(data_dir);
 
+    if secrets_found > 0 {
+        let _ = app_handle.emit("secrets-found", &updated.id);
+    }
+    let _ = app_handle.emit("items-changed", &updated.id);
+
     // Check if AI enrichment is needed
-    if let Some(request) = needs_enrichment(&updated, &input_type, &input_lang, &input_tags, &input_folder, &input_desc) {
-        spawn_enrichment(app_handle, updated.id.clone(), request);
+    if !updated.encrypted {
+        if let Some(request) = needs_enrichment(&updated, &input_type, &input_lang, &input_tags, &input_folder, &input_desc) {
+            enrichment::enqueue(&app_handle, &updated.id, &request);
+        }
     }
 
     Ok(updated)
 }
 
 #[tauri::command]
-pub fn delete_item(state: State<AppState>, id: String) -> Result<(), String> {
+pub fn delete_item(
+    app_handle: tauri::AppHandle,
+    state: State<AppState>,
+    id: String,
+) -> Result<(), String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
     let item = queries::get_item(&db, &id)
         .map_err(|e| e.to_string())?
@@ -336,20 +334,28 @@ pub fn delete_item(state: State<AppState>, id: String) -> Result<(), String> {
     // Delete from DB first so reconciliation won't re-add if file delete fails
     queries::delete_item(&db, &id).map_err(|e| e.to_string())?;
     let _ = std::fs::remove_file(&item.file_path);
+    drop(db);
 
+    let _ = app_handle.emit("items-changed", &id);
     Ok(())
 }
 
 #[tauri::command]
 pub fn get_item(state: State<AppState>, id: String) -> Result<Option<Item>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    queries::get_item(&db, &id).map_err(|e| e.to_string())
+    let mut item = queries::get_item(&db, &id).map_err(|e| e.to_string())?;
+    if let Some(item) = item.as_mut() {
+        state.vault.reveal(std::slice::from_mut(item));
+    }
+    Ok(item)
 }
 
 #[tauri::command]
 pub fn list_items(state: State<AppState>) -> Result<Vec<Item>, String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    queries::list_items(&db).map_err(|e| e.to_string())
+    let mut items = queries::list_items(&db).map_err(|e| e.to_string())?;
+    state.vault.reveal(&mut items);
+    Ok(items)
 }
 
 #[tauri::command]
@@ -0,0 +1,173 @@
+use crate::ai::ledger;
+use crate::ai::prompts::{self, PromptKind};
+use crate::ai::provider::{AiProvider, TokenUsage};
+use crate::ai::tag_merges::{self, TagMergeSuggestion};
+use crate::db::queries::{self, Item};
+use crate::db::synonyms::{self, same_tag, TagSynonyms};
+use crate::files::sync;
+use crate::state::AppState;
+use chrono::Utc;
+use std::sync::Arc;
+use tauri::{Emitter, State};
+
+fn validate_tag(tag: &str) -> Result<String, String> {
+    let tag = tag.trim();
+    if tag.is_empty() {
+        return Err("Tag can't be empty".to_string());
+    }
+    if tag.contains(',') {
+        return Err("Tags can't contain commas".to_string());
+    }
+    Ok(tag.to_string())
+}
+
+/// Apply `retag` to the tags of every item and rewrite those that changed, as one
+/// transactional batch. Tags that end up repeated (ignoring case) keep their first
+/// copy. Emits a single `items-changed` for the whole batch.
+/// Returns how many items changed.
+fn retag(
+    app_handle: &tauri::AppHandle,
+    state: &AppState,
+    retag: impl Fn(&[String]) -> Vec<String>,
+) -> Result<usize, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let data_dir = state.data_dir.lock().map_err(|e| e.to_string())?;
+
+    let now = Utc::now().to_rfc3339();
+    let changed: Vec<Item> = queries::list_items(&db)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter_map(|item| {
+            let mut tags: Vec<String> = Vec::with_capacity(item.tags.len());
+            for tag in retag(&item.tags) {
+                if !tags.iter().any(|t| same_tag(t, &tag)) {
+                    tags.push(tag);
+                }
+            }
+            (tags != item.tags).then(|| Item {
+                tags,
+                modified: now.clone(),
+                ..item
+            })
+        })
+        .collect();
+    if changed.is_empty() {
+        return Ok(0);
+    }
+
+    sync::write_items(&db, &data_dir, &changed)?;
+    drop(data_dir);
+    drop(db);
+
+    let _ = app_handle.emit("items-changed", "tags");
+    Ok(changed.len())
+}
+
+/// Rename `from` (in any case) to `to` on every item. Items that already had `to`
+/// keep one copy.
+#[tauri::command]
+pub fn rename_tag(
+    app_handle: tauri::AppHandle,
+    state: State<AppState>,
+    from: String,
+    to: String,
+) -> Result<usize, String> {
+    let to = validate_tag(&to)?;
+    retag(&app_handle, &state, |tags| {
+        tags.iter()
+            .map(|t| {
+                if same_tag(t, &from) {
+                    to.clone()
+                } else {
+                    t.clone()
+                }
+            })
+            .collect()
+    })
+}
+
+/// Replace each of `sources` with `target`, e.g. `k8s` and `kube` into `kubernetes`.
+#[tauri::command]
+pub fn merge_tags(
+    app_handle: tauri::AppHandle,
+    state: State<AppState>,
+    sources: Vec<String>,
+    target: String,
+) -> Result<usize, String> {
+    let target = validate_tag(&target)?;
+    retag(&app_handle, &state, |tags| {
+        tags.iter()
+            .map(|t| {
+                if sources.iter().any(|s| same_tag(s, t)) {
+                    target.clone()
+                } else {
+                    t.clone()
+                }
+            })
+            .collect()
+    })
+}
+
+/// Remove `tag` from every item.
+#[tauri::command]
+pub fn delete_tag(
+    app_handle: tauri::AppHandle,
+    state: State<AppState>,
+    tag: String,
+) -> Result<usize, String> {
+    retag(&app_handle, &state, |tags| {
+        tags.iter()
+            .filter(|t| !same_tag(t, &tag))
+            .cloned()
+            .collect()
+    })
+}
+
+#[tauri::command]
+pub fn get_tag_synonyms(state: State<AppState>) -> Result<TagSynonyms, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    Ok(synonyms::load(&db))
+}
+
+/// Replace the synonyms map. It applies to items saved or enriched from now on
+/// and to searches; existing tags are left alone (`merge_tags` rewrites them).
+#[tauri::command]
+pub fn set_tag_synonyms(
+    state: State<AppState>,
+    synonyms: TagSynonyms,
+) -> Result<TagSynonyms, String> {
+    let synonyms = synonyms.validated()?;
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    synonyms::save(&db, &synonyms)?;
+    Ok(synonyms)
+}
+
+/// Ask the AI provider which existing tags mean the same thing. Nothing is changed;
+/// each suggestion can be applied with `merge_tags` or kept as synonyms.
+#[tauri::command]
+pub async fn suggest_tag_merges(
+    state: State<'_, AppState>,
+) -> Result<Vec<TagMergeSuggestion>, String> {
+    let provider: Arc<dyn AiProvider> = {
+        let guard = state.ai_provider.read().await;
+        guard
+            .clone()
+            .ok_or_else(|| "AI provider not configured".to_string())?
+    };
+
+    let (tags, template) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        (
+            queries::get_all_tags(&db).map_err(|e| e.to_string())?,
+            prompts::load(&db, PromptKind::TagMerges),
+        )
+    };
+
+    let mut usage = TokenUsage::default();
+    let suggestions =
+        tag_merges::suggest_tag_merges(provider.as_ref(), &tags, &template, &mut usage).await;
+    if let Ok(db) = state.db.lock() {
+        ledger::record(&db, "suggest_tag_merges", provider.as_ref(), &usage);
+    }
+    suggestions
+}
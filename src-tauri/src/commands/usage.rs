@@ -0,0 +1,50 @@
+use crate::db::queries::{self, Item};
+use crate::state::AppState;
+use chrono::Utc;
+use rusqlite::Connection;
+use tauri::{Emitter, State};
+
+const VALID_ACTIONS: &[&str] = &["copy", "open", "run"];
+const DEFAULT_LIMIT: usize = 10;
+
+/// Record one use of an item. Failures are logged rather than surfaced, since
+/// usage tracking should never get in the way of the action itself.
+pub fn track(conn: &Connection, item_id: &str, action: &str) {
+    if let Err(e) = queries::record_usage(conn, item_id, action, &Utc::now().to_rfc3339()) {
+        eprintln!("Failed to record {} usage for {}: {}", action, item_id, e);
+    }
+}
+
+#[tauri::command]
+pub fn record_usage(
+    app_handle: tauri::AppHandle,
+    state: State<AppState>,
+    id: String,
+    action: String,
+) -> Result<(), String> {
+    if !VALID_ACTIONS.contains(&action.as_str()) {
+        return Err(format!("Unknown usage action: {}", action));
+    }
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        queries::record_usage(&db, &id, &action, &Utc::now().to_rfc3339())
+            .map_err(|e| e.to_string())?;
+    }
+    let _ = app_handle.emit("usage-changed", &id);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_most_used(state: State<AppState>, limit: Option<usize>) -> Result<Vec<Item>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    queries::most_used_items(&db, limit.unwrap_or(DEFAULT_LIMIT)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_recently_used(
+    state: State<AppState>,
+    limit: Option<usize>,
+) -> Result<Vec<Item>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    queries::recently_used_items(&db, limit.unwrap_or(DEFAULT_LIMIT)).map_err(|e| e.to_string())
+}
@@ -0,0 +1,75 @@
+use crate::secrets::vault;
+use crate::state::AppState;
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{Emitter, Manager, State};
+
+/// How often the auto-lock timeout is checked.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Serialize)]
+pub struct VaultStatus {
+    pub unlocked: bool,
+    pub auto_lock_minutes: u64,
+}
+
+#[tauri::command]
+pub fn unlock_vault(
+    app_handle: tauri::AppHandle,
+    state: State<AppState>,
+    passphrase: String,
+) -> Result<(), String> {
+    // Takes the DB lock only around its reads and writes, not the slow key derivation
+    state.vault.unlock(&state.db, &passphrase)?;
+
+    let _ = app_handle.emit("vault-unlocked", ());
+    // Encrypted items now come back with their content
+    let _ = app_handle.emit("items-changed", "vault");
+    Ok(())
+}
+
+#[tauri::command]
+pub fn lock_vault(app_handle: tauri::AppHandle, state: State<AppState>) -> Result<(), String> {
+    if state.vault.lock() {
+        emit_locked(&app_handle);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_vault_status(state: State<AppState>) -> Result<VaultStatus, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    Ok(VaultStatus {
+        unlocked: state.vault.is_unlocked(),
+        auto_lock_minutes: vault::auto_lock_minutes(&db),
+    })
+}
+
+/// Set the idle time before the vault locks itself; 0 keeps it unlocked until
+/// `lock_vault` or quit.
+#[tauri::command]
+pub fn set_vault_auto_lock(state: State<AppState>, minutes: u64) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    vault::set_auto_lock_minutes(&db, minutes)?;
+    drop(db);
+    state.vault.set_auto_lock(minutes);
+    Ok(())
+}
+
+fn emit_locked(app_handle: &tauri::AppHandle) {
+    let _ = app_handle.emit("vault-locked", ());
+    let _ = app_handle.emit("items-changed", "vault");
+}
+
+/// Lock the vault once it has been idle for the auto-lock timeout.
+pub fn start_auto_lock(app_handle: &tauri::AppHandle) {
+    let app_handle = app_handle.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            tokio::time::sleep(IDLE_CHECK_INTERVAL).await;
+            if app_handle.state::<AppState>().vault.lock_if_idle() {
+                emit_locked(&app_handle);
+            }
+        }
+    });
+}
@@ -0,0 +1,40 @@
+use crate::db::vocabulary::{self, Vocabulary};
+use crate::state::AppState;
+use tauri::{Emitter, State};
+
+#[tauri::command]
+pub fn get_vocabulary(state: State<AppState>) -> Result<Vocabulary, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    Ok(vocabulary::load(&db))
+}
+
+/// Replace the item type and language lists. Existing items keep their type and
+/// language even if these are no longer listed.
+#[tauri::command]
+pub fn set_vocabulary(
+    app_handle: tauri::AppHandle,
+    state: State<AppState>,
+    vocabulary: Vocabulary,
+) -> Result<Vocabulary, String> {
+    let vocabulary = vocabulary.validated()?;
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        vocabulary::save(&db, &vocabulary)?;
+    }
+    let _ = app_handle.emit("vocabulary-changed", &vocabulary);
+    Ok(vocabulary)
+}
+
+#[tauri::command]
+pub fn reset_vocabulary(
+    app_handle: tauri::AppHandle,
+    state: State<AppState>,
+) -> Result<Vocabulary, String> {
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        vocabulary::reset(&db)?;
+    }
+    let vocabulary = Vocabulary::default();
+    let _ = app_handle.emit("vocabulary-changed", &vocabulary);
+    Ok(vocabulary)
+}
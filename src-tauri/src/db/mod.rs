@@ -1,2 +1,4 @@
 pub mod queries;
 pub mod schema;
+pub mod synonyms;
+pub mod vocabulary;
@@ -1,5 +1,7 @@
+use super::synonyms::TagSynonyms;
 use rusqlite::{params, Connection, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Item {
@@ -16,13 +18,37 @@ pub struct Item {
     pub file_hash: String,
     pub created: String,
     pub modified: String,
+    #[serde(default)]
+    pub trusted: bool,
+    #[serde(default)]
+    pub pinned: bool,
+    #[serde(default)]
+    pub slot: Option<u8>,
+    /// Set by `ai: never` in the frontmatter; the item is never sent to an AI provider.
+    #[serde(default)]
+    pub ai_opt_out: bool,
+    /// `encrypted: true`: the body is stored only as `ciphertext`, and `content` is
+    /// empty unless the vault has filled it in.
+    #[serde(default)]
+    pub encrypted: bool,
+    #[serde(skip)]
+    pub ciphertext: String,
 }
 
+/// Column list matching `Item::from_row`, for queries that alias `items` as `i`.
+const ITEM_COLUMNS: &str = "i.id, i.title, i.item_type, i.language, i.tags, i.folder, i.description, i.content, \
+     i.file_path, i.file_hash, i.created, i.modified, i.trusted, i.pinned, i.slot, i.ai_opt_out, i.encrypted, i.ciphertext";
+
 impl Item {
     pub fn tags_string(&self) -> String {
         self.tags.join(",")
     }
 
+    /// Whether the item may be sent to an AI provider at all.
+    pub fn ai_allowed(&self) -> bool {
+        !self.ai_opt_out && !self.encrypted
+    }
+
     pub fn from_row(row: &rusqlite::Row) -> Result<Self> {
         let tags_str: String = row.get(4)?;
         Ok(Item {
@@ -42,14 +68,20 @@ impl Item {
             file_hash: row.get(9)?,
             created: row.get(10)?,
             modified: row.get(11)?,
+            trusted: row.get(12)?,
+            pinned: row.get(13)?,
+            slot: row.get(14)?,
+            ai_opt_out: row.get(15)?,
+            encrypted: row.get(16)?,
+            ciphertext: row.get(17)?,
         })
     }
 }
 
 pub fn insert_item(conn: &Connection, item: &Item) -> Result<()> {
     conn.execute(
-        "INSERT OR REPLACE INTO items (id, title, item_type, language, tags, folder, description, content, file_path, file_hash, created, modified)
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+        "INSERT OR REPLACE INTO items (id, title, item_type, language, tags, folder, description, content, file_path, file_hash, created, modified, trusted, pinned, slot, ai_opt_out, encrypted, ciphertext)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
         params![
             item.id,
             item.title,
@@ -58,11 +90,18 @@ pub fn insert_item(conn: &Connection, item: &Item) -> Result<()> {
             item.tags_string(),
             item.folder,
             item.description,
-            item.content,
+            // Decrypted text never reaches the table (or the FTS index built from it)
+            if item.encrypted { "" } else { item.content.as_str() },
             item.file_path,
             item.file_hash,
             item.created,
             item.modified,
+            item.trusted,
+            item.pinned,
+            item.slot,
+            item.ai_opt_out,
+            item.encrypted,
+            item.ciphertext,
         ],
     )?;
     Ok(())
@@ -70,14 +109,18 @@ pub fn insert_item(conn: &Connection, item: &Item) -> Result<()> {
 
 pub fn delete_item(conn: &Connection, id: &str) -> Result<()> {
     conn.execute("DELETE FROM items WHERE id = ?1", params![id])?;
-    Ok(())
+    conn.execute("DELETE FROM secret_findings WHERE item_id = ?1", params![id])?;
+    conn.execute("DELETE FROM usage WHERE item_id = ?1", params![id])?;
+    conn.execute("DELETE FROM runs WHERE item_id = ?1", params![id])?;
+    unlink_item(conn, id)?;
+    prune_term_vectors(conn)
 }
 
 pub fn get_item(conn: &Connection, id: &str) -> Result<Option<Item>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, title, item_type, language, tags, folder, description, content, file_path, file_hash, created, modified
-         FROM items WHERE id = ?1",
-    )?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM items i WHERE i.id = ?1",
+        ITEM_COLUMNS
+    ))?;
     let mut rows = stmt.query_map(params![id], Item::from_row)?;
     match rows.next() {
         Some(row) => Ok(Some(row?)),
@@ -85,16 +128,32 @@ pub fn get_item(conn: &Connection, id: &str) -> Result<Option<Item>> {
     }
 }
 
+/// Items ordered by frecency: recent use plus a one-use bonus for recent edits,
+/// so freshly written items aren't buried under frequently copied ones.
 pub fn list_items(conn: &Connection) -> Result<Vec<Item>> {
-    let mut stmt = conn.prepare(
-        "SELECT id, title, item_type, language, tags, folder, description, content, file_path, file_hash, created, modified
-         FROM items ORDER BY modified DESC",
-    )?;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}
+         FROM items i
+         LEFT JOIN item_frecency u ON u.item_id = i.id
+         ORDER BY COALESCE(u.score, 0) + CASE
+             WHEN julianday('now') - julianday(i.modified) < 4 THEN 100
+             WHEN julianday('now') - julianday(i.modified) < 14 THEN 70
+             WHEN julianday('now') - julianday(i.modified) < 31 THEN 50
+             ELSE 0
+         END DESC, i.modified DESC",
+        ITEM_COLUMNS
+    ))?;
     let rows = stmt.query_map([], Item::from_row)?;
     rows.collect()
 }
 
-pub fn search_items(conn: &Connection, query: &str) -> Result<Vec<Item>> {
+/// bm25 is negative (lower is better), so scaling it up by frecency pulls
+/// often-used items forward. A maxed-out score (10 uses this week) doubles the rank.
+const FRECENCY_SCALE: f64 = 1000.0;
+
+/// Full-text search. A word that is a tag synonym also matches the other names
+/// of its tag, so `k8s` finds items tagged or mentioning `kubernetes`.
+pub fn search_items(conn: &Connection, query: &str, synonyms: &TagSynonyms) -> Result<Vec<Item>> {
     if query.trim().is_empty() {
         return list_items(conn);
     }
@@ -102,21 +161,76 @@ pub fn search_items(conn: &Connection, query: &str) -> Result<Vec<Item>> {
     // Build FTS5 query: add prefix matching with *
     let fts_query = query
         .split_whitespace()
-        .map(|word| format!("\"{}\"*", word.replace('"', "")))
+        .map(|word| {
+            let names: Vec<String> = synonyms
+                .expand(word)
+                .iter()
+                .map(|name| format!("\"{}\"*", name.replace('"', "")))
+                .collect();
+            if names.len() == 1 {
+                names[0].clone()
+            } else {
+                format!("({})", names.join(" OR "))
+            }
+        })
         .collect::<Vec<_>>()
-        .join(" ");
+        // FTS5 only allows the implicit AND between plain phrases
+        .join(" AND ");
 
-    let mut stmt = conn.prepare(
-        "SELECT i.id, i.title, i.item_type, i.language, i.tags, i.folder, i.description, i.content, i.file_path, i.file_hash, i.created, i.modified
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}
          FROM items i
          JOIN items_fts f ON i.id = f.id
+         LEFT JOIN item_frecency u ON u.item_id = i.id
          WHERE items_fts MATCH ?1
-         ORDER BY bm25(items_fts, 0.0, 10.0, 5.0, 3.0, 1.0)",
-    )?;
-    let rows = stmt.query_map(params![fts_query], Item::from_row)?;
+         ORDER BY bm25(items_fts, 0.0, 10.0, 5.0, 3.0, 1.0) * (1.0 + COALESCE(u.score, 0) / ?2)",
+        ITEM_COLUMNS
+    ))?;
+    let rows = stmt.query_map(params![fts_query, FRECENCY_SCALE], Item::from_row)?;
+    rows.collect()
+}
+
+pub fn pinned_items(conn: &Connection) -> Result<Vec<Item>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM items i WHERE i.pinned = 1 ORDER BY i.slot IS NULL, i.slot, i.title",
+        ITEM_COLUMNS
+    ))?;
+    let rows = stmt.query_map([], Item::from_row)?;
     rows.collect()
 }
 
+pub fn slotted_items(conn: &Connection) -> Result<Vec<Item>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM items i WHERE i.slot IS NOT NULL ORDER BY i.slot",
+        ITEM_COLUMNS
+    ))?;
+    let rows = stmt.query_map([], Item::from_row)?;
+    rows.collect()
+}
+
+pub fn get_item_by_slot(conn: &Connection, slot: u8) -> Result<Option<Item>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM items i WHERE i.slot = ?1 ORDER BY i.modified DESC",
+        ITEM_COLUMNS
+    ))?;
+    let mut rows = stmt.query_map(params![slot], Item::from_row)?;
+    match rows.next() {
+        Some(row) => Ok(Some(row?)),
+        None => Ok(None),
+    }
+}
+
+/// Some encrypted item's id and ciphertext, to check a passphrase against.
+pub fn first_encrypted_item(conn: &Connection) -> Result<Option<(String, String)>> {
+    let mut stmt =
+        conn.prepare("SELECT id, ciphertext FROM items WHERE encrypted = 1 AND ciphertext != '' LIMIT 1")?;
+    let mut rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    match rows.next() {
+        Some(row) => Ok(Some(row?)),
+        None => Ok(None),
+    }
+}
+
 pub fn get_all_tags(conn: &Connection) -> Result<Vec<String>> {
     let mut stmt = conn.prepare("SELECT DISTINCT tags FROM items WHERE tags != ''")?;
     let rows = stmt.query_map([], |row| row.get::<_, String>(0))?;
@@ -150,7 +264,738 @@ pub fn get_file_hash(conn: &Connection, file_path: &str) -> Result<Option<String
     }
 }
 
+/// `(id, file_hash)` of every item, to spot what changed without loading content.
+pub fn list_item_hashes(conn: &Connection) -> Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare("SELECT id, file_hash FROM items")?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
 pub fn delete_item_by_path(conn: &Connection, file_path: &str) -> Result<()> {
+    let ids: Vec<String> = conn
+        .prepare("SELECT id FROM items WHERE file_path = ?1")?
+        .query_map(params![file_path], |row| row.get(0))?
+        .collect::<Result<_>>()?;
+    for table in ["secret_findings", "usage", "runs"] {
+        conn.execute(
+            &format!(
+                "DELETE FROM {} WHERE item_id IN (SELECT id FROM items WHERE file_path = ?1)",
+                table
+            ),
+            params![file_path],
+        )?;
+    }
     conn.execute("DELETE FROM items WHERE file_path = ?1", params![file_path])?;
+    for id in ids {
+        unlink_item(conn, &id)?;
+    }
+    prune_term_vectors(conn)
+}
+
+pub fn get_setting(conn: &Connection, key: &str) -> Result<Option<String>> {
+    let mut stmt = conn.prepare("SELECT value FROM settings WHERE key = ?1")?;
+    let mut rows = stmt.query_map(params![key], |row| row.get::<_, String>(0))?;
+    match rows.next() {
+        Some(row) => Ok(Some(row?)),
+        None => Ok(None),
+    }
+}
+
+pub fn set_setting(conn: &Connection, key: &str, value: &str) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+        params![key, value],
+    )?;
+    Ok(())
+}
+
+pub fn delete_setting(conn: &Connection, key: &str) -> Result<()> {
+    conn.execute("DELETE FROM settings WHERE key = ?1", params![key])?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub id: String,
+    pub item_id: String,
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub duration_ms: u64,
+    pub started: String,
+}
+
+pub fn insert_run(conn: &Connection, run: &RunRecord) -> Result<()> {
+    conn.execute(
+        "INSERT INTO runs (id, item_id, exit_code, timed_out, duration_ms, started)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            run.id,
+            run.item_id,
+            run.exit_code,
+            run.timed_out,
+            run.duration_ms as i64,
+            run.started,
+        ],
+    )?;
+    Ok(())
+}
+
+pub fn list_runs(conn: &Connection, item_id: &str, limit: usize) -> Result<Vec<RunRecord>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, item_id, exit_code, timed_out, duration_ms, started
+         FROM runs WHERE item_id = ?1 ORDER BY started DESC LIMIT ?2",
+    )?;
+    let rows = stmt.query_map(params![item_id, limit as i64], |row| {
+        Ok(RunRecord {
+            id: row.get(0)?,
+            item_id: row.get(1)?,
+            exit_code: row.get(2)?,
+            timed_out: row.get(3)?,
+            duration_ms: row.get::<_, i64>(4)? as u64,
+            started: row.get(5)?,
+        })
+    })?;
+    rows.collect()
+}
+
+pub fn record_usage(conn: &Connection, item_id: &str, action: &str, used_at: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO usage (item_id, action, used_at) VALUES (?1, ?2, ?3)",
+        params![item_id, action, used_at],
+    )?;
+    Ok(())
+}
+
+/// Drop usage events older than a year; they no longer affect frecency.
+pub fn prune_usage(conn: &Connection) -> Result<usize> {
+    conn.execute(
+        "DELETE FROM usage WHERE julianday('now') - julianday(used_at) > 365",
+        [],
+    )
+}
+
+pub fn get_ai_cache(conn: &Connection, key: &str, ttl_days: u32) -> Result<Option<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT value FROM ai_cache
+         WHERE key = ?1 AND julianday('now') - julianday(created) <= ?2",
+    )?;
+    let mut rows = stmt.query_map(params![key, ttl_days], |row| row.get::<_, String>(0))?;
+    match rows.next() {
+        Some(row) => Ok(Some(row?)),
+        None => Ok(None),
+    }
+}
+
+pub fn put_ai_cache(
+    conn: &Connection,
+    key: &str,
+    kind: &str,
+    value: &str,
+    created: &str,
+) -> Result<()> {
+    conn.execute(
+        "INSERT OR REPLACE INTO ai_cache (key, kind, value, created) VALUES (?1, ?2, ?3, ?4)",
+        params![key, kind, value, created],
+    )?;
+    Ok(())
+}
+
+pub fn prune_ai_cache(conn: &Connection, ttl_days: u32) -> Result<usize> {
+    conn.execute(
+        "DELETE FROM ai_cache WHERE julianday('now') - julianday(created) > ?1",
+        params![ttl_days],
+    )
+}
+
+pub fn clear_ai_cache(conn: &Connection) -> Result<usize> {
+    conn.execute("DELETE FROM ai_cache", [])
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AiUsageEntry {
+    pub operation: String,
+    pub provider: String,
+    pub model: String,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+    pub created: String,
+}
+
+/// Token and cost totals for one provider/model over one day or month.
+#[derive(Debug, Clone, Serialize)]
+pub struct AiUsageRollup {
+    pub period: String,
+    pub provider: String,
+    pub model: String,
+    pub calls: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+}
+
+pub fn insert_ai_usage(conn: &Connection, entry: &AiUsageEntry) -> Result<()> {
+    conn.execute(
+        "INSERT INTO ai_usage (operation, provider, model, input_tokens, output_tokens, cost_usd, created)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![
+            entry.operation,
+            entry.provider,
+            entry.model,
+            entry.input_tokens as i64,
+            entry.output_tokens as i64,
+            entry.cost_usd,
+            entry.created,
+        ],
+    )?;
+    Ok(())
+}
+
+/// Usage since `since` (RFC 3339), grouped by `strftime(period_format)` of the
+/// entry time, newest period first.
+pub fn ai_usage_rollup(
+    conn: &Connection,
+    period_format: &str,
+    since: &str,
+) -> Result<Vec<AiUsageRollup>> {
+    let mut stmt = conn.prepare(
+        "SELECT strftime(?1, created) AS period, provider, model, COUNT(*),
+                SUM(input_tokens), SUM(output_tokens), SUM(cost_usd)
+         FROM ai_usage
+         WHERE created >= ?2
+         GROUP BY period, provider, model
+         ORDER BY period DESC, SUM(cost_usd) DESC",
+    )?;
+    let rows = stmt.query_map(params![period_format, since], |row| {
+        Ok(AiUsageRollup {
+            period: row.get(0)?,
+            provider: row.get(1)?,
+            model: row.get(2)?,
+            calls: row.get::<_, i64>(3)? as u64,
+            input_tokens: row.get::<_, i64>(4)? as u64,
+            output_tokens: row.get::<_, i64>(5)? as u64,
+            cost_usd: row.get(6)?,
+        })
+    })?;
+    rows.collect()
+}
+
+pub fn ai_cost_since(conn: &Connection, since: &str) -> Result<f64> {
+    conn.query_row(
+        "SELECT COALESCE(SUM(cost_usd), 0) FROM ai_usage WHERE created >= ?1",
+        params![since],
+        |row| row.get(0),
+    )
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RedactionLogEntry {
+    pub id: i64,
+    pub provider: String,
+    pub rule: String,
+    pub fingerprint: String,
+    pub created: String,
+}
+
+pub fn insert_redactions(
+    conn: &Connection,
+    provider: &str,
+    redactions: &[(String, String)],
+    created: &str,
+) -> Result<()> {
+    let mut stmt = conn.prepare(
+        "INSERT INTO redaction_log (provider, rule, fingerprint, created) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for (rule, fingerprint) in redactions {
+        stmt.execute(params![provider, rule, fingerprint, created])?;
+    }
+    Ok(())
+}
+
+pub fn list_redactions(conn: &Connection, limit: usize) -> Result<Vec<RedactionLogEntry>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, provider, rule, fingerprint, created FROM redaction_log
+         ORDER BY id DESC LIMIT ?1",
+    )?;
+    let rows = stmt.query_map(params![limit as i64], |row| {
+        Ok(RedactionLogEntry {
+            id: row.get(0)?,
+            provider: row.get(1)?,
+            rule: row.get(2)?,
+            fingerprint: row.get(3)?,
+            created: row.get(4)?,
+        })
+    })?;
+    rows.collect()
+}
+
+pub fn clear_redactions(conn: &Connection) -> Result<usize> {
+    conn.execute("DELETE FROM redaction_log", [])
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SecretFinding {
+    pub item_id: String,
+    pub item_title: String,
+    pub rule: String,
+    /// 1-based line of the content the value starts on.
+    pub line: usize,
+    pub fingerprint: String,
+}
+
+pub fn replace_secret_findings(
+    conn: &Connection,
+    item_id: &str,
+    findings: &[SecretFinding],
+) -> Result<()> {
+    conn.execute(
+        "DELETE FROM secret_findings WHERE item_id = ?1",
+        params![item_id],
+    )?;
+    let mut stmt = conn.prepare(
+        "INSERT INTO secret_findings (item_id, rule, line, fingerprint) VALUES (?1, ?2, ?3, ?4)",
+    )?;
+    for f in findings {
+        stmt.execute(params![item_id, f.rule, f.line as i64, f.fingerprint])?;
+    }
+    Ok(())
+}
+
+/// Findings for one item, or for every item when `item_id` is `None`.
+pub fn list_secret_findings(conn: &Connection, item_id: Option<&str>) -> Result<Vec<SecretFinding>> {
+    let mut stmt = conn.prepare(
+        "SELECT f.item_id, i.title, f.rule, f.line, f.fingerprint
+         FROM secret_findings f
+         JOIN items i ON i.id = f.item_id
+         WHERE ?1 IS NULL OR f.item_id = ?1
+         ORDER BY i.title, f.line",
+    )?;
+    let rows = stmt.query_map(params![item_id], |row| {
+        Ok(SecretFinding {
+            item_id: row.get(0)?,
+            item_title: row.get(1)?,
+            rule: row.get(2)?,
+            line: row.get::<_, i64>(3)? as usize,
+            fingerprint: row.get(4)?,
+        })
+    })?;
+    rows.collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct AiJob {
+    pub item_id: String,
+    /// JSON of the fields enrichment should fill.
+    pub fields: String,
+    pub generation: i64,
+}
+
+pub fn get_ai_job(conn: &Connection, item_id: &str) -> Result<Option<AiJob>> {
+    let mut stmt =
+        conn.prepare("SELECT item_id, fields, generation FROM ai_jobs WHERE item_id = ?1")?;
+    let mut rows = stmt.query_map(params![item_id], |row| {
+        Ok(AiJob {
+            item_id: row.get(0)?,
+            fields: row.get(1)?,
+            generation: row.get(2)?,
+        })
+    })?;
+    match rows.next() {
+        Some(row) => Ok(Some(row?)),
+        None => Ok(None),
+    }
+}
+
+/// Insert or replace the job for an item. A job that is already running keeps
+/// running; `finish_ai_job` puts it back to pending afterwards.
+pub fn upsert_ai_job(conn: &Connection, item_id: &str, fields: &str, now: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO ai_jobs (item_id, fields, status, generation, attempts, created, updated)
+         VALUES (?1, ?2, 'pending', 0, 0, ?3, ?3)
+         ON CONFLICT(item_id) DO UPDATE SET
+             fields = excluded.fields,
+             generation = generation + 1,
+             updated = excluded.updated",
+        params![item_id, fields, now],
+    )?;
+    Ok(())
+}
+
+/// Mark up to `limit` of the oldest pending jobs as running and return them.
+pub fn claim_ai_jobs(conn: &Connection, limit: usize, now: &str) -> Result<Vec<AiJob>> {
+    let jobs: Vec<AiJob> = {
+        let mut stmt = conn.prepare(
+            "SELECT item_id, fields, generation FROM ai_jobs
+             WHERE status = 'pending'
+             ORDER BY created
+             LIMIT ?1",
+        )?;
+        let rows = stmt.query_map(params![limit as i64], |row| {
+            Ok(AiJob {
+                item_id: row.get(0)?,
+                fields: row.get(1)?,
+                generation: row.get(2)?,
+            })
+        })?;
+        rows.collect::<Result<_>>()?
+    };
+    for job in &jobs {
+        conn.execute(
+            "UPDATE ai_jobs SET status = 'running', attempts = attempts + 1, updated = ?2
+             WHERE item_id = ?1",
+            params![job.item_id, now],
+        )?;
+    }
+    Ok(jobs)
+}
+
+/// Remove a finished job, unless the item was queued again while it ran, in
+/// which case the newer request stays pending.
+pub fn finish_ai_job(conn: &Connection, item_id: &str, generation: i64) -> Result<()> {
+    conn.execute(
+        "DELETE FROM ai_jobs WHERE item_id = ?1 AND generation = ?2",
+        params![item_id, generation],
+    )?;
+    conn.execute(
+        "UPDATE ai_jobs SET status = 'pending' WHERE item_id = ?1 AND status = 'running'",
+        params![item_id],
+    )?;
+    Ok(())
+}
+
+/// Requeue jobs interrupted by a shutdown, dropping any that have already been
+/// interrupted `max_attempts` times. Returns the number requeued.
+pub fn recover_ai_jobs(conn: &Connection, max_attempts: u32) -> Result<usize> {
+    conn.execute(
+        "DELETE FROM ai_jobs WHERE status = 'running' AND attempts >= ?1",
+        params![max_attempts],
+    )?;
+    conn.execute(
+        "UPDATE ai_jobs SET status = 'pending' WHERE status = 'running'",
+        [],
+    )
+}
+
+/// Number of jobs per status.
+pub fn count_ai_jobs(conn: &Connection) -> Result<Vec<(String, usize)>> {
+    let mut stmt = conn.prepare("SELECT status, COUNT(*) FROM ai_jobs GROUP BY status")?;
+    let rows = stmt.query_map([], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as usize))
+    })?;
+    rows.collect()
+}
+
+pub fn most_used_items(conn: &Connection, limit: usize) -> Result<Vec<Item>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}
+         FROM items i
+         JOIN (SELECT item_id, COUNT(*) AS uses FROM usage GROUP BY item_id) u ON u.item_id = i.id
+         ORDER BY u.uses DESC, i.modified DESC
+         LIMIT ?1",
+        ITEM_COLUMNS
+    ))?;
+    let rows = stmt.query_map(params![limit as i64], Item::from_row)?;
+    rows.collect()
+}
+
+pub fn recently_used_items(conn: &Connection, limit: usize) -> Result<Vec<Item>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {}
+         FROM items i
+         JOIN (SELECT item_id, MAX(used_at) AS last_used FROM usage GROUP BY item_id) u ON u.item_id = i.id
+         ORDER BY u.last_used DESC
+         LIMIT ?1",
+        ITEM_COLUMNS
+    ))?;
+    let rows = stmt.query_map(params![limit as i64], Item::from_row)?;
+    rows.collect()
+}
+
+/// A `[[target]]` link from one item to another. `target_id` is `None` for a
+/// dangling link whose target matches no item.
+#[derive(Debug, Clone, Serialize)]
+pub struct Link {
+    pub source_id: String,
+    pub source_title: String,
+    pub target: String,
+    pub target_id: Option<String>,
+    pub target_title: Option<String>,
+}
+
+/// What link targets resolve against: every item id, and each title folded with
+/// `to_lowercase` mapped to the oldest item carrying it. Titles are folded in Rust
+/// rather than with SQLite's `NOCASE`, which only folds ASCII, so resolving
+/// matches exactly what `links::rewrite_links` rewrites.
+struct LinkTargets {
+    ids: HashSet<String>,
+    titles: HashMap<String, String>,
+}
+
+impl LinkTargets {
+    fn load(conn: &Connection) -> Result<Self> {
+        let mut stmt = conn.prepare("SELECT id, title FROM items ORDER BY created")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?;
+        let mut targets = LinkTargets {
+            ids: HashSet::new(),
+            titles: HashMap::new(),
+        };
+        for row in rows {
+            let (id, title) = row?;
+            targets.titles.entry(title.to_lowercase()).or_insert_with(|| id.clone());
+            targets.ids.insert(id);
+        }
+        Ok(targets)
+    }
+
+    /// The item a link target names: an exact id, else a case-insensitive title.
+    fn resolve(&self, target: &str) -> Option<&str> {
+        if let Some(id) = self.ids.get(target) {
+            return Some(id);
+        }
+        self.titles.get(&target.to_lowercase()).map(String::as_str)
+    }
+}
+
+/// Replace an item's links. They start out dangling; `resolve_dangling_links`
+/// points them at the items they name.
+pub fn replace_links(conn: &Connection, source_id: &str, targets: &[String]) -> Result<()> {
+    conn.execute("DELETE FROM links WHERE source_id = ?1", params![source_id])?;
+    let mut stmt = conn.prepare("INSERT OR IGNORE INTO links (source_id, target) VALUES (?1, ?2)")?;
+    for target in targets {
+        stmt.execute(params![source_id, target])?;
+    }
+    Ok(())
+}
+
+/// Point dangling links at items that now match them.
+pub fn resolve_dangling_links(conn: &Connection) -> Result<()> {
+    let dangling: Vec<(String, String)> = conn
+        .prepare("SELECT source_id, target FROM links WHERE target_id IS NULL")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_>>()?;
+    if dangling.is_empty() {
+        return Ok(());
+    }
+    let targets = LinkTargets::load(conn)?;
+    let mut stmt =
+        conn.prepare("UPDATE links SET target_id = ?3 WHERE source_id = ?1 AND target = ?2")?;
+    for (source_id, target) in &dangling {
+        if let Some(id) = targets.resolve(target) {
+            stmt.execute(params![source_id, target, id])?;
+        }
+    }
     Ok(())
 }
+
+/// Drop a deleted item's own links and leave links to it dangling, or pointing at
+/// another item with the same title.
+fn unlink_item(conn: &Connection, id: &str) -> Result<()> {
+    conn.execute("DELETE FROM links WHERE source_id = ?1", params![id])?;
+    conn.execute("UPDATE links SET target_id = NULL WHERE target_id = ?1", params![id])?;
+    resolve_dangling_links(conn)
+}
+
+const LINK_COLUMNS: &str = "l.source_id, s.title, l.target, l.target_id, t.title";
+
+fn link_from_row(row: &rusqlite::Row) -> Result<Link> {
+    Ok(Link {
+        source_id: row.get(0)?,
+        source_title: row.get(1)?,
+        target: row.get(2)?,
+        target_id: row.get(3)?,
+        target_title: row.get(4)?,
+    })
+}
+
+/// Links out of an item, dangling ones included.
+pub fn get_outgoing_links(conn: &Connection, source_id: &str) -> Result<Vec<Link>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM links l
+         JOIN items s ON s.id = l.source_id
+         LEFT JOIN items t ON t.id = l.target_id
+         WHERE l.source_id = ?1
+         ORDER BY l.target COLLATE NOCASE",
+        LINK_COLUMNS
+    ))?;
+    let rows = stmt.query_map(params![source_id], link_from_row)?;
+    rows.collect()
+}
+
+/// Links from other items that resolve to this one.
+pub fn get_backlinks(conn: &Connection, target_id: &str) -> Result<Vec<Link>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM links l
+         JOIN items s ON s.id = l.source_id
+         LEFT JOIN items t ON t.id = l.target_id
+         WHERE l.target_id = ?1 AND l.source_id != ?1
+         ORDER BY s.title COLLATE NOCASE",
+        LINK_COLUMNS
+    ))?;
+    let rows = stmt.query_map(params![target_id], link_from_row)?;
+    rows.collect()
+}
+
+/// How closely one item relates to another, on a 0-1 scale.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RelatedScore {
+    pub related_id: String,
+    pub score: f64,
+    pub text_score: f64,
+    pub tag_score: f64,
+}
+
+/// A related item as shown next to an item.
+#[derive(Debug, Clone, Serialize)]
+pub struct RelatedItem {
+    pub id: String,
+    pub title: String,
+    #[serde(rename = "type")]
+    pub item_type: String,
+    pub folder: String,
+    pub score: f64,
+    pub text_score: f64,
+    pub tag_score: f64,
+}
+
+pub fn replace_item_terms(
+    conn: &Connection,
+    item_id: &str,
+    file_hash: &str,
+    terms: &[(String, f64)],
+) -> Result<()> {
+    conn.execute("DELETE FROM item_terms WHERE item_id = ?1", params![item_id])?;
+    let mut stmt = conn.prepare("INSERT INTO item_terms (item_id, term, weight) VALUES (?1, ?2, ?3)")?;
+    for (term, weight) in terms {
+        stmt.execute(params![item_id, term, weight])?;
+    }
+    conn.execute(
+        "INSERT OR REPLACE INTO term_vectors (item_id, file_hash) VALUES (?1, ?2)",
+        params![item_id, file_hash],
+    )?;
+    Ok(())
+}
+
+/// Items changed (or added) since their term vector was computed.
+pub fn stale_term_items(conn: &Connection) -> Result<Vec<Item>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM items i
+         LEFT JOIN term_vectors v ON v.item_id = i.id
+         WHERE v.file_hash IS NULL OR v.file_hash != i.file_hash",
+        ITEM_COLUMNS
+    ))?;
+    let rows = stmt.query_map([], Item::from_row)?;
+    rows.collect()
+}
+
+/// Drop vectors and scores of items that no longer exist.
+pub fn prune_term_vectors(conn: &Connection) -> Result<()> {
+    conn.execute_batch(
+        "DELETE FROM item_terms WHERE item_id NOT IN (SELECT id FROM items);
+         DELETE FROM term_vectors WHERE item_id NOT IN (SELECT id FROM items);
+         DELETE FROM related_items
+         WHERE item_id NOT IN (SELECT id FROM items) OR related_id NOT IN (SELECT id FROM items);",
+    )
+}
+
+/// Cosine similarity of an item's term vector with every item sharing a term.
+pub fn term_similarities(conn: &Connection, item_id: &str) -> Result<Vec<(String, f64)>> {
+    let mut stmt = conn.prepare(
+        "SELECT b.item_id, SUM(a.weight * b.weight)
+         FROM item_terms a
+         JOIN item_terms b ON b.term = a.term AND b.item_id != a.item_id
+         WHERE a.item_id = ?1
+         GROUP BY b.item_id",
+    )?;
+    let rows = stmt.query_map(params![item_id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// Every item's id and tags, without the content.
+pub fn list_item_tags(conn: &Connection) -> Result<Vec<(String, Vec<String>)>> {
+    let mut stmt = conn.prepare("SELECT id, tags FROM items")?;
+    let rows = stmt.query_map([], |row| {
+        let tags: String = row.get(1)?;
+        Ok((
+            row.get(0)?,
+            tags.split(',')
+                .filter(|s| !s.is_empty())
+                .map(|s| s.trim().to_string())
+                .collect(),
+        ))
+    })?;
+    rows.collect()
+}
+
+/// Replace every pair involving `item_id`, writing both directions.
+pub fn replace_related(conn: &Connection, item_id: &str, related: &[RelatedScore]) -> Result<()> {
+    conn.execute(
+        "DELETE FROM related_items WHERE item_id = ?1 OR related_id = ?1",
+        params![item_id],
+    )?;
+    let mut stmt = conn.prepare(
+        "INSERT OR REPLACE INTO related_items (item_id, related_id, score, text_score, tag_score)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+    )?;
+    for r in related {
+        stmt.execute(params![item_id, r.related_id, r.score, r.text_score, r.tag_score])?;
+        stmt.execute(params![r.related_id, item_id, r.score, r.text_score, r.tag_score])?;
+    }
+    Ok(())
+}
+
+/// The `limit` items most related to `item_id`, best first.
+pub fn get_related_items(conn: &Connection, item_id: &str, limit: usize) -> Result<Vec<RelatedItem>> {
+    let mut stmt = conn.prepare(
+        "SELECT i.id, i.title, i.item_type, i.folder, r.score, r.text_score, r.tag_score
+         FROM related_items r
+         JOIN items i ON i.id = r.related_id
+         WHERE r.item_id = ?1
+         ORDER BY r.score DESC, i.title
+         LIMIT ?2",
+    )?;
+    let rows = stmt.query_map(params![item_id, limit as i64], |row| {
+        Ok(RelatedItem {
+            id: row.get(0)?,
+            title: row.get(1)?,
+            item_type: row.get(2)?,
+            folder: row.get(3)?,
+            score: row.get(4)?,
+            text_score: row.get(5)?,
+            tag_score: row.get(6)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Each related pair once, as (item, related item, scores).
+pub fn list_related_pairs(conn: &Connection, min_score: f64) -> Result<Vec<(String, RelatedScore)>> {
+    let mut stmt = conn.prepare(
+        "SELECT item_id, related_id, score, text_score, tag_score
+         FROM related_items
+         WHERE item_id < related_id AND score >= ?1",
+    )?;
+    let rows = stmt.query_map(params![min_score], |row| {
+        Ok((
+            row.get(0)?,
+            RelatedScore {
+                related_id: row.get(1)?,
+                score: row.get(2)?,
+                text_score: row.get(3)?,
+                tag_score: row.get(4)?,
+            },
+        ))
+    })?;
+    rows.collect()
+}
+
+/// Resolved links as (source, target) pairs; self-links are left out.
+pub fn list_resolved_links(conn: &Connection) -> Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare(
+        "SELECT DISTINCT source_id, target_id FROM links
+         WHERE target_id IS NOT NULL AND target_id != source_id",
+    )?;
+    let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
@@ -15,7 +15,10 @@ pub fn initialize_db(conn: &Connection) -> Result<()> {
             file_path TEXT NOT NULL,
             file_hash TEXT NOT NULL,
             created TEXT NOT NULL,
-            modified TEXT NOT NULL
+            modified TEXT NOT NULL,
+            trusted INTEGER NOT NULL DEFAULT 0,
+            pinned INTEGER NOT NULL DEFAULT 0,
+            slot INTEGER
         );
 
         CREATE VIRTUAL TABLE IF NOT EXISTS items_fts USING fts5(
@@ -50,11 +53,164 @@ pub fn initialize_db(conn: &Connection) -> Result<()> {
             key TEXT PRIMARY KEY,
             value TEXT NOT NULL
         );
+
+        CREATE TABLE IF NOT EXISTS runs (
+            id TEXT PRIMARY KEY,
+            item_id TEXT NOT NULL,
+            exit_code INTEGER,
+            timed_out INTEGER NOT NULL DEFAULT 0,
+            duration_ms INTEGER NOT NULL,
+            started TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS runs_item_idx ON runs(item_id, started);
+
+        CREATE TABLE IF NOT EXISTS usage (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            item_id TEXT NOT NULL,
+            action TEXT NOT NULL,
+            used_at TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS usage_item_idx ON usage(item_id, used_at);
+
+        CREATE TABLE IF NOT EXISTS ai_usage (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            operation TEXT NOT NULL,
+            provider TEXT NOT NULL,
+            model TEXT NOT NULL,
+            input_tokens INTEGER NOT NULL,
+            output_tokens INTEGER NOT NULL,
+            cost_usd REAL NOT NULL,
+            created TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS ai_usage_created_idx ON ai_usage(created);
+
+        CREATE TABLE IF NOT EXISTS ai_cache (
+            key TEXT PRIMARY KEY,
+            kind TEXT NOT NULL,
+            value TEXT NOT NULL,
+            created TEXT NOT NULL
+        );
+
+        -- One pending enrichment job per item; generation changes on every enqueue so a
+        -- worker only removes the job it actually processed
+        CREATE TABLE IF NOT EXISTS ai_jobs (
+            item_id TEXT PRIMARY KEY,
+            fields TEXT NOT NULL,
+            status TEXT NOT NULL DEFAULT 'pending',
+            generation INTEGER NOT NULL DEFAULT 0,
+            attempts INTEGER NOT NULL DEFAULT 0,
+            created TEXT NOT NULL,
+            updated TEXT NOT NULL
+        );
+
+        -- What the redaction pass masked from outbound AI requests; the secret itself
+        -- is never stored, only a short hash to tell repeats apart
+        CREATE TABLE IF NOT EXISTS redaction_log (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            provider TEXT NOT NULL,
+            rule TEXT NOT NULL,
+            fingerprint TEXT NOT NULL,
+            created TEXT NOT NULL
+        );
+
+        -- Credential-looking values found in item content by the secret scanner
+        CREATE TABLE IF NOT EXISTS secret_findings (
+            item_id TEXT NOT NULL,
+            rule TEXT NOT NULL,
+            line INTEGER NOT NULL,
+            fingerprint TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS secret_findings_item_idx ON secret_findings(item_id);
+
+        -- [[Target]] links between items; target_id is NULL while nothing matches
+        -- the target by id or title
+        CREATE TABLE IF NOT EXISTS links (
+            source_id TEXT NOT NULL,
+            target TEXT NOT NULL,
+            target_id TEXT,
+            PRIMARY KEY (source_id, target)
+        );
+
+        CREATE INDEX IF NOT EXISTS links_target_idx ON links(target_id);
+
+        -- Unit-length term-frequency vectors of each item's text, and the file hash
+        -- they were computed from
+        CREATE TABLE IF NOT EXISTS item_terms (
+            item_id TEXT NOT NULL,
+            term TEXT NOT NULL,
+            weight REAL NOT NULL,
+            PRIMARY KEY (item_id, term)
+        );
+
+        CREATE INDEX IF NOT EXISTS item_terms_term_idx ON item_terms(term);
+
+        CREATE TABLE IF NOT EXISTS term_vectors (
+            item_id TEXT PRIMARY KEY,
+            file_hash TEXT NOT NULL
+        );
+
+        -- Pairs of related items with their combined, content and shared-tag
+        -- scores; each pair is stored in both directions
+        CREATE TABLE IF NOT EXISTS related_items (
+            item_id TEXT NOT NULL,
+            related_id TEXT NOT NULL,
+            score REAL NOT NULL,
+            text_score REAL NOT NULL,
+            tag_score REAL NOT NULL,
+            PRIMARY KEY (item_id, related_id)
+        );
+
+        -- Frecency over the 10 most recent uses of each item, weighted by age in days
+        DROP VIEW IF EXISTS item_frecency;
+        CREATE VIEW item_frecency AS
+        SELECT item_id,
+               SUM(CASE
+                   WHEN age < 4 THEN 100
+                   WHEN age < 14 THEN 70
+                   WHEN age < 31 THEN 50
+                   WHEN age < 90 THEN 30
+                   ELSE 10
+               END) AS score
+        FROM (
+            SELECT item_id,
+                   julianday('now') - julianday(used_at) AS age,
+                   ROW_NUMBER() OVER (PARTITION BY item_id ORDER BY used_at DESC) AS rn
+            FROM usage
+        )
+        WHERE rn <= 10
+        GROUP BY item_id;
         ",
     )?;
 
+    // Columns added after the initial schema; older databases need them appended
+    ensure_column(conn, "items", "trusted", "INTEGER NOT NULL DEFAULT 0")?;
+    ensure_column(conn, "items", "pinned", "INTEGER NOT NULL DEFAULT 0")?;
+    ensure_column(conn, "items", "slot", "INTEGER")?;
+    ensure_column(conn, "items", "ai_opt_out", "INTEGER NOT NULL DEFAULT 0")?;
+    ensure_column(conn, "items", "encrypted", "INTEGER NOT NULL DEFAULT 0")?;
+    ensure_column(conn, "items", "ciphertext", "TEXT NOT NULL DEFAULT ''")?;
+
     // Rebuild FTS index from content table on startup
     conn.execute_batch("INSERT INTO items_fts(items_fts) VALUES('rebuild');")?;
 
     Ok(())
 }
+
+fn ensure_column(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(|r| r.ok())
+        .any(|name| name == column);
+    if !exists {
+        conn.execute_batch(&format!(
+            "ALTER TABLE {} ADD COLUMN {} {}",
+            table, column, definition
+        ))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use super::queries;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+const SETTING_KEY: &str = "tag_synonyms";
+
+/// Alternative tag names and the tag each stands for, e.g. `k8s → kubernetes`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TagSynonyms(BTreeMap<String, String>);
+
+impl TagSynonyms {
+    /// The tag `tag` stands for. Aliases are matched ignoring case; any other tag
+    /// is returned as written.
+    pub fn canonical(&self, tag: &str) -> String {
+        let tag = tag.trim();
+        self.0
+            .get(&tag.to_lowercase())
+            .cloned()
+            .unwrap_or_else(|| tag.to_string())
+    }
+
+    /// `tags` with aliases replaced, keeping the first copy of any repeats
+    /// (compared ignoring case).
+    pub fn normalize(&self, tags: &[String]) -> Vec<String> {
+        let mut normalized: Vec<String> = Vec::with_capacity(tags.len());
+        for tag in tags {
+            let tag = self.canonical(tag);
+            if !tag.is_empty() && !normalized.iter().any(|t| same_tag(t, &tag)) {
+                normalized.push(tag);
+            }
+        }
+        normalized
+    }
+
+    /// Every name for the tag `word` stands for: the canonical tag and its aliases,
+    /// or just `word` if it isn't part of any synonym.
+    pub fn expand(&self, word: &str) -> Vec<String> {
+        let canonical = self.canonical(word);
+        let mut names = vec![word.to_string()];
+        for name in std::iter::once(&canonical).chain(
+            self.0
+                .iter()
+                .filter(|(_, c)| same_tag(c, &canonical))
+                .map(|(alias, _)| alias),
+        ) {
+            if !names.iter().any(|n| same_tag(n, name)) {
+                names.push(name.clone());
+            }
+        }
+        names
+    }
+
+    /// Trim names and lowercase aliases (canonical tags keep their case), and check
+    /// no alias is also a canonical tag, so a tag resolves in one step.
+    pub fn validated(self) -> Result<Self, String> {
+        let mut map = BTreeMap::new();
+        for (alias, canonical) in self.0 {
+            let alias = alias.trim().to_lowercase();
+            let canonical = canonical.trim().to_string();
+            if alias.is_empty() || canonical.is_empty() {
+                return Err("Synonyms can't be empty".to_string());
+            }
+            if alias.contains(',') || canonical.contains(',') {
+                return Err("Tags can't contain commas".to_string());
+            }
+            if same_tag(&alias, &canonical) {
+                return Err(format!("\"{}\" can't be a synonym of itself", alias));
+            }
+            if map.insert(alias.clone(), canonical).is_some() {
+                return Err(format!("Duplicate synonym: {}", alias));
+            }
+        }
+        if let Some((alias, canonical)) = map
+            .iter()
+            .find(|(_, c)| map.contains_key(&c.to_lowercase()))
+        {
+            return Err(format!(
+                "\"{}\" maps to \"{}\", which is itself a synonym of \"{}\"",
+                alias,
+                canonical,
+                map[&canonical.to_lowercase()]
+            ));
+        }
+        Ok(Self(map))
+    }
+}
+
+/// Whether two tag names are the same tag. Tags keep the case they were written
+/// in, but `Docker` and `docker` are not different tags.
+pub fn same_tag(a: &str, b: &str) -> bool {
+    a.to_lowercase() == b.to_lowercase()
+}
+
+/// The saved synonyms, or none if none are saved or they can't be read.
+pub fn load(conn: &Connection) -> TagSynonyms {
+    match queries::get_setting(conn, SETTING_KEY) {
+        Ok(Some(json)) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            eprintln!("Invalid saved tag synonyms, ignoring them: {}", e);
+            TagSynonyms::default()
+        }),
+        Ok(None) => TagSynonyms::default(),
+        Err(e) => {
+            eprintln!("Failed to load tag synonyms: {}", e);
+            TagSynonyms::default()
+        }
+    }
+}
+
+pub fn save(conn: &Connection, synonyms: &TagSynonyms) -> Result<(), String> {
+    let json = serde_json::to_string(synonyms).map_err(|e| e.to_string())?;
+    queries::set_setting(conn, SETTING_KEY, &json).map_err(|e| e.to_string())
+}
@@ -0,0 +1,178 @@
+use super::queries;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+
+const SETTING_KEY: &str = "vocabulary";
+
+/// `note` is what new items default to and what enrichment treats as "unclassified",
+/// so it can be restyled but not removed.
+pub const FALLBACK_TYPE: &str = "note";
+
+pub const BUILTIN_LANGUAGES: &[&str] = &[
+    "bash",
+    "javascript",
+    "typescript",
+    "python",
+    "rust",
+    "go",
+    "json",
+    "yaml",
+    "toml",
+    "sql",
+    "html",
+    "css",
+    "markdown",
+    "dockerfile",
+    "terraform",
+    "other",
+];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ItemTypeDef {
+    pub name: String,
+    pub label: String,
+    pub color: String,
+    #[serde(default)]
+    pub default_language: String,
+    /// Tabler icon name, e.g. "terminal".
+    #[serde(default)]
+    pub icon: String,
+}
+
+/// The item types and languages items can be classified with.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Vocabulary {
+    pub types: Vec<ItemTypeDef>,
+    pub languages: Vec<String>,
+}
+
+fn type_def(
+    name: &str,
+    label: &str,
+    color: &str,
+    default_language: &str,
+    icon: &str,
+) -> ItemTypeDef {
+    ItemTypeDef {
+        name: name.to_string(),
+        label: label.to_string(),
+        color: color.to_string(),
+        default_language: default_language.to_string(),
+        icon: icon.to_string(),
+    }
+}
+
+impl Default for Vocabulary {
+    fn default() -> Self {
+        Self {
+            types: vec![
+                type_def("note", "Note", "#22d3ee", "", "file-text"),
+                type_def("shell", "Shell Command", "#6366f1", "bash", "terminal"),
+                type_def("snippet", "Code Snippet", "#8b5cf6", "", "code"),
+                type_def("config", "Configuration", "#7c3aed", "", "settings"),
+            ],
+            languages: BUILTIN_LANGUAGES.iter().map(|l| l.to_string()).collect(),
+        }
+    }
+}
+
+impl Vocabulary {
+    pub fn has_type(&self, name: &str) -> bool {
+        self.types.iter().any(|t| t.name == name)
+    }
+
+    pub fn has_language(&self, name: &str) -> bool {
+        self.languages.iter().any(|l| l == name)
+    }
+
+    pub fn get_type(&self, name: &str) -> Option<&ItemTypeDef> {
+        self.types.iter().find(|t| t.name == name)
+    }
+
+    /// Trim and lowercase names, and check the vocabulary is usable.
+    pub fn validated(mut self) -> Result<Self, String> {
+        for language in &mut self.languages {
+            *language = language.trim().to_lowercase();
+            if !is_valid_name(language) {
+                return Err(format!("Invalid language name: \"{}\"", language));
+            }
+        }
+        if let Some(dup) = first_duplicate(self.languages.iter().map(String::as_str)) {
+            return Err(format!("Duplicate language: {}", dup));
+        }
+
+        for def in &mut self.types {
+            def.name = def.name.trim().to_lowercase();
+            def.label = def.label.trim().to_string();
+            def.default_language = def.default_language.trim().to_lowercase();
+            def.icon = def.icon.trim().to_string();
+            if !is_valid_name(&def.name) {
+                return Err(format!("Invalid type name: \"{}\"", def.name));
+            }
+            if def.label.is_empty() {
+                def.label = def.name.clone();
+            }
+            if !is_hex_color(&def.color) {
+                return Err(format!(
+                    "Invalid color for type {}: \"{}\". Use #rrggbb",
+                    def.name, def.color
+                ));
+            }
+            if !def.default_language.is_empty() && !self.languages.contains(&def.default_language) {
+                return Err(format!(
+                    "Default language \"{}\" for type {} is not in the language list",
+                    def.default_language, def.name
+                ));
+            }
+        }
+        if let Some(dup) = first_duplicate(self.types.iter().map(|t| t.name.as_str())) {
+            return Err(format!("Duplicate type: {}", dup));
+        }
+        if !self.has_type(FALLBACK_TYPE) {
+            return Err(format!("The \"{}\" type cannot be removed", FALLBACK_TYPE));
+        }
+
+        Ok(self)
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 32
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '+' || c == '#')
+}
+
+fn is_hex_color(color: &str) -> bool {
+    color.len() == 7 && color.starts_with('#') && color[1..].chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn first_duplicate<'a>(names: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let mut seen = std::collections::HashSet::new();
+    names.into_iter().find(|n| !seen.insert(*n))
+}
+
+/// The saved vocabulary, or the built-in one if none is saved or it can't be read.
+pub fn load(conn: &Connection) -> Vocabulary {
+    match queries::get_setting(conn, SETTING_KEY) {
+        Ok(Some(json)) => serde_json::from_str(&json).unwrap_or_else(|e| {
+            eprintln!("Invalid saved vocabulary, using defaults: {}", e);
+            Vocabulary::default()
+        }),
+        Ok(None) => Vocabulary::default(),
+        Err(e) => {
+            eprintln!("Failed to load vocabulary: {}", e);
+            Vocabulary::default()
+        }
+    }
+}
+
+pub fn save(conn: &Connection, vocabulary: &Vocabulary) -> Result<(), String> {
+    let json = serde_json::to_string(vocabulary).map_err(|e| e.to_string())?;
+    queries::set_setting(conn, SETTING_KEY, &json).map_err(|e| e.to_string())
+}
+
+pub fn reset(conn: &Connection) -> Result<(), String> {
+    queries::delete_setting(conn, SETTING_KEY).map_err(|e| e.to_string())
+}
@@ -0,0 +1,2 @@
+pub mod placeholders;
+pub mod runner;
@@ -0,0 +1,163 @@
+use crate::ai::redact::SecretMatch;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// A `{{name}}` or `{{name=default}}` marker inside item content.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Placeholder {
+    pub name: String,
+    pub default: Option<String>,
+}
+
+enum Segment<'a> {
+    Text(&'a str),
+    /// A placeholder and the exact text it was written as.
+    Placeholder(Placeholder, &'a str),
+    /// `{{secret:NAME}}`, filled from the keyring rather than asked for.
+    Secret(String, &'a str),
+}
+
+/// List the placeholders in `content`, each name once, in order of first appearance.
+pub fn find_placeholders(content: &str) -> Vec<Placeholder> {
+    let mut found: Vec<Placeholder> = Vec::new();
+    for segment in split(content) {
+        if let Segment::Placeholder(p, _) = segment {
+            if !found.iter().any(|f| f.name == p.name) {
+                found.push(p);
+            }
+        }
+    }
+    found
+}
+
+/// Replace every `{{secret:NAME}}` marker with `resolve(NAME)`, leaving other
+/// placeholders as they are.
+pub fn expand_secrets(
+    content: &str,
+    mut resolve: impl FnMut(&str) -> Result<String, String>,
+) -> Result<String, String> {
+    let mut out = String::with_capacity(content.len());
+    for segment in split(content) {
+        match segment {
+            Segment::Text(text) | Segment::Placeholder(_, text) => out.push_str(text),
+            Segment::Secret(name, _) => out.push_str(&resolve(&name)?),
+        }
+    }
+    Ok(out)
+}
+
+/// Fill in placeholders from `values` (falling back to their defaults) and
+/// secret markers from `resolve` in a single pass over `content`, so text that
+/// came in as a value is never read as a marker.
+pub fn expand(
+    content: &str,
+    values: &HashMap<String, String>,
+    mut resolve: impl FnMut(&str) -> Result<String, String>,
+) -> Result<String, String> {
+    let mut out = String::with_capacity(content.len());
+    for segment in split(content) {
+        match segment {
+            Segment::Text(text) => out.push_str(text),
+            Segment::Placeholder(p, _) => {
+                let value = values
+                    .get(&p.name)
+                    .cloned()
+                    .or(p.default)
+                    .ok_or_else(|| format!("Missing value for placeholder '{}'", p.name))?;
+                out.push_str(&value);
+            }
+            Segment::Secret(name, _) => out.push_str(&resolve(&name)?),
+        }
+    }
+    Ok(out)
+}
+
+/// Byte ranges of the `{{secret:NAME}}` markers in `content`.
+pub fn secret_spans(content: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let mut offset = 0;
+    for segment in split(content) {
+        let (raw, is_secret) = match segment {
+            Segment::Text(raw) | Segment::Placeholder(_, raw) => (raw, false),
+            Segment::Secret(_, raw) => (raw, true),
+        };
+        if is_secret {
+            spans.push((offset, offset + raw.len()));
+        }
+        offset += raw.len();
+    }
+    spans
+}
+
+/// `content` with each of `matches` replaced by a `{{secret:NAME}}` marker.
+pub fn replace_with_secret(content: &str, matches: &[SecretMatch], name: &str) -> String {
+    let marker = format!("{{{{secret:{}}}}}", name);
+    let mut out = String::with_capacity(content.len());
+    let mut last = 0;
+    for m in matches {
+        out.push_str(&content[last..m.start]);
+        out.push_str(&marker);
+        last = m.end;
+    }
+    out.push_str(&content[last..]);
+    out
+}
+
+fn split(content: &str) -> Vec<Segment<'_>> {
+    let mut segments = Vec::new();
+    let mut rest = content;
+
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        let end = match after.find("}}") {
+            Some(end) => end,
+            None => break,
+        };
+
+        let inner = &after[..end];
+        let raw = &rest[start..start + end + 4];
+        let parsed = match inner.trim().strip_prefix("secret:").map(str::trim) {
+            Some(name) => is_valid_name(name).then(|| Segment::Secret(name.to_string(), raw)),
+            None => parse_placeholder(inner).map(|p| Segment::Placeholder(p, raw)),
+        };
+        match parsed {
+            Some(segment) => {
+                segments.push(Segment::Text(&rest[..start]));
+                segments.push(segment);
+            }
+            // Not one of ours (e.g. Helm or GitHub Actions templates), keep verbatim
+            None => segments.push(Segment::Text(&rest[..start + end + 4])),
+        }
+        rest = &after[end + 2..];
+    }
+
+    segments.push(Segment::Text(rest));
+    segments
+}
+
+fn parse_placeholder(inner: &str) -> Option<Placeholder> {
+    let (name, default) = match inner.split_once('=') {
+        Some((name, default)) => (name.trim(), Some(default.to_string())),
+        None => (inner.trim(), None),
+    };
+
+    if !is_valid_name(name) {
+        return None;
+    }
+
+    Some(Placeholder {
+        name: name.to_string(),
+        default,
+    })
+}
+
+/// Placeholder and secret names: a letter or `_`, then letters, digits, `_` or `-`.
+pub fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_alphabetic() || first == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        }
+        _ => false,
+    }
+}
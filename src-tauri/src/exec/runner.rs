@@ -0,0 +1,156 @@
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
+use tokio::task::JoinHandle;
+
+pub const DEFAULT_TIMEOUT_SECS: u64 = 60;
+
+/// How long to keep draining output after the process exits, in case a
+/// backgrounded child is still holding the pipes open.
+const OUTPUT_GRACE: Duration = Duration::from_secs(2);
+
+pub struct RunSpec {
+    pub shell: String,
+    pub cwd: PathBuf,
+    pub timeout: Duration,
+    pub command: String,
+}
+
+pub struct RunOutcome {
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub duration: Duration,
+}
+
+pub fn default_shell() -> String {
+    if cfg!(windows) {
+        return "cmd".to_string();
+    }
+    std::env::var("SHELL")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .unwrap_or_else(|| "/bin/sh".to_string())
+}
+
+fn command_flag(shell: &str) -> &'static str {
+    let name = Path::new(shell)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match name.as_str() {
+        "cmd" => "/C",
+        "powershell" | "pwsh" => "-Command",
+        _ => "-c",
+    }
+}
+
+/// Shell items are often pasted as a fenced block; run only what is inside the fence.
+pub fn strip_code_fence(content: &str) -> &str {
+    let trimmed = content.trim();
+    if !trimmed.starts_with("```") {
+        return trimmed;
+    }
+    let body = match trimmed.find('\n') {
+        Some(i) => &trimmed[i + 1..],
+        None => return "",
+    };
+    body.trim_end().strip_suffix("```").unwrap_or(body).trim()
+}
+
+/// Run `spec.command` through the configured shell. `on_line` is called with
+/// ("stdout" | "stderr", line) for every line as soon as it is read.
+pub async fn run_command<F>(spec: &RunSpec, on_line: F) -> Result<RunOutcome, String>
+where
+    F: Fn(&'static str, String) + Send + Sync + 'static,
+{
+    let mut command = Command::new(&spec.shell);
+    command
+        .arg(command_flag(&spec.shell))
+        .arg(&spec.command)
+        .current_dir(&spec.cwd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    // Its own process group, so a timeout can kill everything the command started
+    #[cfg(unix)]
+    command.process_group(0);
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to start {}: {}", spec.shell, e))?;
+
+    let started = Instant::now();
+    let on_line = Arc::new(on_line);
+    let mut readers = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        readers.push(spawn_reader(stdout, "stdout", on_line.clone()));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        readers.push(spawn_reader(stderr, "stderr", on_line.clone()));
+    }
+
+    let (exit_code, timed_out) = match tokio::time::timeout(spec.timeout, child.wait()).await {
+        Ok(status) => {
+            let status = status.map_err(|e| format!("Failed to wait for command: {}", e))?;
+            (status.code(), false)
+        }
+        Err(_) => {
+            kill_tree(&mut child).await;
+            (None, true)
+        }
+    };
+    let duration = started.elapsed();
+
+    for mut reader in readers {
+        if timed_out
+            || tokio::time::timeout(OUTPUT_GRACE, &mut reader)
+                .await
+                .is_err()
+        {
+            reader.abort();
+        }
+    }
+
+    Ok(RunOutcome {
+        exit_code,
+        timed_out,
+        duration,
+    })
+}
+
+/// Kill the shell and everything it started. Killing only the shell would leave
+/// pipelines, `&` jobs and commands like `sleep` running with the pipes open.
+async fn kill_tree(child: &mut Child) {
+    if let Some(pid) = child.id() {
+        #[cfg(unix)]
+        let killed = Command::new("kill")
+            .args(["-KILL", "--", &format!("-{}", pid)])
+            .status()
+            .await;
+        #[cfg(windows)]
+        let killed = Command::new("taskkill")
+            .args(["/T", "/F", "/PID", &pid.to_string()])
+            .status()
+            .await;
+        if let Err(e) = killed {
+            eprintln!("Failed to kill process tree {}: {}", pid, e);
+        }
+    }
+    let _ = child.kill().await;
+}
+
+fn spawn_reader<R, F>(reader: R, stream: &'static str, on_line: Arc<F>) -> JoinHandle<()>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+    F: Fn(&'static str, String) + Send + Sync + 'static,
+{
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            on_line(stream, line);
+        }
+    })
+}
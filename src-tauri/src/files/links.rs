@@ -0,0 +1,165 @@
+use crate::db::queries::{self, Item};
+use crate::files::sync;
+use chrono::Utc;
+use rusqlite::Connection;
+use std::ops::Range;
+use std::path::Path;
+
+/// Byte ranges of the targets of `[[Target]]` and `[[Target|label]]` links.
+/// Fenced code blocks and inline code are skipped, and a target may not start or
+/// end with whitespace, so shell tests like `[[ -f file ]]` aren't taken as links.
+fn target_spans(content: &str) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut in_fence = false;
+    let mut offset = 0;
+
+    for line in content.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        let mut in_code = false;
+        let mut i = 0;
+        while i < line.len() {
+            let rest = &line[i..];
+            if rest.starts_with('`') {
+                in_code = !in_code;
+                i += 1;
+                continue;
+            }
+            if !in_code && rest.starts_with("[[") {
+                if let Some(end) = rest[2..].find("]]") {
+                    let inner = &rest[2..2 + end];
+                    let target = inner.split('|').next().unwrap_or("");
+                    if is_valid_target(target) {
+                        spans.push(start + i + 2..start + i + 2 + target.len());
+                    }
+                    i += end + 4;
+                    continue;
+                }
+            }
+            i += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+    spans
+}
+
+fn is_valid_target(target: &str) -> bool {
+    !target.is_empty() && target.trim() == target && !target.contains(['[', ']', '\n'])
+}
+
+/// Link targets in `content`, in order of first appearance and without repeats.
+pub fn parse_links(content: &str) -> Vec<String> {
+    let mut targets: Vec<String> = Vec::new();
+    for span in target_spans(content) {
+        let target = &content[span];
+        if !targets.iter().any(|t| t == target) {
+            targets.push(target.to_string());
+        }
+    }
+    targets
+}
+
+/// Point links at `old_title` to `new_title`, keeping their labels. Returns `None`
+/// when nothing changed.
+pub fn rewrite_links(content: &str, old_title: &str, new_title: &str) -> Option<String> {
+    let old = old_title.to_lowercase();
+    let spans: Vec<Range<usize>> = target_spans(content)
+        .into_iter()
+        .filter(|span| content[span.clone()].to_lowercase() == old)
+        .collect();
+    if spans.is_empty() {
+        return None;
+    }
+
+    let mut rewritten = String::with_capacity(content.len());
+    let mut last = 0;
+    for span in spans {
+        rewritten.push_str(&content[last..span.start]);
+        rewritten.push_str(new_title);
+        last = span.end;
+    }
+    rewritten.push_str(&content[last..]);
+    Some(rewritten)
+}
+
+/// Re-index the links in `item` and resolve any dangling links its id or title
+/// now satisfies. Encrypted items have no readable links.
+pub fn update(conn: &Connection, item: &Item) {
+    let targets = if item.encrypted {
+        Vec::new()
+    } else {
+        parse_links(&item.content)
+    };
+    if let Err(e) = queries::replace_links(conn, &item.id, &targets)
+        .and_then(|_| queries::resolve_dangling_links(conn))
+    {
+        eprintln!("Failed to index links for {}: {}", item.id, e);
+    }
+}
+
+/// Re-index the links of every item, e.g. on startup.
+pub fn rebuild(conn: &Connection) -> Result<(), String> {
+    let items = queries::list_items(conn).map_err(|e| e.to_string())?;
+    for item in &items {
+        let targets = if item.encrypted {
+            Vec::new()
+        } else {
+            parse_links(&item.content)
+        };
+        queries::replace_links(conn, &item.id, &targets).map_err(|e| e.to_string())?;
+    }
+    queries::resolve_dangling_links(conn).map_err(|e| e.to_string())
+}
+
+/// After item `id` is renamed, rewrite `[[old_title]]` links to it in other items
+/// so they keep reading as its title. Returns the ids of the items rewritten.
+pub fn rewrite_backlinks(
+    conn: &Connection,
+    data_dir: &Path,
+    id: &str,
+    old_title: &str,
+    new_title: &str,
+) -> Vec<String> {
+    let backlinks = match queries::get_backlinks(conn, id) {
+        Ok(links) => links,
+        Err(e) => {
+            eprintln!("Failed to load backlinks of {}: {}", id, e);
+            return Vec::new();
+        }
+    };
+
+    let old = old_title.to_lowercase();
+    let mut rewritten = Vec::new();
+    for link in backlinks.iter().filter(|l| l.target.to_lowercase() == old) {
+        let item = match queries::get_item(conn, &link.source_id) {
+            Ok(Some(item)) => item,
+            Ok(None) => continue,
+            Err(e) => {
+                eprintln!("Failed to load {}: {}", link.source_id, e);
+                continue;
+            }
+        };
+        let Some(content) = rewrite_links(&item.content, old_title, new_title) else {
+            continue;
+        };
+        let item = Item {
+            content,
+            modified: Utc::now().to_rfc3339(),
+            ..item
+        };
+        match sync::write_item(conn, data_dir, &item) {
+            Ok(written) => rewritten.push(written.id),
+            Err(e) => eprintln!("Failed to rewrite links in {}: {}", item.id, e),
+        }
+    }
+    rewritten
+}
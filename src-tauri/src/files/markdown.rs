@@ -17,6 +17,20 @@ pub struct Frontmatter {
     pub folder: String,
     #[serde(default)]
     pub description: String,
+    /// Shell items only run when this is explicitly set in the file.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub trusted: bool,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub pinned: bool,
+    /// Quick slot 1-9, copied by its global shortcut.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub slot: Option<u8>,
+    /// `never` keeps the item out of every AI request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ai: Option<String>,
+    /// The body is an encrypted envelope, readable only with the vault passphrase.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub encrypted: bool,
     #[serde(default)]
     pub created: String,
     #[serde(default)]
@@ -31,6 +45,10 @@ fn default_folder() -> String {
     "/".to_string()
 }
 
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
 pub fn parse_markdown_file(path: &Path) -> Result<Item, String> {
     let raw = fs::read_to_string(path).map_err(|e| format!("Failed to read file: {}", e))?;
     let hash = compute_hash(&raw);
@@ -42,6 +60,13 @@ pub fn parse_markdown_content(raw: &str, path: &Path, hash: &str) -> Result<Item
     let fm: Frontmatter =
         serde_yaml::from_str(&frontmatter).map_err(|e| format!("Failed to parse frontmatter: {}", e))?;
 
+    let body = content.trim().to_string();
+    let (content, ciphertext) = if fm.encrypted {
+        (String::new(), body)
+    } else {
+        (body, String::new())
+    };
+
     Ok(Item {
         id: fm.id,
         title: fm.title,
@@ -50,11 +75,17 @@ pub fn parse_markdown_content(raw: &str, path: &Path, hash: &str) -> Result<Item
         tags: fm.tags,
         folder: fm.folder,
         description: fm.description,
-        content: content.trim().to_string(),
+        content,
         file_path: path.to_string_lossy().to_string(),
         file_hash: hash.to_string(),
         created: fm.created,
         modified: fm.modified,
+        trusted: fm.trusted,
+        pinned: fm.pinned,
+        slot: fm.slot.filter(|s| (1..=9).contains(s)),
+        ai_opt_out: fm.ai.is_some_and(|a| a.trim().eq_ignore_ascii_case("never")),
+        encrypted: fm.encrypted,
+        ciphertext,
     })
 }
 
@@ -98,13 +129,23 @@ pub fn item_to_markdown(item: &Item) -> String {
         tags: item.tags.clone(),
         folder: item.folder.clone(),
         description: item.description.clone(),
+        trusted: item.trusted,
+        pinned: item.pinned,
+        slot: item.slot,
+        ai: item.ai_opt_out.then(|| "never".to_string()),
+        encrypted: item.encrypted,
         created: item.created.clone(),
         modified: item.modified.clone(),
     };
 
     let yaml = serde_yaml::to_string(&fm)
         .unwrap_or_else(|_| format!("id: {}\ntitle: {}\n", fm.id, fm.title));
-    format!("---\n{}---\n\n{}\n", yaml, item.content)
+    let body = if item.encrypted {
+        &item.ciphertext
+    } else {
+        &item.content
+    };
+    format!("---\n{}---\n\n{}\n", yaml, body)
 }
 
 pub fn generate_filename(title: &str, id: &str) -> String {
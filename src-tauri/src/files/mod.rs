@@ -1,3 +1,4 @@
+pub mod links;
 pub mod markdown;
 pub mod sync;
 pub mod watcher;
@@ -1,9 +1,94 @@
-use crate::db::queries;
-use crate::files::markdown;
+use crate::ai::redact::Redactor;
+use crate::db::queries::{self, Item};
+use crate::files::{links, markdown};
+use crate::graph::related;
+use crate::secrets::scanner;
 use rusqlite::Connection;
 use std::collections::HashSet;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+
+/// Write an item to its markdown file and index it. The previous file is removed
+/// if a title change moved it. Returns the item with its new path and hash.
+pub fn write_item(conn: &Connection, data_dir: &Path, item: &Item) -> Result<Item, String> {
+    let file_path = markdown::save_item_to_file(data_dir, item)?;
+    let path_str = file_path.to_string_lossy().to_string();
+
+    // Save new file first, then delete old (prevents data loss if save fails)
+    if !item.file_path.is_empty() && item.file_path != path_str {
+        let _ = fs::remove_file(&item.file_path);
+    }
+
+    let raw = fs::read_to_string(&file_path).map_err(|e| e.to_string())?;
+    let written = Item {
+        file_path: path_str,
+        file_hash: markdown::compute_hash(&raw),
+        ..item.clone()
+    };
+    queries::insert_item(conn, &written).map_err(|e| e.to_string())?;
+    scanner::update(conn, &Redactor::load(conn), &written);
+    links::update(conn, &written);
+    related::refresh(conn);
+    Ok(written)
+}
+
+/// Write a batch of metadata changes (tags, folders) as one change. Every file is
+/// written before the index is touched, and the index is updated in a single
+/// transaction. If any file can't be written, or the index update fails, files
+/// already written get their previous contents back and nothing is committed.
+///
+/// Files are never moved between directories: the data directory is flat, since
+/// `reconcile` and the watcher only look at its top level. A folder is the
+/// `folder` field in the frontmatter, so changing it rewrites the file in place.
+pub fn write_items(conn: &Connection, data_dir: &Path, items: &[Item]) -> Result<Vec<Item>, String> {
+    // Each written path with what was there before, for rolling back
+    let mut undo: Vec<(PathBuf, Option<String>)> = Vec::new();
+    let mut written = Vec::with_capacity(items.len());
+
+    let result = (|| {
+        for item in items {
+            let path = markdown::item_file_path(data_dir, &item.title, &item.id);
+            let previous = fs::read_to_string(&path).ok();
+            let raw = markdown::item_to_markdown(item);
+            fs::write(&path, &raw)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            undo.push((path.clone(), previous));
+            written.push(Item {
+                file_path: path.to_string_lossy().to_string(),
+                file_hash: markdown::compute_hash(&raw),
+                ..item.clone()
+            });
+        }
+
+        let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+        for item in &written {
+            queries::insert_item(&tx, item).map_err(|e| e.to_string())?;
+        }
+        tx.commit().map_err(|e| e.to_string())
+    })();
+
+    if let Err(e) = result {
+        for (path, previous) in undo.iter().rev() {
+            let restored = match previous {
+                Some(raw) => fs::write(path, raw),
+                None => fs::remove_file(path),
+            };
+            if let Err(err) = restored {
+                eprintln!("Failed to roll back {}: {}", path.display(), err);
+            }
+        }
+        return Err(e);
+    }
+
+    // Only now drop files a path change left behind
+    for (item, new) in items.iter().zip(&written) {
+        if !item.file_path.is_empty() && item.file_path != new.file_path {
+            let _ = fs::remove_file(&item.file_path);
+        }
+    }
+    related::refresh(conn);
+    Ok(written)
+}
 
 /// Full reconciliation: scan all .md files in data_dir and sync with DB.
 /// Returns (added, updated, removed) counts.
@@ -13,6 +98,7 @@ pub fn reconcile(conn: &Connection, data_dir: &Path) -> Result<(usize, usize, us
     let mut removed = 0;
 
     let mut seen_paths = HashSet::new();
+    let redactor = Redactor::load(conn);
 
     // Scan all .md files in data directory
     let entries = fs::read_dir(data_dir).map_err(|e| format!("Failed to read data dir: {}", e))?;
@@ -42,6 +128,7 @@ pub fn reconcile(conn: &Connection, data_dir: &Path) -> Result<(usize, usize, us
                             if let Err(e) = queries::insert_item(conn, &item) {
                                 eprintln!("Reconcile: failed to update DB for {}: {}", path.display(), e);
                             } else {
+                                scanner::update(conn, &redactor, &item);
                                 updated += 1;
                             }
                         }
@@ -55,6 +142,7 @@ pub fn reconcile(conn: &Connection, data_dir: &Path) -> Result<(usize, usize, us
                             if let Err(e) = queries::insert_item(conn, &item) {
                                 eprintln!("Reconcile: failed to insert DB for {}: {}", path.display(), e);
                             } else {
+                                scanner::update(conn, &redactor, &item);
                                 added += 1;
                             }
                         }
@@ -87,6 +175,11 @@ pub fn reconcile(conn: &Connection, data_dir: &Path) -> Result<(usize, usize, us
         }
     }
 
+    if let Err(e) = links::rebuild(conn) {
+        eprintln!("Reconcile: failed to rebuild links: {}", e);
+    }
+    related::refresh(conn);
+
     Ok((added, updated, removed))
 }
 
@@ -96,11 +189,15 @@ pub fn process_changes(
     changed: &[std::path::PathBuf],
     removed: &[std::path::PathBuf],
 ) {
+    let redactor = Redactor::load(conn);
     for path in changed {
         match markdown::parse_markdown_file(path) {
             Ok(item) => {
                 if let Err(e) = queries::insert_item(conn, &item) {
                     eprintln!("Watcher: failed to sync {} to DB: {}", path.display(), e);
+                } else {
+                    scanner::update(conn, &redactor, &item);
+                    links::update(conn, &item);
                 }
             }
             Err(e) => eprintln!("Watcher: failed to parse {}: {}", path.display(), e),
@@ -113,4 +210,5 @@ pub fn process_changes(
             eprintln!("Watcher: failed to remove {} from DB: {}", path.display(), e);
         }
     }
+    related::refresh(conn);
 }
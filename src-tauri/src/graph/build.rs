@@ -0,0 +1,148 @@
+use crate::db::queries::{self, Item};
+use crate::graph::related::MIN_SCORE;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Weight of an edge backed by an explicit `[[link]]`, whatever its other scores.
+const LINK_WEIGHT: f64 = 1.0;
+
+/// Which items to include. Every field narrows the set; an empty filter is the
+/// whole knowledge base.
+#[derive(Debug, Default, Deserialize)]
+pub struct GraphFilter {
+    /// Only these items, e.g. the ones the list is showing.
+    pub ids: Option<Vec<String>>,
+    #[serde(rename = "type")]
+    pub item_type: Option<String>,
+    /// A folder and its subfolders.
+    pub folder: Option<String>,
+    pub tag: Option<String>,
+    /// Drop similarity edges weaker than this. Links are always kept.
+    pub min_weight: Option<f64>,
+}
+
+impl GraphFilter {
+    fn matches(&self, item: &Item, ids: Option<&HashSet<&str>>) -> bool {
+        ids.is_none_or(|ids| ids.contains(item.id.as_str()))
+            && self.item_type.as_ref().is_none_or(|t| &item.item_type == t)
+            && self.folder.as_ref().is_none_or(|f| {
+                let f = f.trim_end_matches('/');
+                f.is_empty() || item.folder == f || item.folder.starts_with(&format!("{}/", f))
+            })
+            && self.tag.as_ref().is_none_or(|t| item.tags.contains(t))
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphNode {
+    pub id: String,
+    pub title: String,
+    #[serde(rename = "type")]
+    pub item_type: String,
+    pub folder: String,
+    pub tags: Vec<String>,
+    /// Sum of the weights of the node's edges.
+    pub weight: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+    pub weight: f64,
+    /// One of the items links to the other.
+    pub linked: bool,
+    pub text_score: f64,
+    pub tag_score: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Graph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+}
+
+/// The item graph from stored link and related-item scores. Nothing is scored
+/// here; call `related::refresh` first if items may have changed.
+pub fn build(conn: &Connection, filter: &GraphFilter) -> Result<Graph, String> {
+    let ids: Option<HashSet<&str>> = filter
+        .ids
+        .as_ref()
+        .map(|ids| ids.iter().map(String::as_str).collect());
+    let items: Vec<Item> = queries::list_items(conn)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|item| filter.matches(item, ids.as_ref()))
+        .collect();
+    let included: HashSet<&str> = items.iter().map(|i| i.id.as_str()).collect();
+
+    let min_weight = filter.min_weight.unwrap_or(MIN_SCORE);
+    let mut edges: HashMap<(String, String), GraphEdge> = HashMap::new();
+    let key = |a: &str, b: &str| {
+        if a < b {
+            (a.to_string(), b.to_string())
+        } else {
+            (b.to_string(), a.to_string())
+        }
+    };
+
+    for (item_id, related) in
+        queries::list_related_pairs(conn, min_weight).map_err(|e| e.to_string())?
+    {
+        if included.contains(item_id.as_str()) && included.contains(related.related_id.as_str()) {
+            edges.insert(
+                key(&item_id, &related.related_id),
+                GraphEdge {
+                    source: item_id,
+                    target: related.related_id,
+                    weight: related.score,
+                    linked: false,
+                    text_score: related.text_score,
+                    tag_score: related.tag_score,
+                },
+            );
+        }
+    }
+    for (source, target) in queries::list_resolved_links(conn).map_err(|e| e.to_string())? {
+        if !included.contains(source.as_str()) || !included.contains(target.as_str()) {
+            continue;
+        }
+        let edge = edges
+            .entry(key(&source, &target))
+            .or_insert_with(|| GraphEdge {
+                source: source.clone(),
+                target: target.clone(),
+                weight: 0.0,
+                linked: false,
+                text_score: 0.0,
+                tag_score: 0.0,
+            });
+        // Point the edge the way the link goes
+        edge.source = source;
+        edge.target = target;
+        edge.linked = true;
+        edge.weight = LINK_WEIGHT;
+    }
+
+    let mut degree: HashMap<&str, f64> = HashMap::new();
+    for edge in edges.values() {
+        *degree.entry(edge.source.as_str()).or_default() += edge.weight;
+        *degree.entry(edge.target.as_str()).or_default() += edge.weight;
+    }
+    let nodes = items
+        .iter()
+        .map(|item| GraphNode {
+            id: item.id.clone(),
+            title: item.title.clone(),
+            item_type: item.item_type.clone(),
+            folder: item.folder.clone(),
+            tags: item.tags.clone(),
+            weight: degree.get(item.id.as_str()).copied().unwrap_or(0.0),
+        })
+        .collect();
+
+    let mut edges: Vec<GraphEdge> = edges.into_values().collect();
+    edges.sort_by(|a, b| (&a.source, &a.target).cmp(&(&b.source, &b.target)));
+    Ok(Graph { nodes, edges })
+}
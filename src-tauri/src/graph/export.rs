@@ -0,0 +1,210 @@
+use crate::graph::build::{Graph, GraphEdge};
+use serde::Deserialize;
+use serde_json::{json, Map, Value};
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    /// GraphML, for Gephi, yEd and NetworkX.
+    Graphml,
+    /// Graphviz DOT.
+    Dot,
+    /// JSON Graph Format (jsongraphformat.info, version 2).
+    Json,
+}
+
+pub fn export(graph: &Graph, format: GraphFormat) -> Result<String, String> {
+    match format {
+        GraphFormat::Graphml => Ok(to_graphml(graph)),
+        GraphFormat::Dot => Ok(to_dot(graph)),
+        GraphFormat::Json => {
+            serde_json::to_string_pretty(&to_json_graph(graph)).map_err(|e| e.to_string())
+        }
+    }
+}
+
+/// What an edge stands for: an explicit link, or relatedness by content and tags.
+fn relation(edge: &GraphEdge) -> &'static str {
+    if edge.linked {
+        "link"
+    } else if edge.text_score == 0.0 {
+        "shared_tags"
+    } else {
+        "similar"
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Not allowed in XML 1.0 at all
+            c if (c as u32) < 0x20 && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+const GRAPHML_KEYS: &[(&str, &str, &str, &str)] = &[
+    ("label", "node", "label", "string"),
+    ("type", "node", "type", "string"),
+    ("folder", "node", "folder", "string"),
+    ("tags", "node", "tags", "string"),
+    ("node_weight", "node", "weight", "double"),
+    ("weight", "edge", "weight", "double"),
+    ("relation", "edge", "relation", "string"),
+    ("linked", "edge", "linked", "boolean"),
+    ("text_score", "edge", "text_score", "double"),
+    ("tag_score", "edge", "tag_score", "double"),
+];
+
+fn to_graphml(graph: &Graph) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\" \
+         xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" \
+         xsi:schemaLocation=\"http://graphml.graphdrawing.org/xmlns http://graphml.graphdrawing.org/xmlns/1.0/graphml.xsd\">\n",
+    );
+    for (id, target, name, kind) in GRAPHML_KEYS {
+        out.push_str(&format!(
+            "  <key id=\"{}\" for=\"{}\" attr.name=\"{}\" attr.type=\"{}\"/>\n",
+            id, target, name, kind
+        ));
+    }
+    // Links have a direction and similarity doesn't, so each edge says which it is
+    out.push_str("  <graph id=\"lynxnote\" edgedefault=\"undirected\">\n");
+    for node in &graph.nodes {
+        out.push_str(&format!("    <node id=\"{}\">\n", xml_escape(&node.id)));
+        for (key, value) in [
+            ("label", xml_escape(&node.title)),
+            ("type", xml_escape(&node.item_type)),
+            ("folder", xml_escape(&node.folder)),
+            ("tags", xml_escape(&node.tags.join(","))),
+            ("node_weight", node.weight.to_string()),
+        ] {
+            out.push_str(&format!("      <data key=\"{}\">{}</data>\n", key, value));
+        }
+        out.push_str("    </node>\n");
+    }
+    for (i, edge) in graph.edges.iter().enumerate() {
+        out.push_str(&format!(
+            "    <edge id=\"e{}\" source=\"{}\" target=\"{}\" directed=\"{}\">\n",
+            i,
+            xml_escape(&edge.source),
+            xml_escape(&edge.target),
+            edge.linked
+        ));
+        for (key, value) in [
+            ("weight", edge.weight.to_string()),
+            ("relation", relation(edge).to_string()),
+            ("linked", edge.linked.to_string()),
+            ("text_score", edge.text_score.to_string()),
+            ("tag_score", edge.tag_score.to_string()),
+        ] {
+            out.push_str(&format!("      <data key=\"{}\">{}</data>\n", key, value));
+        }
+        out.push_str("    </edge>\n");
+    }
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+/// A DOT double-quoted string.
+fn dot_quote(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\r', "");
+    format!("\"{}\"", escaped)
+}
+
+fn to_dot(graph: &Graph) -> String {
+    let mut out = String::from("graph lynxnote {\n  node [shape=box, style=rounded];\n");
+    for node in &graph.nodes {
+        out.push_str(&format!(
+            "  {} [label={}, type={}, folder={}, tags={}, weight={}];\n",
+            dot_quote(&node.id),
+            dot_quote(&node.title),
+            dot_quote(&node.item_type),
+            dot_quote(&node.folder),
+            dot_quote(&node.tags.join(",")),
+            node.weight
+        ));
+    }
+    for edge in &graph.edges {
+        // An undirected graph can still draw an arrowhead for a link
+        let style = if edge.linked {
+            "dir=forward, style=solid"
+        } else {
+            "style=dashed"
+        };
+        out.push_str(&format!(
+            "  {} -- {} [score={}, penwidth={:.2}, relation={}, text_score={}, tag_score={}, {}];\n",
+            dot_quote(&edge.source),
+            dot_quote(&edge.target),
+            edge.weight,
+            1.0 + edge.weight * 3.0,
+            relation(edge),
+            edge.text_score,
+            edge.tag_score,
+            style
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn to_json_graph(graph: &Graph) -> Value {
+    let nodes: Map<String, Value> = graph
+        .nodes
+        .iter()
+        .map(|node| {
+            (
+                node.id.clone(),
+                json!({
+                    "label": node.title,
+                    "metadata": {
+                        "type": node.item_type,
+                        "folder": node.folder,
+                        "tags": node.tags,
+                        "weight": node.weight,
+                    }
+                }),
+            )
+        })
+        .collect();
+    let edges: Vec<Value> = graph
+        .edges
+        .iter()
+        .map(|edge| {
+            json!({
+                "source": edge.source,
+                "target": edge.target,
+                "relation": relation(edge),
+                "directed": edge.linked,
+                "metadata": {
+                    "weight": edge.weight,
+                    "linked": edge.linked,
+                    "text_score": edge.text_score,
+                    "tag_score": edge.tag_score,
+                }
+            })
+        })
+        .collect();
+    json!({
+        "graph": {
+            "label": "LynxNote",
+            "type": "lynxnote",
+            "directed": false,
+            "nodes": nodes,
+            "edges": edges,
+        }
+    })
+}
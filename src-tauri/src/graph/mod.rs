@@ -0,0 +1,4 @@
+pub mod build;
+pub mod export;
+pub mod related;
+pub mod terms;
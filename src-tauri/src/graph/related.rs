@@ -0,0 +1,95 @@
+use crate::db::queries::{self, Item, RelatedScore};
+use crate::graph::terms;
+use rusqlite::Connection;
+use std::collections::HashMap;
+
+/// Weight of content similarity in a relation's score; shared tags make up the rest.
+const TEXT_WEIGHT: f64 = 0.6;
+const TAG_WEIGHT: f64 = 0.4;
+/// Each shared tag adds this much to the tag score, up to 1.
+const TAG_STEP: f64 = 0.3;
+/// Pairs scoring below this aren't stored.
+pub const MIN_SCORE: f64 = 0.15;
+
+fn tag_score(shared: usize) -> f64 {
+    (shared as f64 * TAG_STEP).min(1.0)
+}
+
+/// The text an item's term vector is built from. Encrypted bodies are left out.
+fn item_text(item: &Item) -> String {
+    let content = if item.encrypted {
+        ""
+    } else {
+        item.content.as_str()
+    };
+    format!("{} {} {}", item.title, item.description, content)
+}
+
+/// Re-index `item`'s terms and recompute its scores against every other item.
+/// Both directions of each pair are stored, so only this item's rows change.
+fn update(
+    conn: &Connection,
+    item: &Item,
+    all_tags: &[(String, Vec<String>)],
+) -> rusqlite::Result<()> {
+    let vector = terms::term_vector(&item_text(item));
+    queries::replace_item_terms(conn, &item.id, &item.file_hash, &vector)?;
+
+    let mut scores: HashMap<String, RelatedScore> = HashMap::new();
+    for (related_id, similarity) in queries::term_similarities(conn, &item.id)? {
+        scores
+            .entry(related_id.clone())
+            .or_insert_with(|| RelatedScore {
+                related_id,
+                ..RelatedScore::default()
+            })
+            .text_score = similarity.min(1.0);
+    }
+    for (related_id, tags) in all_tags {
+        if *related_id == item.id {
+            continue;
+        }
+        let shared = tags.iter().filter(|t| item.tags.contains(t)).count();
+        if shared > 0 {
+            scores
+                .entry(related_id.clone())
+                .or_insert_with(|| RelatedScore {
+                    related_id: related_id.clone(),
+                    ..RelatedScore::default()
+                })
+                .tag_score = tag_score(shared);
+        }
+    }
+
+    let related: Vec<RelatedScore> = scores
+        .into_values()
+        .map(|mut s| {
+            s.score = (s.text_score * TEXT_WEIGHT + s.tag_score * TAG_WEIGHT).min(1.0);
+            s
+        })
+        .filter(|s| s.score >= MIN_SCORE)
+        .collect();
+    queries::replace_related(conn, &item.id, &related)
+}
+
+/// Bring term vectors and related-item scores up to date with the items table.
+/// Only items whose file hash changed since they were last indexed are
+/// recomputed, so this is cheap to call after every write.
+pub fn refresh(conn: &Connection) {
+    let result = queries::prune_term_vectors(conn).and_then(|_| {
+        let stale = queries::stale_term_items(conn)?;
+        if stale.is_empty() {
+            return Ok(());
+        }
+        // One transaction, so a full rebuild isn't a commit per item
+        let tx = conn.unchecked_transaction()?;
+        let all_tags = queries::list_item_tags(&tx)?;
+        for item in &stale {
+            update(&tx, item, &all_tags)?;
+        }
+        tx.commit()
+    });
+    if let Err(e) = result {
+        eprintln!("Failed to update related items: {}", e);
+    }
+}
@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+
+/// Words too common to tell items apart. Kept in step with the duplicate check
+/// in the frontend (`extract-keywords.ts`).
+const STOP_WORDS: &[&str] = &[
+    "the", "a", "an", "and", "or", "but", "in", "on", "at", "to", "for", "of", "with", "by",
+    "from", "is", "it", "as", "be", "was", "are", "been", "being", "have", "has", "had", "do",
+    "does", "did", "will", "would", "could", "should", "may", "might", "shall", "can", "need",
+    "this", "that", "these", "those", "not", "no", "nor", "so", "if", "then", "than", "too",
+    "very", "just", "about", "above", "after", "again", "all", "also", "any", "because", "before",
+    "between", "both", "each", "few", "get", "got", "here", "how", "into", "its", "let", "more",
+    "most", "new", "now", "only", "other", "our", "out", "over", "own", "same", "she", "some",
+    "such", "them", "there", "they", "through", "under", "until", "use", "used", "using", "what",
+    "when", "where", "which", "while", "who", "why", "you", "your",
+];
+
+/// Significant lowercase words in `text`, repeats included.
+pub fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| w.len() >= 3)
+        .map(|w| w.to_ascii_lowercase())
+        .filter(|w| !STOP_WORDS.contains(&w.as_str()))
+}
+
+/// Sublinear term-frequency weights (1 + ln tf), scaled to unit length so the dot
+/// product of two vectors is their cosine similarity.
+pub fn term_vector(text: &str) -> Vec<(String, f64)> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for word in tokenize(text) {
+        *counts.entry(word).or_default() += 1;
+    }
+    let mut vector: Vec<(String, f64)> = counts
+        .into_iter()
+        .map(|(term, count)| (term, 1.0 + (count as f64).ln()))
+        .collect();
+    let norm = vector.iter().map(|(_, w)| w * w).sum::<f64>().sqrt();
+    if norm > 0.0 {
+        for (_, weight) in &mut vector {
+            *weight /= norm;
+        }
+    }
+    vector.sort_by(|a, b| a.0.cmp(&b.0));
+    vector
+}
@@ -1,20 +1,23 @@
 mod ai;
 mod commands;
 mod db;
+mod exec;
 mod files;
+mod graph;
+mod secrets;
 mod state;
+mod tray;
 
-use commands::{ai as ai_commands, recategorize, search, settings, snippets};
+use commands::{
+    ai as ai_commands, enrichment, favorites, folders, graph as graph_commands, links, prompts,
+    recategorize, run, search, secrets as secret_commands, settings, shortcuts, snippets, tags,
+    usage, vault, vocabulary,
+};
 use rusqlite::Connection;
 use state::AppState;
 use std::fs;
 use std::path::PathBuf;
-use tauri::{
-    menu::{Menu, MenuItem},
-    tray::TrayIconBuilder,
-    Manager, Emitter,
-};
-use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+use tauri::{Emitter, Manager};
 
 fn default_data_dir() -> PathBuf {
     dirs::document_dir()
@@ -48,6 +51,25 @@ pub fn run() {
     let conn = Connection::open(&db_path).expect("Failed to open database");
     db::schema::initialize_db(&conn).expect("Failed to initialize database schema");
 
+    // Secrets go to the OS keyring, or an encrypted file beside the database without one
+    if let Some(app_support) = db_path.parent() {
+        secrets::store::init(app_support);
+    }
+    ai_commands::migrate_api_key(&conn);
+    // Stored findings are only comparable with fingerprints made under the same key
+    match secrets::store::fingerprint_key() {
+        Ok((key, created)) => {
+            ai::redact::init_fingerprint_key(key);
+            if created {
+                secrets::scanner::rescan_all(&conn);
+            }
+        }
+        Err(e) => {
+            eprintln!("Using a one-off key for secret fingerprints: {}", e);
+            secrets::scanner::rescan_all(&conn);
+        }
+    }
+
     let data_dir = resolve_data_dir(&conn);
 
     // Run initial reconciliation
@@ -63,6 +85,11 @@ pub fn run() {
         Err(e) => eprintln!("Reconciliation error: {}", e),
     }
 
+    if let Err(e) = db::queries::prune_usage(&conn) {
+        eprintln!("Failed to prune usage history: {}", e);
+    }
+    ai::cache::prune(&conn);
+
     let app_state = AppState::new(conn, data_dir.clone());
 
     tauri::Builder::default()
@@ -74,34 +101,20 @@ pub fn run() {
         .manage(app_state)
         .setup(move |app| {
             // Set up system tray
-            let quit = MenuItem::with_id(app, "quit", "Quit LynxNote", true, None::<&str>)?;
-            let show = MenuItem::with_id(app, "show", "Show LynxNote", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&show, &quit])?;
-
-            let _tray = TrayIconBuilder::new()
-                .menu(&menu)
-                .tooltip("LynxNote")
-                .on_menu_event(|app, event| match event.id.as_ref() {
-                    "quit" => {
-                        app.exit(0);
-                    }
-                    "show" => {
-                        if let Some(window) = app.get_webview_window("main") {
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                        }
-                    }
-                    _ => {}
-                })
-                .build(app)?;
+            tray::init(app.handle())?;
 
             // Initialize AI provider from saved settings
             let ai_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
-                let state = ai_handle.state::<AppState>();
-                ai_commands::rebuild_ai_provider(&state).await;
+                ai_commands::rebuild_ai_provider(&ai_handle).await;
             });
 
+            // Resume queued enrichment and start processing new jobs
+            enrichment::start(app.handle());
+
+            // Forget the vault passphrase after the idle timeout
+            vault::start_auto_lock(app.handle());
+
             // Set up file watcher
             let app_handle = app.handle().clone();
             let watch_dir = data_dir.clone();
@@ -116,6 +129,7 @@ pub fn run() {
                 };
                 files::sync::process_changes(&db, &changed, &removed);
                 drop(db);
+                let _ = app_handle.emit("items-changed", "watcher");
             }) {
                 Ok(w) => Some(w),
                 Err(e) => {
@@ -124,42 +138,82 @@ pub fn run() {
                 }
             };
 
-            // Set up global shortcut
-            app.global_shortcut().on_shortcut(
-                "CommandOrControl+Shift+Space",
-                move |app: &tauri::AppHandle, _shortcut, event| {
-                    if event.state == ShortcutState::Pressed {
-                        if let Some(window) = app.get_webview_window("search") {
-                            if window.is_visible().unwrap_or(false) {
-                                let _ = window.hide();
-                            } else {
-                                let _ = window.show();
-                                let _ = window.set_focus();
-                                let _ = window.emit("search-focus", ());
-                            }
-                        }
-                    }
-                },
-            )?;
+            // Register global shortcuts from settings
+            shortcuts::register_all(app.handle());
 
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
             snippets::create_item,
+            snippets::create_item_from_clipboard,
             snippets::update_item,
             snippets::delete_item,
             snippets::get_item,
             snippets::list_items,
             snippets::get_all_tags,
             snippets::get_all_folders,
+            tags::rename_tag,
+            tags::merge_tags,
+            tags::delete_tag,
+            tags::get_tag_synonyms,
+            tags::set_tag_synonyms,
+            tags::suggest_tag_merges,
+            folders::rename_folder,
+            folders::merge_folders,
+            folders::delete_folder,
             search::search_items,
+            links::get_outgoing_links,
+            links::get_backlinks,
+            graph_commands::get_related_items,
+            graph_commands::get_graph,
+            graph_commands::export_graph,
+            secret_commands::list_secret_findings,
+            secret_commands::move_secret_to_keyring,
+            secret_commands::expand_secrets,
+            vault::unlock_vault,
+            vault::lock_vault,
+            vault::get_vault_status,
+            vault::set_vault_auto_lock,
             settings::get_setting,
             settings::set_setting,
             settings::get_data_dir,
             settings::set_data_dir,
             ai_commands::get_ai_settings,
             ai_commands::set_ai_settings,
+            ai_commands::clear_ai_cache,
+            ai_commands::test_ai_connection,
+            ai_commands::list_ai_models,
+            ai_commands::get_ai_usage,
+            ai_commands::get_ai_price_table,
+            ai_commands::set_ai_price_table,
+            ai_commands::set_ai_budget,
+            ai_commands::list_redaction_log,
+            ai_commands::clear_redaction_log,
+            ai_commands::get_redaction_patterns,
+            ai_commands::set_redaction_patterns,
+            enrichment::get_ai_queue_status,
+            enrichment::set_ai_queue_settings,
             recategorize::recategorize_all,
+            run::run_item,
+            run::get_item_placeholders,
+            run::get_run_history,
+            usage::record_usage,
+            usage::get_most_used,
+            usage::get_recently_used,
+            favorites::copy_item,
+            favorites::set_pinned,
+            favorites::set_quick_slot,
+            favorites::get_pinned_items,
+            favorites::get_quick_slots,
+            shortcuts::get_shortcuts,
+            shortcuts::set_shortcut,
+            vocabulary::get_vocabulary,
+            vocabulary::set_vocabulary,
+            vocabulary::reset_vocabulary,
+            prompts::list_prompt_templates,
+            prompts::set_prompt_template,
+            prompts::reset_prompt_template,
+            prompts::test_prompt,
         ])
         .on_window_event(|window, event| {
             // Hide search window on blur instead of closing
@@ -0,0 +1,45 @@
+use crate::ai::redact::{self, Redactor};
+use crate::db::queries::{self, Item, SecretFinding};
+use rusqlite::Connection;
+
+/// Credential-looking values in an item's content, using the same rules (and
+/// custom patterns) as AI redaction. Encrypted items are not scanned; their body
+/// never leaves the file in the clear.
+pub fn scan(redactor: &Redactor, item: &Item) -> Vec<SecretFinding> {
+    if item.encrypted {
+        return Vec::new();
+    }
+    redactor
+        .find(&item.content)
+        .iter()
+        .map(|m| SecretFinding {
+            item_id: item.id.clone(),
+            item_title: item.title.clone(),
+            rule: m.rule.clone(),
+            line: item.content[..m.start].matches('\n').count() + 1,
+            fingerprint: redact::fingerprint(m.secret(&item.content)),
+        })
+        .collect()
+}
+
+/// Rescan `item` and replace its stored findings. Returns how many were found.
+pub fn update(conn: &Connection, redactor: &Redactor, item: &Item) -> usize {
+    let findings = scan(redactor, item);
+    if let Err(e) = queries::replace_secret_findings(conn, &item.id, &findings) {
+        eprintln!("Failed to store secret findings for {}: {}", item.id, e);
+    }
+    findings.len()
+}
+
+/// Rescan every item, e.g. after the fingerprint key changed.
+pub fn rescan_all(conn: &Connection) {
+    let redactor = Redactor::load(conn);
+    match queries::list_items(conn) {
+        Ok(items) => {
+            for item in &items {
+                update(conn, &redactor, item);
+            }
+        }
+        Err(e) => eprintln!("Failed to list items to rescan for secrets: {}", e),
+    }
+}
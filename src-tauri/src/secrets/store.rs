@@ -0,0 +1,197 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use keyring::Entry;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+/// Keyring service every LynxNote secret is filed under.
+const SERVICE: &str = "lynxnote";
+
+/// Set to `file` to skip the OS keyring, e.g. on headless machines and in CI.
+const BACKEND_ENV: &str = "LYNXNOTE_SECRET_STORE";
+
+/// Keyring account for the key secret fingerprints are computed with.
+const FINGERPRINT_KEY: &str = "fingerprint-key";
+
+const FALLBACK_FILE: &str = "secrets.enc";
+const FALLBACK_KEY_FILE: &str = "secrets.key";
+const NONCE_LEN: usize = 12;
+
+/// Directory holding the encrypted fallback file, set once at startup.
+static FALLBACK_DIR: OnceLock<PathBuf> = OnceLock::new();
+/// Serializes read-modify-write cycles on the fallback file.
+static FALLBACK_LOCK: Mutex<()> = Mutex::new(());
+
+/// Where the encrypted-file fallback lives. Call before any other function here.
+pub fn init(dir: &Path) {
+    let _ = FALLBACK_DIR.set(dir.to_path_buf());
+}
+
+/// Keyring account for the secret behind `{{secret:NAME}}`.
+pub fn item_secret_key(name: &str) -> String {
+    format!("secret:{}", name)
+}
+
+fn use_keyring() -> bool {
+    std::env::var(BACKEND_ENV).map_or(true, |v| v != "file")
+}
+
+/// The keyring entry for `key`, or `None` when there is no keyring service to talk to.
+fn entry(key: &str) -> Option<Entry> {
+    if !use_keyring() {
+        return None;
+    }
+    Entry::new(SERVICE, key)
+        .map_err(|e| eprintln!("Keyring unavailable, using encrypted file: {}", e))
+        .ok()
+}
+
+/// Errors that mean the keyring can't be used at all, as opposed to a bad request.
+fn is_unavailable(e: &keyring::Error) -> bool {
+    matches!(
+        e,
+        keyring::Error::PlatformFailure(_) | keyring::Error::NoStorageAccess(_)
+    )
+}
+
+pub fn get(key: &str) -> Result<Option<String>, String> {
+    if let Some(entry) = entry(key) {
+        match entry.get_password() {
+            Ok(value) => return Ok(Some(value)),
+            // May still be in the file from a session without a keyring
+            Err(keyring::Error::NoEntry) => {}
+            Err(e) if is_unavailable(&e) => {
+                eprintln!("Keyring unavailable, using encrypted file: {}", e)
+            }
+            Err(e) => return Err(format!("Failed to read {} from the keyring: {}", key, e)),
+        }
+    }
+    Ok(read_fallback()?.remove(key))
+}
+
+pub fn set(key: &str, value: &str) -> Result<(), String> {
+    if let Some(entry) = entry(key) {
+        match entry.set_password(value) {
+            Ok(()) => return remove_fallback(key),
+            Err(e) if is_unavailable(&e) => {
+                eprintln!("Keyring unavailable, using encrypted file: {}", e)
+            }
+            Err(e) => return Err(format!("Failed to save {} to the keyring: {}", key, e)),
+        }
+    }
+    let _guard = FALLBACK_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut secrets = read_fallback()?;
+    secrets.insert(key.to_string(), value.to_string());
+    write_fallback(&secrets)
+}
+
+pub fn delete(key: &str) -> Result<(), String> {
+    if let Some(entry) = entry(key) {
+        match entry.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => {}
+            Err(e) if is_unavailable(&e) => {}
+            Err(e) => return Err(format!("Failed to remove {} from the keyring: {}", key, e)),
+        }
+    }
+    remove_fallback(key)
+}
+
+/// The value for a `{{secret:NAME}}` marker, for filling it in at copy or run time.
+pub fn resolve(name: &str) -> Result<String, String> {
+    get(&item_secret_key(name))?.ok_or_else(|| format!("Secret '{}' is not in the keyring", name))
+}
+
+/// This install's key for fingerprinting secrets, and whether it was just
+/// created (so fingerprints stored before now were made with another key).
+pub fn fingerprint_key() -> Result<(Vec<u8>, bool), String> {
+    if let Some(encoded) = get(FINGERPRINT_KEY)? {
+        let key = BASE64
+            .decode(encoded)
+            .map_err(|e| format!("Fingerprint key is not valid base64: {}", e))?;
+        return Ok((key, false));
+    }
+    let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+    set(FINGERPRINT_KEY, &BASE64.encode(key))?;
+    Ok((key.to_vec(), true))
+}
+
+fn fallback_dir() -> Result<&'static PathBuf, String> {
+    FALLBACK_DIR
+        .get()
+        .ok_or_else(|| "Secret store is not initialized".to_string())
+}
+
+fn remove_fallback(key: &str) -> Result<(), String> {
+    let _guard = FALLBACK_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut secrets = read_fallback()?;
+    if secrets.remove(key).is_some() {
+        write_fallback(&secrets)?;
+    }
+    Ok(())
+}
+
+/// The fallback file's key, created on first use. It stays beside the database,
+/// outside the (possibly synced) notes folder.
+fn fallback_cipher() -> Result<ChaCha20Poly1305, String> {
+    let path = fallback_dir()?.join(FALLBACK_KEY_FILE);
+    let key = match fs::read(&path) {
+        Ok(bytes) if bytes.len() == 32 => *Key::from_slice(&bytes),
+        Ok(_) => return Err(format!("{} is corrupt", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+            write_private(&path, key.as_slice())?;
+            key
+        }
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    Ok(ChaCha20Poly1305::new(&key))
+}
+
+fn read_fallback() -> Result<BTreeMap<String, String>, String> {
+    let path = fallback_dir()?.join(FALLBACK_FILE);
+    let bytes = match fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(e) => return Err(format!("Failed to read {}: {}", path.display(), e)),
+    };
+    if bytes.len() < NONCE_LEN {
+        return Err(format!("{} is corrupt", path.display()));
+    }
+    let (nonce, ciphertext) = bytes.split_at(NONCE_LEN);
+    let plaintext = fallback_cipher()?
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| format!("Failed to decrypt {}", path.display()))?;
+    serde_json::from_slice(&plaintext).map_err(|e| e.to_string())
+}
+
+fn write_fallback(secrets: &BTreeMap<String, String>) -> Result<(), String> {
+    let path = fallback_dir()?.join(FALLBACK_FILE);
+    let plaintext = serde_json::to_vec(secrets).map_err(|e| e.to_string())?;
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = fallback_cipher()?
+        .encrypt(&nonce, plaintext.as_slice())
+        .map_err(|_| "Failed to encrypt secrets".to_string())?;
+    let mut bytes = nonce.to_vec();
+    bytes.extend_from_slice(&ciphertext);
+    write_private(&path, &bytes)
+}
+
+/// Write a file only the current user can read.
+fn write_private(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    use std::io::Write;
+    options
+        .open(path)
+        .and_then(|mut file| file.write_all(bytes))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
@@ -0,0 +1,433 @@
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::db::queries::{self, Item};
+
+const ARMOR_BEGIN: &str = "-----BEGIN LYNXNOTE ENCRYPTED NOTE-----";
+const ARMOR_END: &str = "-----END LYNXNOTE ENCRYPTED NOTE-----";
+const FORMAT_VERSION: &str = "1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const WRAP: usize = 76;
+
+const SALT_SETTING: &str = "vault_salt";
+const CHECK_SETTING: &str = "vault_check";
+const AUTO_LOCK_SETTING: &str = "vault_auto_lock_minutes";
+pub const DEFAULT_AUTO_LOCK_MINUTES: u64 = 10;
+
+/// Encrypted under the vault key so a wrong passphrase is caught at unlock.
+const CHECK_AAD: &[u8] = b"lynxnote-vault";
+const CHECK_TEXT: &[u8] = b"ok";
+
+const LOCKED: &str = "The vault is locked";
+
+/// An encrypted body as stored in the markdown file: the Argon2id salt the key
+/// was derived with, the XChaCha20-Poly1305 nonce and the ciphertext.
+struct Envelope {
+    salt: Vec<u8>,
+    nonce: Vec<u8>,
+    ciphertext: Vec<u8>,
+}
+
+impl Envelope {
+    fn parse(armored: &str) -> Result<Self, String> {
+        let body = armored
+            .trim()
+            .strip_prefix(ARMOR_BEGIN)
+            .and_then(|b| b.strip_suffix(ARMOR_END))
+            .ok_or("Encrypted body is missing its BEGIN/END lines")?;
+        let (headers, data) = body
+            .trim()
+            .split_once("\n\n")
+            .ok_or("Encrypted body is missing its headers")?;
+
+        let mut version = None;
+        let mut salt = None;
+        let mut nonce = None;
+        for line in headers.lines() {
+            let (name, value) = line.split_once(':').ok_or("Malformed encrypted header")?;
+            let value = value.trim();
+            match name.trim() {
+                "Version" => version = Some(value.to_string()),
+                "Salt" => salt = Some(decode(value)?),
+                "Nonce" => nonce = Some(decode(value)?),
+                _ => {}
+            }
+        }
+        if version.as_deref() != Some(FORMAT_VERSION) {
+            return Err("Unsupported encrypted note version".to_string());
+        }
+        let data: String = data.split_whitespace().collect();
+        let envelope = Self {
+            salt: salt.ok_or("Encrypted body has no salt")?,
+            nonce: nonce.ok_or("Encrypted body has no nonce")?,
+            ciphertext: decode(&data)?,
+        };
+        if envelope.salt.len() != SALT_LEN || envelope.nonce.len() != NONCE_LEN {
+            return Err("Encrypted body has a bad salt or nonce".to_string());
+        }
+        Ok(envelope)
+    }
+
+    fn to_armored(&self) -> String {
+        let data = BASE64.encode(&self.ciphertext);
+        let lines: Vec<&str> = data
+            .as_bytes()
+            .chunks(WRAP)
+            .map(|chunk| std::str::from_utf8(chunk).expect("base64 is ASCII"))
+            .collect();
+        format!(
+            "{}\nVersion: {}\nKDF: argon2id\nCipher: xchacha20-poly1305\nSalt: {}\nNonce: {}\n\n{}\n{}",
+            ARMOR_BEGIN,
+            FORMAT_VERSION,
+            BASE64.encode(&self.salt),
+            BASE64.encode(&self.nonce),
+            lines.join("\n"),
+            ARMOR_END
+        )
+    }
+}
+
+fn decode(value: &str) -> Result<Vec<u8>, String> {
+    BASE64
+        .decode(value)
+        .map_err(|e| format!("Encrypted body is not valid base64: {}", e))
+}
+
+fn derive_key(passphrase: &str, salt: &[u8]) -> Result<Key, String> {
+    let mut key = Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase.as_bytes(), salt, &mut key)
+        .map_err(|e| format!("Key derivation failed: {}", e))?;
+    Ok(key)
+}
+
+fn seal(key: &Key, salt: &[u8], aad: &[u8], plaintext: &[u8]) -> Result<Envelope, String> {
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = XChaCha20Poly1305::new(key)
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad,
+            },
+        )
+        .map_err(|_| "Encryption failed".to_string())?;
+    Ok(Envelope {
+        salt: salt.to_vec(),
+        nonce: nonce.to_vec(),
+        ciphertext,
+    })
+}
+
+fn open(key: &Key, aad: &[u8], envelope: &Envelope) -> Option<Vec<u8>> {
+    XChaCha20Poly1305::new(key)
+        .decrypt(
+            XNonce::from_slice(&envelope.nonce),
+            Payload {
+                msg: &envelope.ciphertext,
+                aad,
+            },
+        )
+        .ok()
+}
+
+/// Minutes of inactivity before the vault locks itself; 0 never locks.
+pub fn auto_lock_minutes(conn: &Connection) -> u64 {
+    queries::get_setting(conn, AUTO_LOCK_SETTING)
+        .ok()
+        .flatten()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_AUTO_LOCK_MINUTES)
+}
+
+pub fn set_auto_lock_minutes(conn: &Connection, minutes: u64) -> Result<(), String> {
+    queries::set_setting(conn, AUTO_LOCK_SETTING, &minutes.to_string()).map_err(|e| e.to_string())
+}
+
+fn timeout(minutes: u64) -> Option<Duration> {
+    (minutes > 0).then(|| Duration::from_secs(minutes * 60))
+}
+
+struct Unlocked {
+    /// Kept while unlocked to derive keys for notes encrypted on other machines.
+    passphrase: String,
+    /// Salt new notes are encrypted with.
+    salt: Vec<u8>,
+    keys: HashMap<Vec<u8>, Key>,
+    last_used: Instant,
+    timeout: Option<Duration>,
+}
+
+impl Unlocked {
+    fn key_for(&mut self, salt: &[u8]) -> Result<Key, String> {
+        if let Some(key) = self.keys.get(salt) {
+            return Ok(*key);
+        }
+        let key = derive_key(&self.passphrase, salt)?;
+        self.keys.insert(salt.to_vec(), key);
+        Ok(key)
+    }
+
+    fn expired(&self) -> bool {
+        self.timeout.is_some_and(|t| self.last_used.elapsed() >= t)
+    }
+}
+
+/// Holds the derived keys for `encrypted: true` items while unlocked. Nothing is
+/// written to disk; locking (or quitting) forgets them.
+#[derive(Default)]
+pub struct Vault {
+    unlocked: Mutex<Option<Unlocked>>,
+}
+
+impl Vault {
+    /// Check `passphrase` and keep the keys in memory. The first unlock on a machine
+    /// adopts the salt of an existing encrypted note, or starts a new vault. `db`
+    /// is only locked to read and save settings, never during key derivation.
+    pub fn unlock(&self, db: &Mutex<Connection>, passphrase: &str) -> Result<(), String> {
+        if passphrase.is_empty() {
+            return Err("Enter a passphrase".to_string());
+        }
+        let (saved, first_encrypted, minutes) = {
+            let conn = db.lock().map_err(|e| e.to_string())?;
+            let saved_salt = queries::get_setting(&conn, SALT_SETTING)
+                .map_err(|e| e.to_string())?
+                .map(|s| decode(&s))
+                .transpose()?;
+            let check = queries::get_setting(&conn, CHECK_SETTING).map_err(|e| e.to_string())?;
+            let saved = saved_salt.zip(check);
+            let first_encrypted = match saved {
+                Some(_) => None,
+                None => queries::first_encrypted_item(&conn).map_err(|e| e.to_string())?,
+            };
+            (saved, first_encrypted, auto_lock_minutes(&conn))
+        };
+
+        let (salt, key) = match saved {
+            Some((salt, check)) => {
+                let key = derive_key(passphrase, &salt)?;
+                open(&key, CHECK_AAD, &Envelope::parse(&check)?).ok_or("Wrong passphrase")?;
+                (salt, key)
+            }
+            None => {
+                let (salt, key) = match first_encrypted {
+                    Some((id, armored)) => {
+                        let envelope = Envelope::parse(&armored)?;
+                        let key = derive_key(passphrase, &envelope.salt)?;
+                        open(&key, id.as_bytes(), &envelope).ok_or("Wrong passphrase")?;
+                        (envelope.salt, key)
+                    }
+                    None => {
+                        let mut salt = vec![0u8; SALT_LEN];
+                        OsRng.fill_bytes(&mut salt);
+                        let key = derive_key(passphrase, &salt)?;
+                        (salt, key)
+                    }
+                };
+                let check = seal(&key, &salt, CHECK_AAD, CHECK_TEXT)?.to_armored();
+                let conn = db.lock().map_err(|e| e.to_string())?;
+                queries::set_setting(&conn, SALT_SETTING, &BASE64.encode(&salt))
+                    .and_then(|_| queries::set_setting(&conn, CHECK_SETTING, &check))
+                    .map_err(|e| e.to_string())?;
+                (salt, key)
+            }
+        };
+
+        let mut keys = HashMap::new();
+        keys.insert(salt.clone(), key);
+        *self.unlocked.lock().map_err(|e| e.to_string())? = Some(Unlocked {
+            passphrase: passphrase.to_string(),
+            salt,
+            keys,
+            last_used: Instant::now(),
+            timeout: timeout(minutes),
+        });
+        Ok(())
+    }
+
+    /// Forget the keys. Returns whether the vault was unlocked.
+    pub fn lock(&self) -> bool {
+        self.unlocked
+            .lock()
+            .map(|mut u| u.take().is_some())
+            .unwrap_or(false)
+    }
+
+    /// Change the auto-lock timeout of the current session, if unlocked.
+    pub fn set_auto_lock(&self, minutes: u64) {
+        if let Ok(mut unlocked) = self.unlocked.lock() {
+            if let Some(u) = unlocked.as_mut() {
+                u.timeout = timeout(minutes);
+            }
+        }
+    }
+
+    /// Whether the keys are held. Only checks; polling this doesn't count as use,
+    /// so it never holds off the auto-lock.
+    pub fn is_unlocked(&self) -> bool {
+        self.unlocked
+            .lock()
+            .map(|u| u.as_ref().is_some_and(|u| !u.expired()))
+            .unwrap_or(false)
+    }
+
+    /// Lock if the auto-lock timeout has passed. Returns whether it just locked.
+    pub fn lock_if_idle(&self) -> bool {
+        let Ok(mut unlocked) = self.unlocked.lock() else {
+            return false;
+        };
+        if unlocked.as_ref().is_some_and(Unlocked::expired) {
+            *unlocked = None;
+            return true;
+        }
+        false
+    }
+
+    /// Run `f` with the keys. `touch` restarts the auto-lock timer; only work on
+    /// a specific item the user opened or saved should, not background listings.
+    fn with_keys<T>(
+        &self,
+        touch: bool,
+        f: impl FnOnce(&mut Unlocked) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let mut guard = self.unlocked.lock().map_err(|e| e.to_string())?;
+        if guard.as_ref().is_some_and(Unlocked::expired) {
+            *guard = None;
+        }
+        let unlocked = guard.as_mut().ok_or(LOCKED)?;
+        if touch {
+            unlocked.last_used = Instant::now();
+        }
+        f(unlocked)
+    }
+
+    /// Encrypt an item's body for its markdown file. The item id is bound into the
+    /// ciphertext, so a body pasted into another item's file won't decrypt.
+    pub fn encrypt(&self, item_id: &str, plaintext: &str) -> Result<String, String> {
+        self.with_keys(true, |u| {
+            let salt = u.salt.clone();
+            let key = u.key_for(&salt)?;
+            Ok(seal(&key, &salt, item_id.as_bytes(), plaintext.as_bytes())?.to_armored())
+        })
+    }
+
+    pub fn decrypt(&self, item_id: &str, armored: &str) -> Result<String, String> {
+        self.open_item(item_id, armored, true)
+    }
+
+    fn open_item(&self, item_id: &str, armored: &str, touch: bool) -> Result<String, String> {
+        let envelope = Envelope::parse(armored)?;
+        let plaintext = self.with_keys(touch, |u| {
+            let key = u.key_for(&envelope.salt)?;
+            open(&key, item_id.as_bytes(), &envelope)
+                .ok_or_else(|| "This note was encrypted with a different passphrase".to_string())
+        })?;
+        String::from_utf8(plaintext).map_err(|e| e.to_string())
+    }
+
+    /// The plaintext body of `item`, decrypting it if it is encrypted.
+    pub fn content(&self, item: &Item) -> Result<String, String> {
+        if item.encrypted {
+            self.decrypt(&item.id, &item.ciphertext)
+        } else {
+            Ok(item.content.clone())
+        }
+    }
+
+    /// Fill in the content of encrypted items while unlocked. Locked or unreadable
+    /// items keep an empty body. Listings don't restart the auto-lock timer.
+    pub fn reveal(&self, items: &mut [Item]) {
+        if !items.iter().any(|i| i.encrypted) || !self.is_unlocked() {
+            return;
+        }
+        for item in items.iter_mut().filter(|i| i.encrypted) {
+            match self.open_item(&item.id, &item.ciphertext, false) {
+                Ok(content) => item.content = content,
+                Err(e) => eprintln!("Failed to decrypt {}: {}", item.id, e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unlocked_vault(timeout: Duration) -> Vault {
+        let salt = vec![7u8; SALT_LEN];
+        let mut keys = HashMap::new();
+        keys.insert(salt.clone(), Key::default());
+        Vault {
+            unlocked: Mutex::new(Some(Unlocked {
+                passphrase: String::new(),
+                salt,
+                keys,
+                last_used: Instant::now(),
+                timeout: Some(timeout),
+            })),
+        }
+    }
+
+    fn encrypted_item(vault: &Vault) -> Item {
+        Item {
+            id: "note".to_string(),
+            title: "Note".to_string(),
+            item_type: "note".to_string(),
+            language: String::new(),
+            tags: Vec::new(),
+            folder: "/".to_string(),
+            description: String::new(),
+            content: String::new(),
+            file_path: String::new(),
+            file_hash: String::new(),
+            created: String::new(),
+            modified: String::new(),
+            trusted: false,
+            pinned: false,
+            slot: None,
+            ai_opt_out: false,
+            encrypted: true,
+            ciphertext: vault.encrypt("note", "hunter22").unwrap(),
+        }
+    }
+
+    #[test]
+    fn status_polling_does_not_hold_off_auto_lock() {
+        let timeout = Duration::from_millis(100);
+        let vault = unlocked_vault(timeout);
+        let mut items = vec![encrypted_item(&vault)];
+
+        // What the status poll and item listings do in the background
+        let until = Instant::now() + timeout * 3;
+        while Instant::now() < until {
+            vault.is_unlocked();
+            vault.reveal(&mut items);
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        assert!(!vault.is_unlocked());
+        assert!(vault.lock_if_idle());
+    }
+
+    #[test]
+    fn opening_an_item_restarts_the_timer() {
+        let timeout = Duration::from_millis(200);
+        let vault = unlocked_vault(timeout);
+        let item = encrypted_item(&vault);
+
+        std::thread::sleep(timeout / 2);
+        assert_eq!(vault.content(&item).unwrap(), "hunter22");
+        std::thread::sleep(timeout / 2 + Duration::from_millis(50));
+
+        assert!(vault.is_unlocked());
+    }
+}
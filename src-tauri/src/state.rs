@@ -1,4 +1,6 @@
 use crate::ai::provider::AiProvider;
+use crate::ai::queue::AiQueue;
+use crate::secrets::vault::Vault;
 use rusqlite::Connection;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
@@ -8,6 +10,8 @@ pub struct AppState {
     pub db: Mutex<Connection>,
     pub data_dir: Mutex<PathBuf>,
     pub ai_provider: RwLock<Option<Arc<dyn AiProvider>>>,
+    pub ai_queue: AiQueue,
+    pub vault: Vault,
 }
 
 impl AppState {
@@ -16,6 +20,8 @@ impl AppState {
             db: Mutex::new(db),
             data_dir: Mutex::new(data_dir),
             ai_provider: RwLock::new(None),
+            ai_queue: AiQueue::default(),
+            vault: Vault::default(),
         }
     }
 }
@@ -0,0 +1,154 @@
+use crate::commands::{ai as ai_commands, favorites, shortcuts};
+use crate::db::queries::{self, Item};
+use crate::state::AppState;
+use tauri::menu::{Menu, MenuBuilder, MenuItem, SubmenuBuilder};
+use tauri::tray::TrayIconBuilder;
+use tauri::{AppHandle, Listener, Manager};
+
+const TRAY_ID: &str = "main";
+const SECTION_SIZE: usize = 8;
+const MAX_LABEL_CHARS: usize = 40;
+
+struct TrayContents {
+    pinned: Vec<Item>,
+    recent: Vec<Item>,
+    most_used: Vec<Item>,
+    ai_status: String,
+}
+
+fn load_contents(app: &AppHandle) -> Result<TrayContents, String> {
+    let state = app.state::<AppState>();
+    let api_key = ai_commands::saved_api_key();
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+
+    let ai_status = match ai_commands::read_ai_config(&db, api_key) {
+        Some(config) if config.model.is_empty() => format!("AI: {}", config.provider),
+        Some(config) => format!("AI: {} · {}", config.provider, config.model),
+        None => "AI: not configured".to_string(),
+    };
+
+    Ok(TrayContents {
+        pinned: queries::pinned_items(&db)
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .take(SECTION_SIZE)
+            .collect(),
+        recent: queries::recently_used_items(&db, SECTION_SIZE).map_err(|e| e.to_string())?,
+        most_used: queries::most_used_items(&db, SECTION_SIZE).map_err(|e| e.to_string())?,
+        ai_status,
+    })
+}
+
+fn item_label(item: &Item) -> String {
+    let title: String = item.title.chars().take(MAX_LABEL_CHARS).collect();
+    if title.len() < item.title.len() {
+        format!("{}…", title)
+    } else {
+        title
+    }
+}
+
+/// The same item can appear in several sections, and menu ids must be unique.
+fn copy_id(section: &str, item: &Item) -> String {
+    format!("copy:{}:{}", section, item.id)
+}
+
+fn build_menu(app: &AppHandle) -> tauri::Result<Menu<tauri::Wry>> {
+    let contents = match load_contents(app) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("Tray: failed to load items: {}", e);
+            TrayContents {
+                pinned: Vec::new(),
+                recent: Vec::new(),
+                most_used: Vec::new(),
+                ai_status: "AI: unknown".to_string(),
+            }
+        }
+    };
+
+    let mut menu = MenuBuilder::new(app);
+
+    if !contents.pinned.is_empty() {
+        for item in &contents.pinned {
+            menu = menu.text(copy_id("pinned", item), item_label(item));
+        }
+        menu = menu.separator();
+    }
+
+    for (section, label, items) in [
+        ("recent", "Recent", &contents.recent),
+        ("most_used", "Most Used", &contents.most_used),
+    ] {
+        if items.is_empty() {
+            continue;
+        }
+        let mut submenu = SubmenuBuilder::new(app, label);
+        for item in items {
+            submenu = submenu.text(copy_id(section, item), item_label(item));
+        }
+        menu = menu.item(&submenu.build()?);
+    }
+
+    let ai_status = MenuItem::with_id(app, "ai_status", &contents.ai_status, false, None::<&str>)?;
+
+    menu.separator()
+        .text("new_from_clipboard", "New from Clipboard")
+        .separator()
+        .item(&ai_status)
+        .text("show", "Show LynxNote")
+        .text("quit", "Quit LynxNote")
+        .build()
+}
+
+/// Create the tray icon and keep its menu in sync with the item list.
+pub fn init(app: &AppHandle) -> tauri::Result<()> {
+    let menu = build_menu(app)?;
+
+    TrayIconBuilder::with_id(TRAY_ID)
+        .menu(&menu)
+        .tooltip("LynxNote")
+        .on_menu_event(|app, event| match event.id.as_ref() {
+            "quit" => {
+                app.exit(0);
+            }
+            "show" => {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                }
+            }
+            "new_from_clipboard" => shortcuts::new_from_clipboard(app),
+            id => {
+                if let Some((_, item_id)) = id
+                    .strip_prefix("copy:")
+                    .and_then(|rest| rest.split_once(':'))
+                {
+                    favorites::copy_by_id(app, item_id);
+                }
+            }
+        })
+        .build(app)?;
+
+    for event in ["items-changed", "usage-changed", "ai-settings-changed"] {
+        let handle = app.clone();
+        app.listen_any(event, move |_| refresh(&handle));
+    }
+
+    Ok(())
+}
+
+pub fn refresh(app: &AppHandle) {
+    let tray = match app.tray_by_id(TRAY_ID) {
+        Some(t) => t,
+        None => return,
+    };
+    match build_menu(app) {
+        Ok(menu) => {
+            if let Err(e) = tray.set_menu(Some(menu)) {
+                eprintln!("Tray: failed to update menu: {}", e);
+            }
+        }
+        Err(e) => eprintln!("Tray: failed to build menu: {}", e),
+    }
+}
@@ -25,7 +25,7 @@ import { QuickAdd } from "./components/editor/QuickAdd";
 import { RecategorizeModal } from "./components/editor/RecategorizeModal";
 import { WelcomeModal } from "./components/editor/WelcomeModal";
 import { DocumentationModal } from "./components/editor/DocumentationModal";
-import { getAiSettings, getSetting } from "./lib/tauri-commands";
+import { getAiSettings, getSetting, recordUsage } from "./lib/tauri-commands";
 import {
   useItems,
   useCreateItem,
@@ -79,6 +79,26 @@ export default function App() {
       unlisten.then((fn) => fn());
     };
   }, [queryClient]);
+
+  useEffect(() => {
+    const unlisten = listen("vocabulary-changed", () => {
+      queryClient.invalidateQueries({ queryKey: ["vocabulary"] });
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [queryClient]);
+
+  // Quick add global shortcut
+  useEffect(() => {
+    const unlisten = listen("quick-add", () => {
+      openQuickAdd();
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [openQuickAdd]);
+
   // Check AI config when recategorize modal opens
   useEffect(() => {
     if (recategorizeOpened) {
@@ -127,6 +147,7 @@ export default function App() {
   };
 
   const handleView = useCallback((item: Item) => {
+    recordUsage(item.id, "open").catch(console.error);
     setViewingItem(item);
     openViewer();
   }, [openViewer]);
@@ -83,7 +83,7 @@ export function DocumentationModal({ opened, onClose }: DocumentationModalProps)
                 <Text size="sm" fw={600}>Item Form Fields</Text>
                 <List size="sm" spacing="xs">
                   <List.Item><Text span fw={600}>Title</Text> — a short, descriptive name for the item</List.Item>
-                  <List.Item><Text span fw={600}>Type</Text> — note, shell, snippet, or config</List.Item>
+                  <List.Item><Text span fw={600}>Type</Text> — note, shell, snippet, config, or a custom type</List.Item>
                   <List.Item><Text span fw={600}>Description</Text> — brief summary of what the item is for</List.Item>
                   <List.Item><Text span fw={600}>Content</Text> — the full content (code, commands, notes)</List.Item>
                   <List.Item><Text span fw={600}>Tags</Text> — comma-separated labels for organization</List.Item>
@@ -7,10 +7,11 @@ import {
   Group,
   Stack,
   Input,
+  Checkbox,
 } from "@mantine/core";
 import { useForm } from "@mantine/form";
 import type { Item, CreateItemInput, ItemType } from "../../types";
-import { ITEM_TYPES, LANGUAGES } from "../../types";
+import { useVocabulary } from "../../hooks/useItems";
 import { findDuplicates } from "../../lib/find-duplicates";
 import { DuplicateWarning } from "./DuplicateWarning";
 import { CodeViewer } from "./CodeViewer";
@@ -26,6 +27,7 @@ export function ItemForm({ item, onSubmit, onCancel, loading }: ItemFormProps) {
   const [duplicates, setDuplicates] = useState<Item[]>([]);
   const [pendingValues, setPendingValues] = useState<CreateItemInput | null>(null);
   const [checking, setChecking] = useState(false);
+  const vocabulary = useVocabulary();
 
   const form = useForm({
     initialValues: {
@@ -36,6 +38,8 @@ export function ItemForm({ item, onSubmit, onCancel, loading }: ItemFormProps) {
       folder: item?.folder && item.folder !== "/" ? item.folder : "",
       description: item?.description || "",
       content: item?.content || "",
+      ai_opt_out: item?.ai_opt_out || false,
+      encrypted: item?.encrypted || false,
     },
     validate: {
       title: (v) => (v.trim().length === 0 ? "Title is required" : null),
@@ -87,12 +91,19 @@ export function ItemForm({ item, onSubmit, onCancel, loading }: ItemFormProps) {
         <Group grow>
           <Select
             label="Type"
-            data={ITEM_TYPES}
+            data={vocabulary.types.map((t) => ({ value: t.name, label: t.label }))}
             {...form.getInputProps("type")}
+            onChange={(value) => {
+              form.setFieldValue("type", (value || "note") as ItemType);
+              const def = vocabulary.types.find((t) => t.name === value);
+              if (def?.default_language && !form.values.language) {
+                form.setFieldValue("language", def.default_language);
+              }
+            }}
           />
           <Select
             label="Language"
-            data={LANGUAGES}
+            data={vocabulary.languages}
             clearable
             searchable
             {...form.getInputProps("language")}
@@ -122,6 +133,16 @@ export function ItemForm({ item, onSubmit, onCancel, loading }: ItemFormProps) {
             maxHeight="300px"
           />
         </Input.Wrapper>
+        <Checkbox
+          label="Never send to AI"
+          description="Skips AI enrichment and recategorization for this item (ai: never)"
+          {...form.getInputProps("ai_opt_out", { type: "checkbox" })}
+        />
+        <Checkbox
+          label="Encrypt"
+          description="Stores the content encrypted with the vault passphrase; needs an unlocked vault (encrypted: true)"
+          {...form.getInputProps("encrypted", { type: "checkbox" })}
+        />
         {duplicates.length > 0 && (
           <DuplicateWarning
             duplicates={duplicates}
@@ -5,12 +5,24 @@ import {
   Group,
   Stack,
   Button,
+  Alert,
+  TextInput,
 } from "@mantine/core";
 import { notifications } from "@mantine/notifications";
 import { writeText } from "@tauri-apps/plugin-clipboard-manager";
-import { IconCopy } from "@tabler/icons-react";
+import { IconCopy, IconKey, IconLock } from "@tabler/icons-react";
 import { CodeViewer } from "./CodeViewer";
-import type { Item } from "../../types";
+import {
+  recordUsage,
+  listSecretFindings,
+  moveSecretToKeyring,
+  expandSecrets,
+  getVaultStatus,
+  getOutgoingLinks,
+  getBacklinks,
+  getRelatedItems,
+} from "../../lib/tauri-commands";
+import type { Item, ItemLink, RelatedItem, SecretFinding } from "../../types";
 import { absoluteDateTime, relativeTime } from "../../lib/format-date";
 
 interface ItemViewerProps {
@@ -25,10 +37,27 @@ const straightenQuotes = (text: string) =>
 
 export function ItemViewer({ item, onClose }: ItemViewerProps) {
   const [workingContent, setWorkingContent] = useState("");
+  const [findings, setFindings] = useState<SecretFinding[]>([]);
+  const [secretNames, setSecretNames] = useState<Record<string, string>>({});
+  const [locked, setLocked] = useState(false);
+  const [outgoing, setOutgoing] = useState<ItemLink[]>([]);
+  const [backlinks, setBacklinks] = useState<ItemLink[]>([]);
+  const [related, setRelated] = useState<RelatedItem[]>([]);
 
   useEffect(() => {
     if (item) {
       setWorkingContent(straightenQuotes(item.content));
+      listSecretFindings(item.id).then(setFindings).catch(console.error);
+      getOutgoingLinks(item.id).then(setOutgoing).catch(console.error);
+      getBacklinks(item.id).then(setBacklinks).catch(console.error);
+      getRelatedItems(item.id, 5).then(setRelated).catch(console.error);
+      if (item.encrypted) {
+        getVaultStatus()
+          .then((s) => setLocked(!s.unlocked))
+          .catch(console.error);
+      } else {
+        setLocked(false);
+      }
     }
   }, [item]);
 
@@ -40,7 +69,8 @@ export function ItemViewer({ item, onClose }: ItemViewerProps) {
 
   const handleCopy = async () => {
     try {
-      await writeText(workingContent);
+      await writeText(await expandSecrets(workingContent));
+      recordUsage(item.id, "copy").catch(console.error);
       notifications.show({ message: "Copied to clipboard" });
     } catch (e) {
       notifications.show({
@@ -50,6 +80,22 @@ export function ItemViewer({ item, onClose }: ItemViewerProps) {
     }
   };
 
+  const handleMoveSecret = async (finding: SecretFinding) => {
+    const name = secretNames[finding.fingerprint]?.trim();
+    if (!name) return;
+    try {
+      const updated = await moveSecretToKeyring(item.id, finding.fingerprint, name);
+      setWorkingContent(straightenQuotes(updated.content));
+      setFindings(await listSecretFindings(item.id));
+      notifications.show({ message: `Moved to the keyring as {{secret:${name}}}` });
+    } catch (e) {
+      notifications.show({
+        message: `Failed to move secret: ${e}`,
+        color: "red",
+      });
+    }
+  };
+
   return (
     <Stack gap="sm">
       <Group gap="xs">
@@ -91,6 +137,95 @@ export function ItemViewer({ item, onClose }: ItemViewerProps) {
         </Text>
       </Group>
 
+      {(outgoing.length > 0 || backlinks.length > 0 || related.length > 0) && (
+        <Stack gap={4}>
+          {outgoing.length > 0 && (
+            <Group gap={4}>
+              <Text size="xs" c="dimmed">
+                Links to
+              </Text>
+              {outgoing.map((link) => (
+                <Badge
+                  key={link.target}
+                  size="xs"
+                  variant="light"
+                  color={link.target_id ? undefined : "gray"}
+                  title={link.target_id ? undefined : "No item matches this link"}
+                >
+                  {link.target_title ?? link.target}
+                </Badge>
+              ))}
+            </Group>
+          )}
+          {backlinks.length > 0 && (
+            <Group gap={4}>
+              <Text size="xs" c="dimmed">
+                Linked from
+              </Text>
+              {backlinks.map((link) => (
+                <Badge key={link.source_id} size="xs" variant="outline">
+                  {link.source_title}
+                </Badge>
+              ))}
+            </Group>
+          )}
+          {related.length > 0 && (
+            <Group gap={4}>
+              <Text size="xs" c="dimmed">
+                Related
+              </Text>
+              {related.map((r) => (
+                <Badge
+                  key={r.id}
+                  size="xs"
+                  variant="dot"
+                  title={`${Math.round(r.score * 100)}% related`}
+                >
+                  {r.title}
+                </Badge>
+              ))}
+            </Group>
+          )}
+        </Stack>
+      )}
+
+      {findings.length > 0 && (
+        <Alert color="yellow" icon={<IconKey size={16} />} title="Possible secrets in this item">
+          <Stack gap={6}>
+            {findings.map((finding) => (
+              <Group key={`${finding.fingerprint}-${finding.line}`} gap="xs">
+                <Text size="sm" style={{ flex: 1 }}>
+                  Line {finding.line}: {finding.rule.replace(/_/g, " ")}
+                </Text>
+                <TextInput
+                  size="xs"
+                  placeholder="DB_PASSWORD"
+                  value={secretNames[finding.fingerprint] ?? ""}
+                  onChange={(e) => {
+                    const value = e.currentTarget.value;
+                    setSecretNames((names) => ({ ...names, [finding.fingerprint]: value }));
+                  }}
+                />
+                <Button
+                  size="xs"
+                  variant="light"
+                  disabled={!secretNames[finding.fingerprint]?.trim()}
+                  onClick={() => handleMoveSecret(finding)}
+                >
+                  Move to keyring
+                </Button>
+              </Group>
+            ))}
+          </Stack>
+        </Alert>
+      )}
+
+      {locked && (
+        <Alert color="gray" icon={<IconLock size={16} />} title="Encrypted">
+          Unlock the vault in Settings to read or copy this item.
+        </Alert>
+      )}
+
       <CodeViewer
         value={workingContent}
         language={item.language}
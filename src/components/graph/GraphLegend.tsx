@@ -1,14 +1,8 @@
 import { Paper, Group, ColorSwatch, Text, Stack } from "@mantine/core";
-import { TYPE_COLORS } from "../../lib/graph-builder";
-
-const LEGEND_ITEMS = [
-  { type: "note", label: "Note" },
-  { type: "shell", label: "Shell" },
-  { type: "snippet", label: "Snippet" },
-  { type: "config", label: "Config" },
-];
+import { useVocabulary } from "../../hooks/useItems";
 
 export function GraphLegend() {
+  const vocabulary = useVocabulary();
   return (
     <Paper
       shadow="sm"
@@ -23,9 +17,9 @@ export function GraphLegend() {
       }}
     >
       <Stack gap={4}>
-        {LEGEND_ITEMS.map(({ type, label }) => (
-          <Group key={type} gap="xs">
-            <ColorSwatch color={TYPE_COLORS[type]} size={14} />
+        {vocabulary.types.map(({ name, label, color }) => (
+          <Group key={name} gap="xs">
+            <ColorSwatch color={color} size={14} />
             <Text size="xs">{label}</Text>
           </Group>
         ))}
@@ -1,10 +1,15 @@
 import { useEffect } from "react";
+import { Button, Menu } from "@mantine/core";
+import { notifications } from "@mantine/notifications";
+import { save } from "@tauri-apps/plugin-dialog";
+import { IconDownload } from "@tabler/icons-react";
 import { SigmaContainer, useLoadGraph, useSigma } from "@react-sigma/core";
 import { useLayoutForceAtlas2 } from "@react-sigma/layout-forceatlas2";
 import "@react-sigma/core/lib/style.css";
 import type Graph from "graphology";
-import type { Item } from "../../types";
+import type { GraphFormat, Item } from "../../types";
 import { useGraphData } from "../../hooks/useGraphData";
+import { exportGraph } from "../../lib/tauri-commands";
 import { GraphEvents } from "./GraphEvents";
 import { GraphLegend } from "./GraphLegend";
 
@@ -49,6 +54,26 @@ function LayoutApplier() {
   return null;
 }
 
+const EXPORT_FORMATS: { format: GraphFormat; label: string; extension: string }[] = [
+  { format: "graphml", label: "GraphML (Gephi, yEd)", extension: "graphml" },
+  { format: "dot", label: "Graphviz DOT", extension: "dot" },
+  { format: "json", label: "JSON Graph Format", extension: "json" },
+];
+
+async function handleExport(items: Item[], format: GraphFormat, extension: string) {
+  const path = await save({
+    defaultPath: `lynxnote-graph.${extension}`,
+    filters: [{ name: format.toUpperCase(), extensions: [extension] }],
+  });
+  if (!path) return;
+  try {
+    await exportGraph(format, { ids: items.map((i) => i.id) }, path);
+    notifications.show({ message: `Graph exported to ${path}` });
+  } catch (e) {
+    notifications.show({ message: `Failed to export graph: ${e}`, color: "red" });
+  }
+}
+
 export function GraphView({ items, onClickNode, height }: GraphViewProps) {
   const graph = useGraphData(items);
 
@@ -81,6 +106,25 @@ export function GraphView({ items, onClickNode, height }: GraphViewProps) {
         <GraphEvents items={items} onClickNode={onClickNode} />
       </SigmaContainer>
       <GraphLegend />
+      <Menu position="bottom-end">
+        <Menu.Target>
+          <Button
+            size="xs"
+            variant="default"
+            leftSection={<IconDownload size={14} />}
+            style={{ position: "absolute", top: 16, right: 16, zIndex: 10 }}
+          >
+            Export
+          </Button>
+        </Menu.Target>
+        <Menu.Dropdown>
+          {EXPORT_FORMATS.map(({ format, label, extension }) => (
+            <Menu.Item key={format} onClick={() => handleExport(items, format, extension)}>
+              {label}
+            </Menu.Item>
+          ))}
+        </Menu.Dropdown>
+      </Menu>
     </div>
   );
 }
@@ -13,26 +13,44 @@ import {
 } from "@mantine/core";
 import { IconSearch } from "@tabler/icons-react";
 import { useSearch } from "../../hooks/useSearch";
-import { writeText } from "@tauri-apps/plugin-clipboard-manager";
+import { copyItem, getPinnedItems, getRecentlyUsed } from "../../lib/tauri-commands";
 import { getCurrentWindow } from "@tauri-apps/api/window";
 import { listen } from "@tauri-apps/api/event";
-// types imported from hooks
+import type { Item } from "../../types";
 
 export function SearchOverlayApp() {
-  const { results, query, search, loading } = useSearch();
+  const { results: searchResults, query, search, loading } = useSearch();
+  const [shortlist, setShortlist] = useState<Item[]>([]);
   const [selectedIndex, setSelectedIndex] = useState(0);
   const inputRef = useRef<HTMLInputElement>(null);
 
+  // With no query, show pinned then recently used items instead of an empty list
+  const results = query ? searchResults : shortlist;
+
+  const loadRecent = useCallback(() => {
+    Promise.all([getPinnedItems(), getRecentlyUsed(10)])
+      .then(([pinned, recent]) => {
+        const pinnedIds = new Set(pinned.map((item) => item.id));
+        setShortlist([...pinned, ...recent.filter((item) => !pinnedIds.has(item.id))]);
+      })
+      .catch((err) => console.error("Failed to load recent items:", err));
+  }, []);
+
+  useEffect(() => {
+    loadRecent();
+  }, [loadRecent]);
+
   // Focus input when window gets focus
   useEffect(() => {
     const unlisten = listen("search-focus", () => {
       inputRef.current?.focus();
       inputRef.current?.select();
+      loadRecent();
     });
     return () => {
       unlisten.then((fn) => fn());
     };
-  }, []);
+  }, [loadRecent]);
 
   // Reset selection when results change
   useEffect(() => {
@@ -54,7 +72,7 @@ export function SearchOverlayApp() {
           e.preventDefault();
           if (results[selectedIndex]) {
             try {
-              await writeText(results[selectedIndex].content);
+              await copyItem(results[selectedIndex].id);
             } catch (err) {
               console.error("Failed to copy to clipboard:", err);
             }
@@ -133,6 +151,11 @@ export function SearchOverlayApp() {
                       {item.language}
                     </Badge>
                   )}
+                  {item.slot !== null && (
+                    <Badge size="xs" variant="filled" color="gray">
+                      ⌘⇧{item.slot}
+                    </Badge>
+                  )}
                 </Group>
               </Paper>
             ))}
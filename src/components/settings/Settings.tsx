@@ -3,23 +3,32 @@ import {
   Stack,
   Select,
   TextInput,
+  Autocomplete,
+  Switch,
   PasswordInput,
   Button,
   Badge,
   Group,
   Text,
   Divider,
+  NumberInput,
 } from "@mantine/core";
 import { notifications } from "@mantine/notifications";
 import { open } from "@tauri-apps/plugin-dialog";
 import {
   getAiSettings,
   setAiSettings,
+  testAiConnection,
+  listAiModels,
   getDataDir,
   setDataDir,
+  getVaultStatus,
+  unlockVault,
+  lockVault,
+  setVaultAutoLock,
 } from "../../lib/tauri-commands";
 import { AI_PROVIDERS } from "../../types";
-import type { AiSettings as AiSettingsType } from "../../types";
+import type { AiSettings as AiSettingsType, ConnectionTest } from "../../types";
 
 function DataDirSettings() {
   const [currentDir, setCurrentDir] = useState("");
@@ -85,13 +94,94 @@ function DataDirSettings() {
   );
 }
 
+function VaultSettings() {
+  const [unlocked, setUnlocked] = useState(false);
+  const [autoLock, setAutoLock] = useState<number>(10);
+  const [passphrase, setPassphrase] = useState("");
+  const [busy, setBusy] = useState(false);
+
+  useEffect(() => {
+    getVaultStatus()
+      .then((s) => {
+        setUnlocked(s.unlocked);
+        setAutoLock(s.auto_lock_minutes);
+      })
+      .catch((e) => console.error("Failed to load vault status:", e));
+  }, []);
+
+  const handleUnlock = async () => {
+    setBusy(true);
+    try {
+      await unlockVault(passphrase);
+      setPassphrase("");
+      setUnlocked(true);
+    } catch (e) {
+      notifications.show({ message: `Failed to unlock: ${e}`, color: "red" });
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const handleLock = async () => {
+    await lockVault().catch(console.error);
+    setUnlocked(false);
+  };
+
+  const handleAutoLock = (value: string | number) => {
+    const minutes = typeof value === "number" ? value : 0;
+    setAutoLock(minutes);
+    setVaultAutoLock(minutes).catch(console.error);
+  };
+
+  return (
+    <Stack>
+      <Group>
+        <Text fw={500}>Encrypted Items</Text>
+        {unlocked ? <Badge color="green">Unlocked</Badge> : <Badge color="gray">Locked</Badge>}
+      </Group>
+      {unlocked ? (
+        <Button variant="light" onClick={handleLock}>
+          Lock Now
+        </Button>
+      ) : (
+        <Group align="end">
+          <PasswordInput
+            label="Passphrase"
+            description="The first unlock sets the passphrase for new encrypted items"
+            value={passphrase}
+            onChange={(e) => setPassphrase(e.currentTarget.value)}
+            style={{ flex: 1 }}
+          />
+          <Button onClick={handleUnlock} loading={busy} disabled={!passphrase}>
+            Unlock
+          </Button>
+        </Group>
+      )}
+      <NumberInput
+        label="Auto-lock after (minutes idle)"
+        description="0 keeps the vault unlocked until you lock it or quit"
+        min={0}
+        value={autoLock}
+        onChange={handleAutoLock}
+      />
+    </Stack>
+  );
+}
+
 export function Settings() {
   const [settings, setSettings] = useState<AiSettingsType | null>(null);
   const [provider, setProvider] = useState("openai");
   const [model, setModel] = useState("");
   const [apiKey, setApiKey] = useState("");
   const [baseUrl, setBaseUrl] = useState("");
+  const [apiVersion, setApiVersion] = useState("");
+  const [authHeader, setAuthHeader] = useState("");
+  const [jsonMode, setJsonMode] = useState(true);
   const [saving, setSaving] = useState(false);
+  const [models, setModels] = useState<string[]>([]);
+  const [loadingModels, setLoadingModels] = useState(false);
+  const [testing, setTesting] = useState(false);
+  const [testResult, setTestResult] = useState<ConnectionTest | null>(null);
 
   useEffect(() => {
     getAiSettings()
@@ -100,6 +190,9 @@ export function Settings() {
         if (s.provider) setProvider(s.provider);
         if (s.model) setModel(s.model);
         if (s.base_url) setBaseUrl(s.base_url);
+        setApiVersion(s.api_version);
+        setAuthHeader(s.auth_header);
+        setJsonMode(s.json_mode);
       })
       .catch((e) => console.error("Failed to load AI settings:", e));
   }, []);
@@ -112,6 +205,9 @@ export function Settings() {
         model,
         api_key: apiKey || undefined,
         base_url: baseUrl || undefined,
+        api_version: apiVersion || undefined,
+        auth_header: authHeader || undefined,
+        json_mode: jsonMode,
       });
       setSettings(result);
       setApiKey("");
@@ -126,12 +222,70 @@ export function Settings() {
     }
   };
 
+  const currentInput = () => ({
+    provider,
+    model: model || defaultModel(),
+    api_key: apiKey || undefined,
+    base_url: baseUrl || undefined,
+    api_version: apiVersion || undefined,
+    auth_header: authHeader || undefined,
+    json_mode: jsonMode,
+  });
+
+  const handleLoadModels = async () => {
+    setLoadingModels(true);
+    try {
+      setModels(await listAiModels(currentInput()));
+    } catch (e) {
+      notifications.show({
+        message: `Failed to list models: ${e}`,
+        color: "red",
+      });
+    } finally {
+      setLoadingModels(false);
+    }
+  };
+
+  const handleTest = async () => {
+    setTesting(true);
+    setTestResult(null);
+    try {
+      setTestResult(await testAiConnection(currentInput()));
+    } catch (e) {
+      notifications.show({
+        message: `Connection test failed: ${e}`,
+        color: "red",
+      });
+    } finally {
+      setTesting(false);
+    }
+  };
+
+  const baseUrlPlaceholder = () => {
+    switch (provider) {
+      case "ollama":
+        return "http://localhost:11434";
+      case "claude":
+        return "https://api.anthropic.com";
+      case "gemini":
+        return "https://generativelanguage.googleapis.com";
+      case "azure_openai":
+        return "https://<resource>.openai.azure.com";
+      case "openai_compatible":
+        return "http://localhost:1234/v1";
+      default:
+        return "https://api.openai.com";
+    }
+  };
+
   const defaultModel = () => {
     switch (provider) {
       case "openai":
         return "gpt-4o-mini";
       case "claude":
         return "claude-sonnet-4-20250514";
+      case "gemini":
+        return "gemini-2.0-flash";
       case "ollama":
         return "llama3.2";
       default:
@@ -145,6 +299,10 @@ export function Settings() {
 
       <Divider my="sm" />
 
+      <VaultSettings />
+
+      <Divider my="sm" />
+
       <Group>
         <Text fw={500}>AI Auto-Categorization</Text>
         {settings?.is_configured ? (
@@ -162,20 +320,29 @@ export function Settings() {
           if (v) {
             setProvider(v);
             if (!model) setModel("");
+            setModels([]);
+            setTestResult(null);
           }
         }}
       />
 
-      <TextInput
-        label="Model"
-        placeholder={defaultModel()}
-        value={model}
-        onChange={(e) => setModel(e.currentTarget.value)}
-      />
+      <Group align="end">
+        <Autocomplete
+          label={provider === "azure_openai" ? "Deployment" : "Model"}
+          placeholder={provider === "azure_openai" ? "Deployment name" : defaultModel()}
+          data={models}
+          value={model}
+          onChange={setModel}
+          style={{ flex: 1 }}
+        />
+        <Button variant="light" onClick={handleLoadModels} loading={loadingModels}>
+          Load Models
+        </Button>
+      </Group>
 
       {provider !== "ollama" && (
         <PasswordInput
-          label="API Key"
+          label={provider === "openai_compatible" ? "API Key (optional)" : "API Key"}
           placeholder={settings?.has_api_key ? "••••••••  (saved)" : "Enter API key"}
           value={apiKey}
           onChange={(e) => setApiKey(e.currentTarget.value)}
@@ -184,20 +351,53 @@ export function Settings() {
 
       <TextInput
         label="Base URL"
-        placeholder={
-          provider === "ollama"
-            ? "http://localhost:11434"
-            : provider === "claude"
-              ? "https://api.anthropic.com"
-              : "https://api.openai.com"
-        }
+        placeholder={baseUrlPlaceholder()}
         value={baseUrl}
         onChange={(e) => setBaseUrl(e.currentTarget.value)}
       />
 
-      <Button onClick={handleSave} loading={saving}>
-        Save Settings
-      </Button>
+      {provider === "azure_openai" && (
+        <TextInput
+          label="API Version"
+          placeholder="2024-10-21"
+          value={apiVersion}
+          onChange={(e) => setApiVersion(e.currentTarget.value)}
+        />
+      )}
+
+      {provider === "openai_compatible" && (
+        <>
+          <TextInput
+            label="Auth Header"
+            description="Authorization sends a Bearer token; any other header gets the key as-is"
+            placeholder="Authorization"
+            value={authHeader}
+            onChange={(e) => setAuthHeader(e.currentTarget.value)}
+          />
+          <Switch
+            label="Request JSON mode (turn off for servers that reject response_format)"
+            checked={jsonMode}
+            onChange={(e) => setJsonMode(e.currentTarget.checked)}
+          />
+        </>
+      )}
+
+      {testResult && (
+        <Text size="sm" c={testResult.ok ? "green" : "red"}>
+          {testResult.ok
+            ? `Connected in ${testResult.latency_ms} ms`
+            : `${testResult.error_kind}: ${testResult.error}`}
+        </Text>
+      )}
+
+      <Group grow>
+        <Button variant="light" onClick={handleTest} loading={testing}>
+          Test Connection
+        </Button>
+        <Button onClick={handleSave} loading={saving}>
+          Save Settings
+        </Button>
+      </Group>
     </Stack>
   );
 }
@@ -0,0 +1,157 @@
+import { useState } from "react";
+import {
+  Modal,
+  Stack,
+  Group,
+  Text,
+  TextInput,
+  MultiSelect,
+  Select,
+  Button,
+  ActionIcon,
+  ScrollArea,
+} from "@mantine/core";
+import { notifications } from "@mantine/notifications";
+import { IconCheck, IconTrash } from "@tabler/icons-react";
+import { renameFolder, mergeFolders, deleteFolder } from "../../lib/tauri-commands";
+
+interface FolderManagerProps {
+  opened: boolean;
+  onClose: () => void;
+  folders: string[];
+}
+
+/** Rename, merge and delete folders, subfolders included, across every item file. */
+export function FolderManager({ opened, onClose, folders }: FolderManagerProps) {
+  const [names, setNames] = useState<Record<string, string>>({});
+  const [sources, setSources] = useState<string[]>([]);
+  const [target, setTarget] = useState("");
+  const [reassignTo, setReassignTo] = useState("/");
+  const [busy, setBusy] = useState(false);
+
+  const editable = folders.filter((f) => f !== "/");
+
+  const run = async (action: () => Promise<number>, message: (n: number) => string) => {
+    setBusy(true);
+    try {
+      const count = await action();
+      notifications.show({ message: message(count) });
+    } catch (e) {
+      notifications.show({ message: `Failed: ${e}`, color: "red" });
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const handleRename = (folder: string) => {
+    const to = names[folder]?.trim();
+    if (!to || to === folder) return;
+    run(
+      () => renameFolder(folder, to),
+      (n) => `Moved ${n} items from ${folder} to ${to}`
+    ).then(() => setNames((prev) => ({ ...prev, [folder]: "" })));
+  };
+
+  const handleMerge = () => {
+    run(
+      () => mergeFolders(sources, target.trim()),
+      (n) => `Moved ${n} items into ${target.trim()}`
+    ).then(() => {
+      setSources([]);
+      setTarget("");
+    });
+  };
+
+  const handleDelete = (folder: string) => {
+    run(
+      () => deleteFolder(folder, reassignTo),
+      (n) => `Deleted ${folder}, moved ${n} items to ${reassignTo}`
+    );
+  };
+
+  return (
+    <Modal opened={opened} onClose={onClose} title="Manage Folders" size="lg">
+      <Stack>
+        <Text fw={500} size="sm">
+          Merge
+        </Text>
+        <Group align="end">
+          <MultiSelect
+            label="Folders"
+            data={editable}
+            value={sources}
+            onChange={setSources}
+            searchable
+            style={{ flex: 1 }}
+          />
+          <TextInput
+            label="Into"
+            placeholder="/containers"
+            value={target}
+            onChange={(e) => setTarget(e.currentTarget.value)}
+          />
+          <Button
+            onClick={handleMerge}
+            loading={busy}
+            disabled={sources.length === 0 || !target.trim()}
+          >
+            Merge
+          </Button>
+        </Group>
+
+        <Group justify="space-between" align="end">
+          <Text fw={500} size="sm">
+            Rename or delete
+          </Text>
+          <Select
+            size="xs"
+            label="Items of deleted folders go to"
+            data={folders.includes("/") ? folders : ["/", ...folders]}
+            value={reassignTo}
+            onChange={(value) => setReassignTo(value ?? "/")}
+            allowDeselect={false}
+          />
+        </Group>
+        <ScrollArea h={320}>
+          <Stack gap={6}>
+            {editable.map((folder) => (
+              <Group key={folder} gap="xs">
+                <Text size="sm" style={{ width: 160 }} truncate>
+                  {folder}
+                </Text>
+                <TextInput
+                  size="xs"
+                  placeholder="New path"
+                  value={names[folder] ?? ""}
+                  onChange={(e) => {
+                    const value = e.currentTarget.value;
+                    setNames((prev) => ({ ...prev, [folder]: value }));
+                  }}
+                  onKeyDown={(e) => e.key === "Enter" && handleRename(folder)}
+                  style={{ flex: 1 }}
+                />
+                <ActionIcon
+                  variant="light"
+                  disabled={busy || !names[folder]?.trim()}
+                  onClick={() => handleRename(folder)}
+                  title="Rename"
+                >
+                  <IconCheck size={14} />
+                </ActionIcon>
+                <ActionIcon
+                  variant="light"
+                  color="red"
+                  disabled={busy || folder === reassignTo}
+                  onClick={() => handleDelete(folder)}
+                  title={`Delete and move items to ${reassignTo}`}
+                >
+                  <IconTrash size={14} />
+                </ActionIcon>
+              </Group>
+            ))}
+          </Stack>
+        </ScrollArea>
+      </Stack>
+    </Modal>
+  );
+}
@@ -1,4 +1,6 @@
+import { useState } from "react";
 import {
+  ActionIcon,
   Stack,
   Text,
   NavLink,
@@ -15,8 +17,11 @@ import {
   IconSettings,
   IconApps,
   IconBook,
+  IconPencil,
 } from "@tabler/icons-react";
-import { useTags, useFolders } from "../../hooks/useItems";
+import { useTags, useFolders, useVocabulary } from "../../hooks/useItems";
+import { TagManager } from "./TagManager";
+import { FolderManager } from "./FolderManager";
 
 interface SidebarProps {
   selectedFolder: string | null;
@@ -30,10 +35,12 @@ interface SidebarProps {
 }
 
 const TYPE_ICONS: Record<string, React.ReactNode> = {
-  note: <IconFileText size={16} />,
-  shell: <IconTerminal size={16} />,
-  snippet: <IconCode size={16} />,
-  config: <IconSettings size={16} />,
+  "file-text": <IconFileText size={16} />,
+  terminal: <IconTerminal size={16} />,
+  code: <IconCode size={16} />,
+  settings: <IconSettings size={16} />,
+  book: <IconBook size={16} />,
+  folder: <IconFolder size={16} />,
 };
 
 export function Sidebar({
@@ -48,6 +55,9 @@ export function Sidebar({
 }: SidebarProps) {
   const { data: tags = [] } = useTags();
   const { data: folders = [] } = useFolders();
+  const vocabulary = useVocabulary();
+  const [managingTags, setManagingTags] = useState(false);
+  const [managingFolders, setManagingFolders] = useState(false);
 
   return (
     <ScrollArea h="100%" style={{ display: "flex", flexDirection: "column" }}>
@@ -61,21 +71,34 @@ export function Sidebar({
           active={selectedType === null}
           onClick={() => onSelectType(null)}
         />
-        {["note", "shell", "snippet", "config"].map((type) => (
+        {vocabulary.types.map(({ name, label, icon, color }) => (
           <NavLink
-            key={type}
-            label={type.charAt(0).toUpperCase() + type.slice(1)}
-            leftSection={TYPE_ICONS[type]}
-            active={selectedType === type}
-            onClick={() => onSelectType(selectedType === type ? null : type)}
+            key={name}
+            label={label}
+            leftSection={<span style={{ color, display: "flex" }}>{TYPE_ICONS[icon] ?? <IconFileText size={16} />}</span>}
+            active={selectedType === name}
+            onClick={() => onSelectType(selectedType === name ? null : name)}
           />
         ))}
 
         <Divider my="xs" />
 
-        <Text size="xs" fw={700} c="dimmed" tt="uppercase">
-          Folders
-        </Text>
+        <Group justify="space-between">
+          <Text size="xs" fw={700} c="dimmed" tt="uppercase">
+            Folders
+          </Text>
+          {folders.some((f) => f !== "/") && (
+            <ActionIcon
+              size="xs"
+              variant="subtle"
+              color="gray"
+              onClick={() => setManagingFolders(true)}
+              title="Manage folders"
+            >
+              <IconPencil size={12} />
+            </ActionIcon>
+          )}
+        </Group>
         <NavLink
           label="All Folders"
           leftSection={<IconApps size={16} />}
@@ -96,9 +119,22 @@ export function Sidebar({
 
         <Divider my="xs" />
 
-        <Text size="xs" fw={700} c="dimmed" tt="uppercase">
-          Tags
-        </Text>
+        <Group justify="space-between">
+          <Text size="xs" fw={700} c="dimmed" tt="uppercase">
+            Tags
+          </Text>
+          {tags.length > 0 && (
+            <ActionIcon
+              size="xs"
+              variant="subtle"
+              color="gray"
+              onClick={() => setManagingTags(true)}
+              title="Manage tags"
+            >
+              <IconPencil size={12} />
+            </ActionIcon>
+          )}
+        </Group>
         <Group gap="xs">
           {tags.map((tag) => (
             <Badge
@@ -132,6 +168,16 @@ export function Sidebar({
           onClick={onOpenSettings}
         />
       </Stack>
+      <TagManager
+        opened={managingTags}
+        onClose={() => setManagingTags(false)}
+        tags={tags}
+      />
+      <FolderManager
+        opened={managingFolders}
+        onClose={() => setManagingFolders(false)}
+        folders={folders}
+      />
     </ScrollArea>
   );
 }
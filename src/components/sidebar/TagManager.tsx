@@ -0,0 +1,320 @@
+import { useEffect, useState } from "react";
+import {
+  Modal,
+  Stack,
+  Group,
+  Text,
+  TextInput,
+  MultiSelect,
+  Button,
+  ActionIcon,
+  ScrollArea,
+  Badge,
+  Divider,
+} from "@mantine/core";
+import { notifications } from "@mantine/notifications";
+import { IconCheck, IconTrash, IconWand, IconPlus } from "@tabler/icons-react";
+import {
+  renameTag,
+  mergeTags,
+  deleteTag,
+  getTagSynonyms,
+  setTagSynonyms,
+  suggestTagMerges,
+} from "../../lib/tauri-commands";
+import type { TagMergeSuggestion, TagSynonyms } from "../../types";
+
+interface TagManagerProps {
+  opened: boolean;
+  onClose: () => void;
+  tags: string[];
+}
+
+/** Rename, merge and delete tags across every item file, and edit tag synonyms. */
+export function TagManager({ opened, onClose, tags }: TagManagerProps) {
+  const [names, setNames] = useState<Record<string, string>>({});
+  const [sources, setSources] = useState<string[]>([]);
+  const [target, setTarget] = useState("");
+  const [busy, setBusy] = useState(false);
+  const [synonyms, setSynonyms] = useState<TagSynonyms>({});
+  const [alias, setAlias] = useState("");
+  const [canonical, setCanonical] = useState("");
+  const [suggestions, setSuggestions] = useState<TagMergeSuggestion[] | null>(null);
+  const [suggesting, setSuggesting] = useState(false);
+
+  useEffect(() => {
+    if (opened) getTagSynonyms().then(setSynonyms).catch(() => setSynonyms({}));
+  }, [opened]);
+
+  const saveSynonyms = async (next: TagSynonyms) => {
+    try {
+      setSynonyms(await setTagSynonyms(next));
+    } catch (e) {
+      notifications.show({ message: `Failed: ${e}`, color: "red" });
+    }
+  };
+
+  const handleAddSynonym = () => {
+    if (!alias.trim() || !canonical.trim()) return;
+    saveSynonyms({ ...synonyms, [alias.trim()]: canonical.trim() }).then(() => {
+      setAlias("");
+      setCanonical("");
+    });
+  };
+
+  const handleRemoveSynonym = (name: string) => {
+    const next = { ...synonyms };
+    delete next[name];
+    saveSynonyms(next);
+  };
+
+  const handleSuggest = async () => {
+    setSuggesting(true);
+    try {
+      setSuggestions(await suggestTagMerges());
+    } catch (e) {
+      notifications.show({ message: `Failed: ${e}`, color: "red" });
+    } finally {
+      setSuggesting(false);
+    }
+  };
+
+  const dismiss = (suggestion: TagMergeSuggestion) =>
+    setSuggestions((prev) => prev?.filter((s) => s !== suggestion) ?? null);
+
+  const applySuggestion = (suggestion: TagMergeSuggestion) => {
+    run(
+      () => mergeTags(suggestion.sources, suggestion.target),
+      (n) => `Merged into "${suggestion.target}" on ${n} items`
+    ).then(() => dismiss(suggestion));
+  };
+
+  const keepAsSynonyms = (suggestion: TagMergeSuggestion) => {
+    const next = { ...synonyms };
+    for (const source of suggestion.sources) next[source] = suggestion.target;
+    saveSynonyms(next).then(() => dismiss(suggestion));
+  };
+
+  const run = async (action: () => Promise<number>, message: (n: number) => string) => {
+    setBusy(true);
+    try {
+      const count = await action();
+      notifications.show({ message: message(count) });
+    } catch (e) {
+      notifications.show({ message: `Failed: ${e}`, color: "red" });
+    } finally {
+      setBusy(false);
+    }
+  };
+
+  const handleRename = (tag: string) => {
+    const to = names[tag]?.trim();
+    if (!to || to === tag) return;
+    run(
+      () => renameTag(tag, to),
+      (n) => `Renamed "${tag}" to "${to}" on ${n} items`
+    ).then(() => setNames((prev) => ({ ...prev, [tag]: "" })));
+  };
+
+  const handleMerge = () => {
+    run(
+      () => mergeTags(sources, target.trim()),
+      (n) => `Merged into "${target.trim()}" on ${n} items`
+    ).then(() => {
+      setSources([]);
+      setTarget("");
+    });
+  };
+
+  const handleDelete = (tag: string) => {
+    run(() => deleteTag(tag), (n) => `Removed "${tag}" from ${n} items`);
+  };
+
+  return (
+    <Modal opened={opened} onClose={onClose} title="Manage Tags" size="lg">
+      <Stack>
+        <Text fw={500} size="sm">
+          Merge
+        </Text>
+        <Group align="end">
+          <MultiSelect
+            label="Tags"
+            data={tags}
+            value={sources}
+            onChange={setSources}
+            searchable
+            style={{ flex: 1 }}
+          />
+          <TextInput
+            label="Into"
+            placeholder="kubernetes"
+            value={target}
+            onChange={(e) => setTarget(e.currentTarget.value)}
+          />
+          <Button
+            onClick={handleMerge}
+            loading={busy}
+            disabled={sources.length === 0 || !target.trim()}
+          >
+            Merge
+          </Button>
+        </Group>
+
+        <Group justify="space-between">
+          <Text fw={500} size="sm">
+            Suggestions
+          </Text>
+          <Button
+            size="xs"
+            variant="light"
+            leftSection={<IconWand size={14} />}
+            onClick={handleSuggest}
+            loading={suggesting}
+            disabled={tags.length < 2}
+          >
+            Suggest merges
+          </Button>
+        </Group>
+        {suggestions?.length === 0 && (
+          <Text size="xs" c="dimmed">
+            No tags look like duplicates
+          </Text>
+        )}
+        {suggestions?.map((suggestion) => (
+          <Group key={suggestion.target} gap="xs">
+            {suggestion.sources.map((source) => (
+              <Badge key={source} variant="light" color="gray">
+                {source}
+              </Badge>
+            ))}
+            <Text size="sm">→</Text>
+            <Badge variant="light">{suggestion.target}</Badge>
+            <Group gap={4} ml="auto">
+              <Button
+                size="compact-xs"
+                onClick={() => applySuggestion(suggestion)}
+                disabled={busy}
+              >
+                Merge
+              </Button>
+              <Button
+                size="compact-xs"
+                variant="light"
+                onClick={() => keepAsSynonyms(suggestion)}
+              >
+                Add synonyms
+              </Button>
+              <Button
+                size="compact-xs"
+                variant="subtle"
+                color="gray"
+                onClick={() => dismiss(suggestion)}
+              >
+                Dismiss
+              </Button>
+            </Group>
+          </Group>
+        ))}
+
+        <Divider />
+
+        <Text fw={500} size="sm">
+          Synonyms
+        </Text>
+        <Text size="xs" c="dimmed">
+          Tags saved or suggested by AI under a synonym are stored under its tag, and
+          searching for either name finds both.
+        </Text>
+        <Group gap="xs">
+          {Object.entries(synonyms).map(([name, tag]) => (
+            <Badge
+              key={name}
+              variant="light"
+              rightSection={
+                <ActionIcon
+                  size="xs"
+                  variant="transparent"
+                  onClick={() => handleRemoveSynonym(name)}
+                  title="Remove synonym"
+                >
+                  <IconTrash size={10} />
+                </ActionIcon>
+              }
+            >
+              {name} → {tag}
+            </Badge>
+          ))}
+        </Group>
+        <Group align="end" gap="xs">
+          <TextInput
+            size="xs"
+            placeholder="k8s"
+            value={alias}
+            onChange={(e) => setAlias(e.currentTarget.value)}
+          />
+          <Text size="sm">→</Text>
+          <TextInput
+            size="xs"
+            placeholder="kubernetes"
+            value={canonical}
+            onChange={(e) => setCanonical(e.currentTarget.value)}
+            onKeyDown={(e) => e.key === "Enter" && handleAddSynonym()}
+          />
+          <ActionIcon
+            variant="light"
+            onClick={handleAddSynonym}
+            disabled={!alias.trim() || !canonical.trim()}
+            title="Add synonym"
+          >
+            <IconPlus size={14} />
+          </ActionIcon>
+        </Group>
+
+        <Divider />
+
+        <Text fw={500} size="sm">
+          Rename or delete
+        </Text>
+        <ScrollArea h={320}>
+          <Stack gap={6}>
+            {tags.map((tag) => (
+              <Group key={tag} gap="xs">
+                <Text size="sm" style={{ width: 140 }} truncate>
+                  {tag}
+                </Text>
+                <TextInput
+                  size="xs"
+                  placeholder="New name"
+                  value={names[tag] ?? ""}
+                  onChange={(e) => {
+                    const value = e.currentTarget.value;
+                    setNames((prev) => ({ ...prev, [tag]: value }));
+                  }}
+                  onKeyDown={(e) => e.key === "Enter" && handleRename(tag)}
+                  style={{ flex: 1 }}
+                />
+                <ActionIcon
+                  variant="light"
+                  disabled={busy || !names[tag]?.trim()}
+                  onClick={() => handleRename(tag)}
+                  title="Rename"
+                >
+                  <IconCheck size={14} />
+                </ActionIcon>
+                <ActionIcon
+                  variant="light"
+                  color="red"
+                  disabled={busy}
+                  onClick={() => handleDelete(tag)}
+                  title="Delete from all items"
+                >
+                  <IconTrash size={14} />
+                </ActionIcon>
+              </Group>
+            ))}
+          </Stack>
+        </ScrollArea>
+      </Stack>
+    </Modal>
+  );
+}
@@ -1,7 +1,23 @@
 import { useMemo } from "react";
-import type { Item } from "../types";
+import { useQuery } from "@tanstack/react-query";
+import type { Item, ItemGraph } from "../types";
 import { buildGraph } from "../lib/graph-builder";
+import { getGraph } from "../lib/tauri-commands";
+import { useVocabulary } from "./useItems";
+
+const EMPTY_GRAPH: ItemGraph = { nodes: [], edges: [] };
 
 export function useGraphData(items: Item[]) {
-  return useMemo(() => buildGraph(items), [items]);
+  const vocabulary = useVocabulary();
+  const typeColors = useMemo(
+    () => Object.fromEntries(vocabulary.types.map((t) => [t.name, t.color])),
+    [vocabulary]
+  );
+  const ids = useMemo(() => items.map((i) => i.id).sort(), [items]);
+  // Under the "items" key so item mutations refetch it too
+  const { data } = useQuery({
+    queryKey: ["items", "graph", ids],
+    queryFn: () => getGraph({ ids }),
+  });
+  return useMemo(() => buildGraph(data ?? EMPTY_GRAPH, typeColors), [data, typeColors]);
 }
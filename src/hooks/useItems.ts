@@ -6,8 +6,10 @@ import {
   deleteItem,
   getAllTags,
   getAllFolders,
+  getVocabulary,
 } from "../lib/tauri-commands";
-import type { CreateItemInput, UpdateItemInput } from "../types";
+import type { CreateItemInput, UpdateItemInput, Vocabulary } from "../types";
+import { ITEM_TYPES, LANGUAGES } from "../types";
 
 export function useItems() {
   return useQuery({
@@ -30,6 +32,25 @@ export function useFolders() {
   });
 }
 
+const DEFAULT_VOCABULARY: Vocabulary = {
+  types: ITEM_TYPES.map(({ value, label }) => ({
+    name: value,
+    label,
+    color: "#a78bfa",
+    default_language: "",
+    icon: "",
+  })),
+  languages: LANGUAGES,
+};
+
+export function useVocabulary() {
+  const query = useQuery({
+    queryKey: ["vocabulary"],
+    queryFn: getVocabulary,
+  });
+  return query.data ?? DEFAULT_VOCABULARY;
+}
+
 export function useCreateItem() {
   const queryClient = useQueryClient();
   return useMutation({
@@ -1,6 +1,5 @@
 import Graph from "graphology";
-import type { Item } from "../types";
-import { tokenize, overlapScore } from "./extract-keywords";
+import type { ItemGraph } from "../types";
 
 const TYPE_COLORS: Record<string, string> = {
   note: "#22d3ee",    // cyan (logo top)
@@ -12,60 +11,35 @@ const TYPE_COLORS: Record<string, string> = {
 const DEFAULT_COLOR = "#a78bfa"; // light violet
 
 /**
- * Build a graphology Graph from items.
- * Nodes are colored by type, sized by tag count.
- * Edges weighted by keyword overlap (60%) + shared tags (40%).
+ * Build a graphology Graph from the graph computed by the backend (`get_graph`).
+ * Nodes are colored by type and sized by their weighted degree.
+ * Edges are weighted by links, content similarity and shared tags.
  */
-export function buildGraph(items: Item[]): Graph {
+export function buildGraph(
+  data: ItemGraph,
+  typeColors: Record<string, string> = TYPE_COLORS
+): Graph {
   const graph = new Graph();
 
-  // Pre-tokenize all items
-  const tokenSets = new Map<string, Set<string>>();
-  for (const item of items) {
-    const text = `${item.title} ${item.description} ${item.content}`;
-    tokenSets.set(item.id, tokenize(text));
-  }
-
-  // Add nodes
-  for (const item of items) {
-    const size = Math.min(5 + item.tags.length * 2, 15);
-    graph.addNode(item.id, {
-      label: item.title,
-      size,
-      color: TYPE_COLORS[item.type] ?? DEFAULT_COLOR,
+  for (const node of data.nodes) {
+    graph.addNode(node.id, {
+      label: node.title,
+      size: Math.min(5 + node.weight * 2, 15),
+      color: typeColors[node.type] ?? DEFAULT_COLOR,
       x: Math.random() * 100,
       y: Math.random() * 100,
-      itemType: item.type,
+      itemType: node.type,
     });
   }
 
-  // Add edges
-  for (let i = 0; i < items.length; i++) {
-    for (let j = i + 1; j < items.length; j++) {
-      const a = items[i];
-      const b = items[j];
-
-      const tokensA = tokenSets.get(a.id)!;
-      const tokensB = tokenSets.get(b.id)!;
-      const kwOverlap = overlapScore(tokensA, tokensB);
-
-      const tagsA = new Set(a.tags);
-      let sharedTags = 0;
-      for (const tag of b.tags) {
-        if (tagsA.has(tag)) sharedTags++;
-      }
-      const tagScore = Math.min(sharedTags * 0.3, 1.0);
-
-      const combined = Math.min(kwOverlap * 0.6 + tagScore * 0.4, 1.0);
-
-      if (combined >= 0.15) {
-        graph.addEdge(a.id, b.id, {
-          weight: combined,
-          size: 1 + combined * 4,
-          color: `rgba(150, 150, 150, ${0.2 + combined * 0.6})`,
-        });
-      }
-    }
+  for (const edge of data.edges) {
+    graph.addEdge(edge.source, edge.target, {
+      weight: edge.weight,
+      size: 1 + edge.weight * 4,
+      color: edge.linked
+        ? "rgba(99, 102, 241, 0.8)"
+        : `rgba(150, 150, 150, ${0.2 + edge.weight * 0.6})`,
+    });
   }
 
   return graph;
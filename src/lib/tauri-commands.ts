@@ -1,10 +1,43 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { Item, CreateItemInput, UpdateItemInput, AiSettings, AiSettingsInput } from "../types";
+import type {
+  Item,
+  CreateItemInput,
+  UpdateItemInput,
+  AiSettings,
+  AiSettingsInput,
+  ConnectionTest,
+  AiUsageReport,
+  AiPrice,
+  AiQueueSettings,
+  AiQueueStatus,
+  RedactionPattern,
+  RedactionLogEntry,
+  SecretFinding,
+  ItemLink,
+  TagSynonyms,
+  TagMergeSuggestion,
+  RelatedItem,
+  GraphFilter,
+  ItemGraph,
+  GraphFormat,
+  VaultStatus,
+  RunItemInput,
+  RunRecord,
+  Placeholder,
+  ShortcutBinding,
+  Vocabulary,
+  PromptTemplate,
+  PromptTestResult,
+} from "../types";
 
 export async function createItem(input: CreateItemInput): Promise<Item> {
   return invoke("create_item", { input });
 }
 
+export async function createItemFromClipboard(): Promise<Item> {
+  return invoke("create_item_from_clipboard");
+}
+
 export async function updateItem(input: UpdateItemInput): Promise<Item> {
   return invoke("update_item", { input });
 }
@@ -57,6 +90,241 @@ export async function setAiSettings(input: AiSettingsInput): Promise<AiSettings>
   return invoke("set_ai_settings", { input });
 }
 
+export async function testAiConnection(input?: AiSettingsInput): Promise<ConnectionTest> {
+  return invoke("test_ai_connection", { input });
+}
+
+export async function listAiModels(input?: AiSettingsInput): Promise<string[]> {
+  return invoke("list_ai_models", { input });
+}
+
+export async function clearAiCache(): Promise<number> {
+  return invoke("clear_ai_cache");
+}
+
+export async function getAiUsage(days?: number): Promise<AiUsageReport> {
+  return invoke("get_ai_usage", { days });
+}
+
+export async function getAiPriceTable(): Promise<Record<string, AiPrice>> {
+  return invoke("get_ai_price_table");
+}
+
+export async function setAiPriceTable(prices: Record<string, AiPrice>): Promise<Record<string, AiPrice>> {
+  return invoke("set_ai_price_table", { prices });
+}
+
+export async function setAiBudget(budgetUsd: number | null): Promise<void> {
+  return invoke("set_ai_budget", { budgetUsd });
+}
+
+export async function listRedactionLog(limit?: number): Promise<RedactionLogEntry[]> {
+  return invoke("list_redaction_log", { limit });
+}
+
+export async function clearRedactionLog(): Promise<number> {
+  return invoke("clear_redaction_log");
+}
+
+export async function listSecretFindings(itemId?: string): Promise<SecretFinding[]> {
+  return invoke("list_secret_findings", { itemId });
+}
+
+export async function moveSecretToKeyring(
+  itemId: string,
+  fingerprint: string,
+  name: string
+): Promise<Item> {
+  return invoke("move_secret_to_keyring", { itemId, fingerprint, name });
+}
+
+export async function expandSecrets(content: string): Promise<string> {
+  return invoke("expand_secrets", { content });
+}
+
+/** Each tag command returns how many items it changed. */
+export async function renameTag(from: string, to: string): Promise<number> {
+  return invoke("rename_tag", { from, to });
+}
+
+export async function mergeTags(sources: string[], target: string): Promise<number> {
+  return invoke("merge_tags", { sources, target });
+}
+
+export async function deleteTag(tag: string): Promise<number> {
+  return invoke("delete_tag", { tag });
+}
+
+export async function getTagSynonyms(): Promise<TagSynonyms> {
+  return invoke("get_tag_synonyms");
+}
+
+export async function setTagSynonyms(synonyms: TagSynonyms): Promise<TagSynonyms> {
+  return invoke("set_tag_synonyms", { synonyms });
+}
+
+/** Only suggests; apply a suggestion with `mergeTags` or `setTagSynonyms`. */
+export async function suggestTagMerges(): Promise<TagMergeSuggestion[]> {
+  return invoke("suggest_tag_merges");
+}
+
+/** Folder commands include subfolders and return how many items they moved. */
+export async function renameFolder(from: string, to: string): Promise<number> {
+  return invoke("rename_folder", { from, to });
+}
+
+export async function mergeFolders(sources: string[], target: string): Promise<number> {
+  return invoke("merge_folders", { sources, target });
+}
+
+export async function deleteFolder(folder: string, reassignTo?: string): Promise<number> {
+  return invoke("delete_folder", { folder, reassignTo });
+}
+
+export async function getOutgoingLinks(id: string): Promise<ItemLink[]> {
+  return invoke("get_outgoing_links", { id });
+}
+
+export async function getBacklinks(id: string): Promise<ItemLink[]> {
+  return invoke("get_backlinks", { id });
+}
+
+export async function getRelatedItems(id: string, limit?: number): Promise<RelatedItem[]> {
+  return invoke("get_related_items", { id, limit });
+}
+
+export async function getGraph(filter?: GraphFilter): Promise<ItemGraph> {
+  return invoke("get_graph", { filter });
+}
+
+/** Serialize the graph; written to `path` when given, and returned either way. */
+export async function exportGraph(
+  format: GraphFormat,
+  filter?: GraphFilter,
+  path?: string
+): Promise<string> {
+  return invoke("export_graph", { format, filter, path });
+}
+
+export async function unlockVault(passphrase: string): Promise<void> {
+  return invoke("unlock_vault", { passphrase });
+}
+
+export async function lockVault(): Promise<void> {
+  return invoke("lock_vault");
+}
+
+export async function getVaultStatus(): Promise<VaultStatus> {
+  return invoke("get_vault_status");
+}
+
+export async function setVaultAutoLock(minutes: number): Promise<void> {
+  return invoke("set_vault_auto_lock", { minutes });
+}
+
+export async function getRedactionPatterns(): Promise<RedactionPattern[]> {
+  return invoke("get_redaction_patterns");
+}
+
+export async function setRedactionPatterns(patterns: RedactionPattern[]): Promise<RedactionPattern[]> {
+  return invoke("set_redaction_patterns", { patterns });
+}
+
+export async function getAiQueueStatus(): Promise<AiQueueStatus> {
+  return invoke("get_ai_queue_status");
+}
+
+export async function setAiQueueSettings(settings: AiQueueSettings): Promise<AiQueueSettings> {
+  return invoke("set_ai_queue_settings", { settings });
+}
+
 export async function recategorizeAll(includeManual: boolean, maxFolders?: number): Promise<void> {
   return invoke("recategorize_all", { includeManual, maxFolders });
 }
+
+export async function runItem(input: RunItemInput): Promise<RunRecord> {
+  return invoke("run_item", { input });
+}
+
+export async function getItemPlaceholders(id: string): Promise<Placeholder[]> {
+  return invoke("get_item_placeholders", { id });
+}
+
+export async function getRunHistory(itemId: string, limit?: number): Promise<RunRecord[]> {
+  return invoke("get_run_history", { itemId, limit });
+}
+
+export type UsageAction = "copy" | "open" | "run";
+
+export async function recordUsage(id: string, action: UsageAction): Promise<void> {
+  return invoke("record_usage", { id, action });
+}
+
+export async function getMostUsed(limit?: number): Promise<Item[]> {
+  return invoke("get_most_used", { limit });
+}
+
+export async function getRecentlyUsed(limit?: number): Promise<Item[]> {
+  return invoke("get_recently_used", { limit });
+}
+
+export async function copyItem(id: string): Promise<void> {
+  return invoke("copy_item", { id });
+}
+
+export async function setPinned(id: string, pinned: boolean): Promise<Item> {
+  return invoke("set_pinned", { id, pinned });
+}
+
+export async function setQuickSlot(id: string, slot: number | null): Promise<Item> {
+  return invoke("set_quick_slot", { id, slot });
+}
+
+export async function getPinnedItems(): Promise<Item[]> {
+  return invoke("get_pinned_items");
+}
+
+export async function getQuickSlots(): Promise<Item[]> {
+  return invoke("get_quick_slots");
+}
+
+export async function getShortcuts(): Promise<ShortcutBinding[]> {
+  return invoke("get_shortcuts");
+}
+
+/** Pass `null` to restore the default binding, or `""` to disable the shortcut. */
+export async function setShortcut(action: string, accelerator: string | null): Promise<ShortcutBinding[]> {
+  return invoke("set_shortcut", { action, accelerator });
+}
+
+export async function getVocabulary(): Promise<Vocabulary> {
+  return invoke("get_vocabulary");
+}
+
+export async function setVocabulary(vocabulary: Vocabulary): Promise<Vocabulary> {
+  return invoke("set_vocabulary", { vocabulary });
+}
+
+export async function resetVocabulary(): Promise<Vocabulary> {
+  return invoke("reset_vocabulary");
+}
+
+export async function listPromptTemplates(): Promise<PromptTemplate[]> {
+  return invoke("list_prompt_templates");
+}
+
+export async function setPromptTemplate(name: string, body: string): Promise<PromptTemplate> {
+  return invoke("set_prompt_template", { name, body });
+}
+
+export async function resetPromptTemplate(name: string): Promise<PromptTemplate> {
+  return invoke("reset_prompt_template", { name });
+}
+
+/**
+ * Pass `body` to try an unsaved edit. The taxonomy template ignores `itemId` and uses
+ * all items; the tag merge template uses all tags.
+ */
+export async function testPrompt(name: string, itemId?: string, body?: string): Promise<PromptTestResult> {
+  return invoke("test_prompt", { name, itemId: itemId ?? null, body: body ?? null });
+}
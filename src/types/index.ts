@@ -11,6 +11,13 @@ export interface Item {
   file_hash: string;
   created: string;
   modified: string;
+  trusted: boolean;
+  pinned: boolean;
+  slot: number | null;
+  /** `ai: never` in the frontmatter: never sent to an AI provider. */
+  ai_opt_out: boolean;
+  /** `encrypted: true`: content is empty unless the vault is unlocked. */
+  encrypted: boolean;
 }
 
 export interface CreateItemInput {
@@ -21,6 +28,8 @@ export interface CreateItemInput {
   folder?: string;
   description?: string;
   content: string;
+  ai_opt_out?: boolean;
+  encrypted?: boolean;
 }
 
 export interface UpdateItemInput {
@@ -32,9 +41,12 @@ export interface UpdateItemInput {
   folder?: string;
   description?: string;
   content?: string;
+  ai_opt_out?: boolean;
+  encrypted?: boolean;
 }
 
-export type ItemType = "shell" | "snippet" | "config" | "note";
+/** Built-in types; teams can add their own via the vocabulary settings. */
+export type ItemType = "shell" | "snippet" | "config" | "note" | (string & {});
 
 export const ITEM_TYPES: { value: ItemType; label: string }[] = [
   { value: "note", label: "Note" },
@@ -43,10 +55,26 @@ export const ITEM_TYPES: { value: ItemType; label: string }[] = [
   { value: "config", label: "Configuration" },
 ];
 
+export interface ItemTypeDef {
+  name: string;
+  label: string;
+  color: string;
+  default_language: string;
+  icon: string;
+}
+
+export interface Vocabulary {
+  types: ItemTypeDef[];
+  languages: string[];
+}
+
 export interface AiSettings {
   provider: string;
   model: string;
   base_url: string;
+  api_version: string;
+  auth_header: string;
+  json_mode: boolean;
   has_api_key: boolean;
   is_configured: boolean;
 }
@@ -56,12 +84,158 @@ export interface AiSettingsInput {
   model: string;
   api_key?: string;
   base_url?: string;
+  api_version?: string;
+  auth_header?: string;
+  json_mode?: boolean;
+}
+
+export interface ConnectionTest {
+  ok: boolean;
+  latency_ms: number;
+  error_kind: "unauthorized" | "rate_limited" | "network" | "server" | "client" | null;
+  error: string | null;
+}
+
+export interface AiUsageRollup {
+  period: string;
+  provider: string;
+  model: string;
+  calls: number;
+  input_tokens: number;
+  output_tokens: number;
+  cost_usd: number;
+}
+
+export interface AiUsageReport {
+  daily: AiUsageRollup[];
+  monthly: AiUsageRollup[];
+  month_to_date_usd: number;
+  monthly_budget_usd: number | null;
+  budget_exceeded: boolean;
+}
+
+/** USD per million tokens, keyed by model name prefix. */
+export interface AiPrice {
+  input_per_mtok: number;
+  output_per_mtok: number;
+}
+
+/** A user-defined secret pattern; with a capture group only the group is masked. */
+export interface RedactionPattern {
+  name: string;
+  pattern: string;
+}
+
+export interface RedactionLogEntry {
+  id: number;
+  provider: string;
+  rule: string;
+  /** First 8 hex characters of the secret's SHA-256, to spot repeats. */
+  fingerprint: string;
+  created: string;
+}
+
+/** Alias → canonical tag, e.g. `{ k8s: "kubernetes" }`. */
+export type TagSynonyms = Record<string, string>;
+
+/** Existing tags the AI thinks mean the same as `target`. */
+export interface TagMergeSuggestion {
+  target: string;
+  sources: string[];
+}
+
+/** A `[[target]]` link; `target_id` is null while no item matches. */
+export interface ItemLink {
+  source_id: string;
+  source_title: string;
+  target: string;
+  target_id: string | null;
+  target_title: string | null;
+}
+
+export interface RelatedItem {
+  id: string;
+  title: string;
+  type: string;
+  folder: string;
+  score: number;
+  text_score: number;
+  tag_score: number;
+}
+
+export interface GraphFilter {
+  /** Only these items, e.g. the ones the list is showing. */
+  ids?: string[];
+  type?: string;
+  /** A folder and its subfolders. */
+  folder?: string;
+  tag?: string;
+  /** Drop similarity edges below this weight (default 0.15); links are always kept. */
+  min_weight?: number;
+}
+
+export interface GraphNode {
+  id: string;
+  title: string;
+  type: string;
+  folder: string;
+  tags: string[];
+  /** Sum of the weights of the node's edges. */
+  weight: number;
+}
+
+export interface GraphEdge {
+  source: string;
+  target: string;
+  weight: number;
+  linked: boolean;
+  text_score: number;
+  tag_score: number;
+}
+
+export type GraphFormat = "graphml" | "dot" | "json";
+
+export interface ItemGraph {
+  nodes: GraphNode[];
+  edges: GraphEdge[];
+}
+
+export interface VaultStatus {
+  unlocked: boolean;
+  /** 0 means the vault stays unlocked until locked by hand. */
+  auto_lock_minutes: number;
+}
+
+/** A credential-looking value the secret scanner found in an item. */
+export interface SecretFinding {
+  item_id: string;
+  item_title: string;
+  rule: string;
+  line: number;
+  fingerprint: string;
+}
+
+export interface AiQueueSettings {
+  workers: number;
+  requests_per_minute: number;
+  batch_size: number;
+}
+
+export interface AiQueueStatus {
+  pending: number;
+  running: number;
+  active_workers: number;
+  settings: AiQueueSettings;
+  budget_exceeded: boolean;
 }
 
 export const AI_PROVIDERS = [
   { value: "openai", label: "OpenAI" },
+  { value: "azure_openai", label: "Azure OpenAI" },
   { value: "claude", label: "Claude" },
+  { value: "gemini", label: "Gemini" },
   { value: "ollama", label: "Ollama" },
+  { value: "openai_compatible", label: "OpenAI-compatible" },
 ];
 
 export interface RecategorizeProgress {
@@ -71,6 +245,42 @@ export interface RecategorizeProgress {
   message: string;
 }
 
+export interface RunItemInput {
+  id: string;
+  values?: Record<string, string>;
+  cwd?: string;
+  timeout_secs?: number;
+}
+
+export interface RunRecord {
+  id: string;
+  item_id: string;
+  exit_code: number | null;
+  timed_out: boolean;
+  duration_ms: number;
+  started: string;
+}
+
+export interface RunOutputEvent {
+  run_id: string;
+  item_id: string;
+  stream: "stdout" | "stderr";
+  line: string;
+}
+
+export interface Placeholder {
+  name: string;
+  default: string | null;
+}
+
+export interface ShortcutBinding {
+  action: string;
+  label: string;
+  accelerator: string | null;
+  default_accelerator: string | null;
+  registered: boolean;
+}
+
 export const LANGUAGES = [
   "bash",
   "javascript",
@@ -89,3 +299,20 @@ export const LANGUAGES = [
   "terraform",
   "other",
 ];
+
+export interface PromptTemplate {
+  name: string;
+  description: string;
+  body: string;
+  version: number;
+  is_default: boolean;
+  variables: string[];
+}
+
+export interface PromptTestResult {
+  system_prompt: string;
+  user_prompt: string;
+  raw: string;
+  parsed: unknown | null;
+  error: string | null;
+}